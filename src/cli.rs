@@ -0,0 +1,575 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{IsTerminal, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::comparison::Comparison;
+use crate::cpu_schedulers::{Process, ContextSwitch, more_than_two_decimal_places};
+use crate::csv;
+use crate::gantt::{SvgGanttChart, terminal_width};
+use crate::metrics::Metric;
+use crate::report;
+use crate::schedule::{Algorithm, ScheduleResult};
+use crate::sweep::{self, Sweep, MAX_MLFQ_LEVELS};
+use crate::trace;
+use crate::tui::Tui;
+use crate::workload::{Arrivals, BurstDistribution, WorkloadGenerator};
+
+/// RR time quantum when `--quantum` is not given.
+const DEFAULT_TIME_QUANTUM: f64 = 2.0;
+/// MLFQ time quanta when `--quanta` is not given.
+const DEFAULT_TIME_QUANTA: [f64; 3] = [2.0, 4.0, 8.0];
+/// Aging rate of `ps_aging` when `--aging` is not given.
+const DEFAULT_AGING_RATE: f64 = 0.1;
+/// α of the exponential average of `psjf` and `psrtf` when `--alpha` is not given.
+const DEFAULT_ALPHA: f64 = 0.5;
+/// Initial guess of the exponential average of `psjf` and `psrtf` when `--guess` is not given.
+const DEFAULT_INITIAL_GUESS: f64 = 5.0;
+
+pub const USAGE: &str = "\
+Usage: rust_os_int2214_library <command> [options]
+
+Commands:
+  schedule <workload>   Run one algorithm on a workload.
+      --algo NAME             fcfs, sjf, ps, ps_aging, rr, srtf, pps, psjf, psrtf or mlfq (default fcfs)
+      --quantum Q             RR time quantum (default 2)
+      --quanta Q1,Q2,...      MLFQ time quanta, one per level (default 2,4,8)
+      --aging RATE            Aging rate of ps_aging (default 0.1)
+      --alpha A               Alpha of the burst predictions of psjf and psrtf (default 0.5)
+      --guess G               Initial guess of the burst predictions of psjf and psrtf (default 5)
+      --context-switch SPEC   none, fixed:C, per_switch:C or split:A:B (default none)
+      --format FORMAT         table, csv, timeline, json, toml, yaml, svg, markdown, html, latex or trace (default table)
+  compare <workload>    Run several algorithms on a workload and rank them.
+      --algos A,B,...         Algorithms to compare (default all of them)
+      --quantum, --quanta, --aging, --alpha, --guess, --context-switch as above
+      --format FORMAT         table, csv or json (default table)
+  sweep <workload>      Run an algorithm for every value of a parameter.
+      --parameter NAME        quantum, levels, aging or context-switch (default quantum)
+      --values VALUES         START:STOP:STEP or V1,V2,... (for levels, up to the largest value)
+      --algo, --quanta, --context-switch as above
+      --metric ID             Metric of the table and charts (default average_waiting_time)
+      --format FORMAT         table, csv, json or svg (default table)
+  generate              Generate a random workload.
+      --count N               Number of processes (default 10)
+      --seed S                Seed (default 0)
+      --arrival-rate R        Poisson arrival rate (default 0.5)
+      --mean-burst B          Mean of the exponential bursts (default 5)
+      --format FORMAT         csv, json, toml or yaml (default csv)
+  tui <workload>        Step through the decisions of a schedule (commands are read line by line).
+      --algo, --quantum, --quanta, --aging, --alpha, --guess, --context-switch as above
+  help                  Show this message.
+
+Every command takes --output FILE to write to a file instead of the standard output.
+Workloads are CSV process tables (`-` reads the standard input), or JSON, TOML and YAML documents.
+JSON, TOML and YAML need the `serde` feature.
+";
+
+/// Why a command failed, with the exit code of the process (from BSD's sysexits.h).
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliError {
+    /// Unknown command or option, or a bad option value.
+    Usage(String),
+    /// The workload cannot be parsed.
+    Input(String),
+    /// A file cannot be read or written.
+    Io(String),
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage(_) => 64,
+            CliError::Input(_) => 65,
+            CliError::Io(_) => 74,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{}\nRun `rust_os_int2214_library help` for the usage.", message),
+            CliError::Input(message) | CliError::Io(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// The options (`--name value` or `--name=value`) and the other arguments of a command.
+struct Arguments {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Arguments {
+    /// Split the arguments, rejecting the options that are not in `allowed`.
+    fn parse(args: &[String], allowed: &[&str]) -> Result<Arguments, CliError> {
+        let mut positional: Vec<String> = Vec::new();
+        let mut options: HashMap<String, String> = HashMap::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let Some(option) = arg.strip_prefix("--") else {
+                positional.push(arg.clone());
+                continue;
+            };
+            let (name, value): (&str, Option<String>) = match option.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (option, None),
+            };
+            if !allowed.contains(&name) && name != "output" {
+                return Err(CliError::Usage(format!("Unknown option `--{}`.", name)));
+            }
+            let value: String = match value.or_else(|| args.next().cloned()) {
+                Some(value) => value,
+                None => return Err(CliError::Usage(format!("Option `--{}` needs a value.", name))),
+            };
+            options.insert(name.to_string(), value);
+        }
+        Ok(Arguments { positional, options })
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    /// The value of an option, or `default` when it is not given.
+    fn value<T: FromStr>(&self, name: &str, default: T) -> Result<T, CliError> {
+        match self.get(name) {
+            None => Ok(default),
+            Some(text) => text.parse::<T>().map_err(|_| CliError::Usage(format!("Invalid value `{}` for `--{}`.", text, name))),
+        }
+    }
+
+    /// A comma separated list of numbers.
+    fn numbers(&self, name: &str, default: &[f64]) -> Result<Vec<f64>, CliError> {
+        match self.get(name) {
+            None => Ok(default.to_vec()),
+            Some(text) => text
+                .split(',')
+                .map(|word| word.trim().parse::<f64>().map_err(|_| CliError::Usage(format!("Invalid number `{}` in `--{}`.", word, name))))
+                .collect(),
+        }
+    }
+
+    fn format(&self, formats: &[&str], default: &str) -> Result<String, CliError> {
+        let format: String = self.get("format").unwrap_or(default).to_lowercase();
+        if !formats.contains(&format.as_str()) {
+            return Err(CliError::Usage(format!("Unknown format `{}` (expected {}).", format, formats.join(", "))));
+        }
+        if !cfg!(feature = "serde") && ["json", "toml", "yaml"].contains(&format.as_str()) {
+            return Err(CliError::Usage(format!("The `{}` format needs the `serde` feature.", format)));
+        }
+        Ok(format)
+    }
+
+    /// Write the output to `--output` if given (and return nothing to print), or return it.
+    fn finish(&self, output: String) -> Result<String, CliError> {
+        match self.get("output") {
+            None => Ok(output),
+            Some(path) => {
+                fs::write(path, output).map_err(|error| CliError::Io(format!("{}: {}", path, error)))?;
+                Ok(String::new())
+            }
+        }
+    }
+}
+
+/// Run a command (the arguments without the program name) and return what to print.
+pub fn run(args: &[String]) -> Result<String, CliError> {
+    let Some(command) = args.first() else {
+        return Err(CliError::Usage(String::from("Missing command.")));
+    };
+    match command.as_str() {
+        "schedule" => schedule(&args[1..]),
+        "compare" => compare(&args[1..]),
+        "sweep" => run_sweep(&args[1..]),
+        "generate" => generate(&args[1..]),
+        "tui" => run_tui(&args[1..]),
+        "help" | "--help" | "-h" => Ok(USAGE.to_string()),
+        "--version" | "-V" => Ok(format!("{} {}\n", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))),
+        _ => Err(CliError::Usage(format!("Unknown command `{}`.", command))),
+    }
+}
+
+/// Read the workload named by the only positional argument.
+fn read_workload(arguments: &Arguments) -> Result<Vec<Process>, CliError> {
+    let path: &str = match arguments.positional.as_slice() {
+        [path] => path,
+        [] => return Err(CliError::Usage(String::from("Missing workload file."))),
+        _ => return Err(CliError::Usage(String::from("Expected a single workload file."))),
+    };
+    let text: String = if path == "-" {
+        let mut text: String = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|error| CliError::Io(format!("standard input: {}", error)))?;
+        text
+    } else {
+        fs::read_to_string(path).map_err(|error| CliError::Io(format!("{}: {}", path, error)))?
+    };
+    let extension: Option<String> = Path::new(path).extension().and_then(|extension| extension.to_str()).map(str::to_lowercase);
+    let processes: Vec<Process> = match extension.as_deref() {
+        Some("json" | "toml" | "yaml" | "yml") => read_document(path, &text)?,
+        _ => csv::read_processes(&text).map_err(|errors| {
+            let errors: Vec<String> = errors.iter().map(|error| format!("{}: {}", path, error)).collect();
+            CliError::Input(errors.join("\n"))
+        })?,
+    };
+    if processes.is_empty() {
+        return Err(CliError::Input(format!("{}: no processes", path)));
+    }
+    Ok(processes)
+}
+
+/// The processes of a workload or experiment document.
+#[cfg(feature = "serde")]
+fn read_document(path: &str, text: &str) -> Result<Vec<Process>, CliError> {
+    use crate::serialization::{Document, Format};
+
+    let format: Format = Format::from_path(Path::new(path)).map_err(CliError::Usage)?;
+    let workload = match Document::from_str(text, format) {
+        Ok(Document::Workload(workload)) => workload,
+        Ok(Document::Experiment(experiment)) => experiment.workload,
+        Ok(_) => return Err(CliError::Input(format!("{}: the document holds no workload", path))),
+        Err(error) => return Err(CliError::Input(format!("{}: {}", path, error))),
+    };
+    workload.to_processes().map_err(|error| CliError::Input(format!("{}: {}", path, error)))
+}
+
+#[cfg(not(feature = "serde"))]
+fn read_document(path: &str, _text: &str) -> Result<Vec<Process>, CliError> {
+    Err(CliError::Usage(format!("{}: JSON, TOML and YAML workloads need the `serde` feature.", path)))
+}
+
+/// A result, comparison or workload in JSON, TOML or YAML.
+#[cfg(feature = "serde")]
+fn write_document(document: crate::serialization::Document, format: &str) -> Result<String, CliError> {
+    use crate::serialization::Format;
+
+    let format: Format = match format {
+        "json" => Format::Json,
+        "toml" => Format::Toml,
+        _ => Format::Yaml,
+    };
+    document.to_string(format).map_err(CliError::Input)
+}
+
+/// The algorithm named `name`, with `--quantum`, `--quanta` or `--aging` as its parameters when the name has none.
+fn parse_algorithm(name: &str, arguments: &Arguments) -> Result<Algorithm, CliError> {
+    let mut text: String = name.replace(':', " ");
+    if text.split_whitespace().count() == 1 {
+        match text.trim().to_lowercase().as_str() {
+            "rr" => text += &format!(" {}", arguments.value("quantum", DEFAULT_TIME_QUANTUM)?),
+            "ps_aging" => text += &format!(" {}", arguments.value("aging", DEFAULT_AGING_RATE)?),
+            "psjf" | "psrtf" => {
+                text += &format!(" {} {}", arguments.value("alpha", DEFAULT_ALPHA)?, arguments.value("guess", DEFAULT_INITIAL_GUESS)?);
+            }
+            "mlfq" => {
+                for time_quantum in arguments.numbers("quanta", &DEFAULT_TIME_QUANTA)? {
+                    text += &format!(" {}", time_quantum);
+                }
+            }
+            _ => {}
+        }
+    }
+    let algorithm: Algorithm = text.parse::<Algorithm>().map_err(CliError::Usage)?;
+    algorithm.check().map_err(CliError::Usage)?;
+    Ok(algorithm)
+}
+
+fn parse_context_switch(arguments: &Arguments) -> Result<ContextSwitch, CliError> {
+    match arguments.get("context-switch") {
+        None => Ok(ContextSwitch::None),
+        Some(text) => text.replace(':', " ").parse::<ContextSwitch>().map_err(CliError::Usage),
+    }
+}
+
+fn schedule(args: &[String]) -> Result<String, CliError> {
+    let arguments: Arguments = Arguments::parse(args, &["algo", "quantum", "quanta", "aging", "alpha", "guess", "context-switch", "format"])?;
+    let format: String = arguments.format(
+        &["table", "csv", "timeline", "json", "toml", "yaml", "svg", "markdown", "html", "latex", "trace"],
+        "table",
+    )?;
+    let algorithm: Algorithm = parse_algorithm(arguments.get("algo").unwrap_or("fcfs"), &arguments)?;
+    let context_switch: ContextSwitch = parse_context_switch(&arguments)?;
+    algorithm.check_context_switch(context_switch).map_err(CliError::Usage)?;
+    let processes: Vec<Process> = read_workload(&arguments)?;
+
+    let result: ScheduleResult = algorithm.run(&processes, context_switch);
+    let output: String = match format.as_str() {
+        "table" => report::write_text(&result, terminal_width()),
+        "csv" => csv::write_results(&result.finished_processes),
+        "timeline" => csv::write_timeline(&result.event_list),
+        "svg" => SvgGanttChart::new().render(&result.event_list),
+        "markdown" => report::write_markdown(&result),
+        "html" => report::write_html(&result),
+        "latex" => report::write_latex(&result),
+        "trace" => trace::write_chrome_trace(&result.finished_processes, &result.event_list),
+        #[cfg(feature = "serde")]
+        "json" | "toml" | "yaml" => write_document(crate::serialization::Document::Result(Box::new(result)), &format)?,
+        _ => unreachable!(),
+    };
+    arguments.finish(output)
+}
+
+fn compare(args: &[String]) -> Result<String, CliError> {
+    let arguments: Arguments = Arguments::parse(args, &["algos", "quantum", "quanta", "aging", "alpha", "guess", "context-switch", "format"])?;
+    let format: String = arguments.format(&["table", "csv", "json"], "table")?;
+    let context_switch: ContextSwitch = parse_context_switch(&arguments)?;
+    let algorithms: Vec<Algorithm> = match arguments.get("algos") {
+        Some(names) => {
+            let algorithms: Vec<Algorithm> =
+                names.split(',').map(|name| parse_algorithm(name, &arguments)).collect::<Result<Vec<Algorithm>, CliError>>()?;
+            for algorithm in algorithms.iter() {
+                algorithm.check_context_switch(context_switch).map_err(CliError::Usage)?;
+            }
+            algorithms
+        }
+        // Without MLFQ when context switches cost something, as it does not model them.
+        None => ["fcfs", "sjf", "ps", "ps_aging", "rr", "srtf", "pps", "psjf", "psrtf", "mlfq"]
+            .iter()
+            .map(|name| parse_algorithm(name, &arguments))
+            .filter(|algorithm| algorithm.as_ref().map_or(true, |algorithm| algorithm.check_context_switch(context_switch).is_ok()))
+            .collect::<Result<Vec<Algorithm>, CliError>>()?,
+    };
+    let processes: Vec<Process> = read_workload(&arguments)?;
+
+    let comparison: Comparison = Comparison::new(processes, &algorithms, context_switch);
+    let output: String = match format.as_str() {
+        "table" => comparison.to_text(),
+        "csv" => comparison.to_csv(),
+        #[cfg(feature = "serde")]
+        "json" => comparison_json(&comparison)?,
+        _ => unreachable!(),
+    };
+    arguments.finish(output)
+}
+
+/// One object per algorithm, with the metrics by id.
+#[cfg(feature = "serde")]
+fn comparison_json(comparison: &Comparison) -> Result<String, CliError> {
+    let rows: Vec<serde_json::Value> = comparison
+        .table()
+        .into_iter()
+        .map(|(algorithm, values)| {
+            let metrics: serde_json::Map<String, serde_json::Value> =
+                Metric::ALL.iter().zip(values).map(|(metric, value)| (metric.id().to_string(), serde_json::json!(value))).collect();
+            serde_json::json!({ "algorithm": algorithm, "metrics": metrics })
+        })
+        .collect();
+    serde_json::to_string_pretty(&rows).map(|json| json + "\n").map_err(|error| CliError::Input(error.to_string()))
+}
+
+/// `START:STOP:STEP` or a comma separated list.
+fn parse_values(arguments: &Arguments) -> Result<Vec<f64>, CliError> {
+    let Some(text) = arguments.get("values") else {
+        return Err(CliError::Usage(String::from("Missing `--values`.")));
+    };
+    let invalid = || CliError::Usage(format!("Invalid values `{}`.", text));
+    if text.contains(':') {
+        let bounds: Vec<f64> = text.split(':').map(|word| word.trim().parse::<f64>()).collect::<Result<Vec<f64>, _>>().map_err(|_| invalid())?;
+        match bounds.as_slice() {
+            [start, stop, step] => {
+                sweep::check_range(*start, *stop, *step).map_err(CliError::Usage)?;
+                Ok(sweep::range(*start, *stop, *step))
+            }
+            _ => Err(invalid()),
+        }
+    } else {
+        let values: Vec<f64> = arguments.numbers("values", &[])?;
+        if values.is_empty() { Err(invalid()) } else { Ok(values) }
+    }
+}
+
+fn run_sweep(args: &[String]) -> Result<String, CliError> {
+    let arguments: Arguments = Arguments::parse(args, &["parameter", "values", "algo", "quanta", "context-switch", "metric", "format"])?;
+    let format: String = arguments.format(&["table", "csv", "json", "svg"], "table")?;
+    let metric_id: &str = arguments.get("metric").unwrap_or("average_waiting_time");
+    let Some(metric) = Metric::ALL.iter().copied().find(|metric| metric.id() == metric_id) else {
+        let ids: Vec<&str> = Metric::ALL.iter().map(|metric| metric.id()).collect();
+        return Err(CliError::Usage(format!("Unknown metric `{}` (expected {}).", metric_id, ids.join(", "))));
+    };
+    let values: Vec<f64> = parse_values(&arguments)?;
+    let context_switch: ContextSwitch = parse_context_switch(&arguments)?;
+    let invalid_values = |what: &str| Err(CliError::Usage(format!("Every value must be {}.", what)));
+
+    let sweep: Sweep = match arguments.get("parameter").unwrap_or("quantum") {
+        "quantum" => {
+            if !values.iter().all(|value| *value > 0.0 && !more_than_two_decimal_places(*value)) {
+                return invalid_values("a positive time quantum with at most two decimal places");
+            }
+            Sweep::time_quantum(&read_workload(&arguments)?, &values, context_switch)
+        }
+        "levels" => {
+            if !values.iter().all(|value| (1.0..=MAX_MLFQ_LEVELS as f64).contains(value) && value.fract() == 0.0) {
+                return invalid_values(&format!("a whole number of levels from 1 to {}", MAX_MLFQ_LEVELS));
+            }
+            let base_quantum: f64 = arguments.numbers("quanta", &DEFAULT_TIME_QUANTA)?[0];
+            if base_quantum <= 0.0 || more_than_two_decimal_places(base_quantum) {
+                return Err(CliError::Usage(format!("Invalid time quantum {}.", base_quantum)));
+            }
+            let max_levels: usize = values.iter().fold(1.0, |max: f64, value| max.max(*value)) as usize;
+            // The quantum doubles on every level.
+            if !(base_quantum * 2f64.powi(max_levels as i32 - 1)).is_finite() {
+                return Err(CliError::Usage(format!("The time quantum of level {} is not finite.", max_levels)));
+            }
+            Sweep::mlfq_levels(&read_workload(&arguments)?, max_levels, base_quantum)
+        }
+        "aging" => {
+            if !values.iter().all(|value| value.is_finite() && *value >= 0.0) {
+                return invalid_values("a finite non negative aging rate");
+            }
+            Sweep::aging_rate(&read_workload(&arguments)?, &values, context_switch)
+        }
+        "context-switch" => {
+            if !values.iter().all(|value| *value >= 0.0 && !more_than_two_decimal_places(*value)) {
+                return invalid_values("a non negative cost with at most two decimal places");
+            }
+            let algorithm: Algorithm = parse_algorithm(arguments.get("algo").unwrap_or("fcfs"), &arguments)?;
+            for value in values.iter() {
+                algorithm.check_context_switch(ContextSwitch::PerSwitch(*value)).map_err(CliError::Usage)?;
+            }
+            Sweep::context_switch_cost(&read_workload(&arguments)?, algorithm, &values)
+        }
+        parameter => return Err(CliError::Usage(format!("Unknown parameter `{}` (expected quantum, levels, aging or context-switch).", parameter))),
+    };
+    let output: String = match format.as_str() {
+        "table" => sweep.to_text(metric),
+        "csv" => sweep.to_csv(),
+        "svg" => sweep.svg_chart(metric, 640, 400),
+        #[cfg(feature = "serde")]
+        "json" => sweep_json(&sweep, metric)?,
+        _ => unreachable!(),
+    };
+    arguments.finish(output)
+}
+
+/// The series of the metric as `[{"<parameter>": value, "<metric>": value}, ...]`.
+#[cfg(feature = "serde")]
+fn sweep_json(sweep: &Sweep, metric: Metric) -> Result<String, CliError> {
+    let points: Vec<serde_json::Value> = sweep
+        .series(metric)
+        .into_iter()
+        .map(|(value, metric_value)| serde_json::json!({ sweep.parameter.as_str(): value, metric.id(): metric_value }))
+        .collect();
+    serde_json::to_string_pretty(&points).map(|json| json + "\n").map_err(|error| CliError::Input(error.to_string()))
+}
+
+fn generate(args: &[String]) -> Result<String, CliError> {
+    let arguments: Arguments = Arguments::parse(args, &["count", "seed", "arrival-rate", "mean-burst", "format"])?;
+    if !arguments.positional.is_empty() {
+        return Err(CliError::Usage(format!("Unexpected argument `{}`.", arguments.positional[0])));
+    }
+    let format: String = arguments.format(&["csv", "json", "toml", "yaml"], "csv")?;
+    let count: usize = arguments.value("count", 10)?;
+    let seed: u64 = arguments.value("seed", 0)?;
+    let rate: f64 = arguments.value("arrival-rate", 0.5)?;
+    let mean: f64 = arguments.value("mean-burst", 5.0)?;
+    let arrivals: Arrivals = Arrivals::Poisson { rate };
+    let bursts: BurstDistribution = BurstDistribution::Exponential { mean };
+    arrivals.validate().and_then(|_| bursts.validate()).map_err(CliError::Usage)?;
+
+    let mut generator: WorkloadGenerator = WorkloadGenerator::new(seed, count);
+    generator.set_arrivals(arrivals);
+    generator.set_bursts(bursts);
+    let processes: Vec<Process> = generator.generate();
+    let output: String = match format.as_str() {
+        "csv" => csv::write_processes(&processes),
+        #[cfg(feature = "serde")]
+        "json" | "toml" | "yaml" => {
+            write_document(crate::serialization::Document::Workload(crate::serialization::Workload::new(&processes)), &format)?
+        }
+        _ => unreachable!(),
+    };
+    arguments.finish(output)
+}
+
+fn run_tui(args: &[String]) -> Result<String, CliError> {
+    let arguments: Arguments = Arguments::parse(args, &["algo", "quantum", "quanta", "aging", "alpha", "guess", "context-switch"])?;
+    let algorithm: Algorithm = parse_algorithm(arguments.get("algo").unwrap_or("fcfs"), &arguments)?;
+    let context_switch: ContextSwitch = parse_context_switch(&arguments)?;
+    algorithm.check_context_switch(context_switch).map_err(CliError::Usage)?;
+    if arguments.positional.first().map(String::as_str) == Some("-") {
+        return Err(CliError::Usage(String::from("The TUI reads its commands from the standard input, so the workload must be a file.")));
+    }
+    let processes: Vec<Process> = read_workload(&arguments)?;
+
+    let mut tui: Tui = Tui::new(processes, algorithm, context_switch);
+    tui.set_width(terminal_width());
+    let stdout = std::io::stdout();
+    let clear: bool = stdout.is_terminal();
+    tui.run(std::io::stdin().lock(), stdout.lock(), clear)
+        .map_err(|error| CliError::Io(format!("terminal: {}", error)))?;
+    Ok(String::new())
+}
+
+// Test every command on a generated workload, and the errors on bad input.
+#[test]
+fn test() {
+    let run_words = |words: &str| run(&words.split_whitespace().map(String::from).collect::<Vec<String>>());
+    let directory = std::env::temp_dir().join(format!("rust_os_int2214_cli_{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let workload = directory.join("workload.csv");
+    let workload: &str = workload.to_str().unwrap();
+
+    // generate --output writes the file and prints nothing.
+    assert_eq!(run_words(&format!("generate --count 6 --seed 7 --output {}", workload)), Ok(String::new()));
+    let processes: Vec<Process> = csv::load_processes(Path::new(workload)).unwrap();
+    assert_eq!(processes.len(), 6);
+
+    let table: String = run_words(&format!("schedule --algo rr --quantum 2 {}", workload)).unwrap();
+    assert!(table.starts_with("RR (q=2.00)\n\nProcess  Arrival") && table.contains("┌"));
+    let results: String = run_words(&format!("schedule --algo=srtf --context-switch per_switch:0.5 --format csv {}", workload)).unwrap();
+    assert_eq!(results.lines().count(), 7);
+    let svg: String = run_words(&format!("schedule --algo mlfq --quanta 1,2 --format svg {}", workload)).unwrap();
+    assert!(svg.starts_with("<svg"));
+
+    let comparison: String = run_words(&format!("compare --algos fcfs,rr,mlfq --format csv {}", workload)).unwrap();
+    let rows: Vec<&str> = comparison.lines().map(|line| line.split(',').next().unwrap()).collect();
+    assert_eq!(rows, ["algorithm", "FCFS", "RR (q=2.00)", "MLFQ (3 levels)"]);
+    assert!(run_words(&format!("compare {}", workload)).unwrap().contains("Ranking (best first):"));
+    assert!(!run_words(&format!("compare --context-switch fixed:0.5 {}", workload)).unwrap().contains("MLFQ"));
+
+    let sweep: String = run_words(&format!("sweep --values 1:3:0.5 --format csv {}", workload)).unwrap();
+    assert!(sweep.starts_with("time_quantum,average_waiting_time,") && sweep.lines().count() == 6);
+    let sweep: String = run_words(&format!("sweep --parameter levels --values 3 --metric throughput {}", workload)).unwrap();
+    assert!(sweep.starts_with("mlfq_levels\tThroughput\n1.00\t\t"));
+
+    // Errors and their exit codes.
+    let exit_code = |words: &str| run_words(words).unwrap_err().exit_code();
+    assert_eq!(exit_code("frobnicate"), 64);
+    assert_eq!(exit_code(&format!("schedule --algo rr --quantum 0 {}", workload)), 64);
+    assert_eq!(exit_code(&format!("schedule --algo lottery {}", workload)), 64);
+    assert_eq!(exit_code(&format!("schedule --algo psjf --alpha 2 {}", workload)), 64);
+    assert_eq!(exit_code(&format!("compare --algos fcfs,mlfq --context-switch fixed:0.5 {}", workload)), 64);
+    assert_eq!(exit_code(&format!("schedule --colour red {}", workload)), 64);
+    assert_eq!(exit_code(&format!("sweep --values 3:1:1 {}", workload)), 64);
+    assert_eq!(exit_code(&format!("sweep --values 0:1e9:1e-9 {}", workload)), 64);
+    assert_eq!(exit_code(&format!("sweep --parameter levels --values 1e12 {}", workload)), 64);
+    assert_eq!(exit_code(&format!("sweep --parameter levels --values 64 --quanta 1e300 {}", workload)), 64);
+    assert_eq!(exit_code(&format!("schedule --algo rr --quantum inf {}", workload)), 64);
+    assert_eq!(exit_code("generate --arrival-rate inf"), 64);
+    assert_eq!(exit_code("generate --mean-burst NaN"), 64);
+    assert_eq!(exit_code("schedule"), 64);
+    assert_eq!(exit_code("schedule does/not/exist.csv"), 74);
+    let bad = directory.join("bad.csv");
+    fs::write(&bad, "pid,arrival,burst\n1,0,3\n2,-1,x\n").unwrap();
+    let error: CliError = run_words(&format!("schedule {}", bad.display())).unwrap_err();
+    assert_eq!(error.exit_code(), 65);
+    assert!(error.to_string().contains("bad.csv: line 3:"));
+
+    // The serialized formats round trip through the workload reader.
+    #[cfg(feature = "serde")]
+    {
+        let yaml = directory.join("workload.yaml");
+        run_words(&format!("generate --count 6 --seed 7 --format yaml --output {}", yaml.display())).unwrap();
+        let from_csv: String = run_words(&format!("schedule --format csv {}", workload)).unwrap();
+        assert_eq!(run_words(&format!("schedule --format csv {}", yaml.display())).unwrap(), from_csv);
+        assert!(run_words(&format!("compare --format json {}", workload)).unwrap().contains("\"algorithm\": \"SRTF\""));
+    }
+    #[cfg(not(feature = "serde"))]
+    assert_eq!(exit_code(&format!("schedule --format json {}", workload)), 64);
+
+    fs::remove_dir_all(&directory).unwrap();
+}
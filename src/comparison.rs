@@ -0,0 +1,138 @@
+use crate::cpu_schedulers::{Process, ContextSwitch};
+use crate::metrics::Metric;
+use crate::schedule::{Algorithm, ScheduleResult};
+
+/// One workload run through several algorithms.
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub processes: Vec<Process>,
+    pub results: Vec<ScheduleResult>,
+}
+
+impl Comparison {
+    /// Run every algorithm on its own copy of the processes.
+    pub fn new(processes: Vec<Process>, algorithms: &[Algorithm], context_switch: ContextSwitch) -> Comparison {
+        if algorithms.is_empty() {
+            panic!("Nothing to compare!");
+        }
+        let results: Vec<ScheduleResult> = algorithms
+            .iter()
+            .map(|algorithm| algorithm.run(&processes, context_switch))
+            .collect();
+        Comparison { processes, results }
+    }
+
+    /// Compare every algorithm in `Algorithm::all` (without MLFQ when context switches cost something, as it does not model them).
+    pub fn all(
+        processes: Vec<Process>,
+        time_quantum: f64,
+        time_quanta: Vec<f64>,
+        aging_rate: f64,
+        alpha: f64,
+        initial_guess: f64,
+        context_switch: ContextSwitch,
+    ) -> Comparison {
+        let algorithms: Vec<Algorithm> = Algorithm::all(time_quantum, time_quanta, aging_rate, alpha, initial_guess)
+            .into_iter()
+            .filter(|algorithm| algorithm.check_context_switch(context_switch).is_ok())
+            .collect();
+        Comparison::new(processes, &algorithms, context_switch)
+    }
+
+    /// The results ordered from best to worst on the given metric. Ties keep the order of the algorithms.
+    pub fn ranking(&self, metric: Metric) -> Vec<&ScheduleResult> {
+        let mut ranking: Vec<&ScheduleResult> = self.results.iter().collect();
+        ranking.sort_by(|a, b| {
+            let ordering = metric.value(&a.metrics).partial_cmp(&metric.value(&b.metrics)).unwrap();
+            if metric.higher_is_better() { ordering.reverse() } else { ordering }
+        });
+        ranking
+    }
+
+    /// The result ranked first on the given metric.
+    pub fn best(&self, metric: Metric) -> &ScheduleResult {
+        self.ranking(metric)[0]
+    }
+
+    /// One row per algorithm, one column per metric in `Metric::ALL`.
+    pub fn table(&self) -> Vec<(String, Vec<f64>)> {
+        self.results
+            .iter()
+            .map(|result| (result.algorithm.clone(), Metric::ALL.iter().map(|metric| metric.value(&result.metrics)).collect()))
+            .collect()
+    }
+}
+
+// Visualization
+impl Comparison {
+    /// One row per algorithm, one column per metric in `Metric::ALL`.
+    pub fn to_csv(&self) -> String {
+        let mut csv: String = String::from("algorithm");
+        for metric in Metric::ALL.iter() {
+            csv += &format!(",{}", metric.id());
+        }
+        csv += "\n";
+        for (algorithm, values) in self.table() {
+            csv += &algorithm;
+            for value in values {
+                csv += &format!(",{:.4}", value);
+            }
+            csv += "\n";
+        }
+        csv
+    }
+
+    /// The metrics table followed by the ranking of the algorithms on every metric.
+    pub fn to_text(&self) -> String {
+        let mut text: String = format!("{:<16}", "Algorithm");
+        for metric in Metric::ALL.iter() {
+            text += &format!("\t{}", metric.short_name());
+        }
+        text += "\n";
+        for (algorithm, values) in self.table() {
+            text += &format!("{:<16}", algorithm);
+            for value in values {
+                text += &format!("\t{:.2}", value);
+            }
+            text += "\n";
+        }
+
+        text += "\nRanking (best first):\n";
+        for metric in Metric::ALL.iter() {
+            let names: Vec<String> = self.ranking(*metric).iter().map(|result| result.algorithm.clone()).collect();
+            text += &format!("{}: {}\n", metric.name(), names.join(" > "));
+        }
+        text
+    }
+
+    pub fn print(&self) {
+        print!("{}", self.to_text());
+    }
+}
+
+// Test the comparison with Silberschatz's SRTF example.
+#[test]
+fn test() {
+    let processes: Vec<Process> = vec![
+        Process::new(1, 0.0, 8.0),
+        Process::new(2, 1.0, 4.0),
+        Process::new(3, 2.0, 9.0),
+        Process::new(4, 3.0, 5.0),
+    ];
+    let comparison: Comparison = Comparison::all(processes, 2.0, vec![2.0, 4.0], 0.1, 0.5, 5.0, ContextSwitch::None);
+
+    assert_eq!(comparison.results.len(), 10);
+    assert_eq!(comparison.best(Metric::AverageWaitingTime).algorithm, "SRTF");
+    assert_eq!(comparison.best(Metric::AverageWaitingTime).metrics.waiting_time.mean, 6.5);
+    // MLFQ preempts for new arrivals on the top level, so it answers faster than RR.
+    assert_eq!(comparison.best(Metric::AverageResponseTime).algorithm, "MLFQ (2 levels)");
+    assert_eq!(comparison.ranking(Metric::AverageResponseTime)[1].algorithm, "RR (q=2.00)");
+    // Every algorithm keeps the CPU busy, so they all finish the workload at the same time.
+    for result in comparison.results.iter() {
+        assert_eq!(result.metrics.finish_time, 26.0);
+        assert_eq!(result.metrics.cpu_utilization, 1.0);
+    }
+    let csv: String = comparison.to_csv();
+    assert!(csv.starts_with("algorithm,average_waiting_time,"));
+    assert!(csv.lines().any(|line| line.starts_with("SRTF,6.5000,")));
+}
@@ -0,0 +1,497 @@
+use std::collections::VecDeque;
+
+use super::{Process, ProcessState, Event, EventKind, more_than_two_decimal_places, to_ticks, from_ticks};
+use crate::gantt::GanttChart;
+
+/// A cell of the Ousterhout matrix: `Some((pid, tid))` or an idle core.
+pub type Slot = Option<(u32, u32)>;
+
+/// The Ousterhout matrix at some point in time: one row per time slice, one column per core.
+#[derive(Debug, Clone)]
+pub struct MatrixSnapshot {
+    pub time: f64,
+    pub rows: Vec<Vec<Slot>>,
+}
+
+pub struct GangScheduler {
+    pub processes: Vec<Process>,
+    pub number_of_cores: usize,
+    pub event_list: Vec<Event>,
+    pub matrix_history: Vec<MatrixSnapshot>,
+    pub finished_processes: Vec<Process>,
+    pub quiet: bool,
+}
+
+// Common methods
+impl GangScheduler {
+    pub fn new(processes: Vec<Process>, number_of_cores: usize) -> GangScheduler {
+        if number_of_cores == 0 {
+            panic!("Number of cores must be at least 1.");
+        }
+        GangScheduler {
+            processes,
+            number_of_cores,
+            event_list: Vec::new(),
+            matrix_history: Vec::new(),
+            finished_processes: Vec::new(),
+            quiet: false,
+        }
+    }
+
+    pub fn sort_by_arrival_time(&mut self) {
+        self.processes.sort_by(|a, b| a.arrival_time.partial_cmp(&b.arrival_time).unwrap());
+    }
+
+    /// Burst time of every thread, in ticks. A process without threads has one thread.
+    fn thread_bursts(process: &Process) -> Vec<u64> {
+        if process.threads.is_empty() {
+            vec![to_ticks(process.burst_time)]
+        } else {
+            process.threads.iter().map(|thread| to_ticks(thread.burst_time)).collect()
+        }
+    }
+
+    /// Event name of a thread.
+    fn thread_name(process: &Process, thread: usize) -> String {
+        format!("P{}.T{}", process.pid, thread)
+    }
+
+    /// Record the end of a process once all its threads are done.
+    /// Waiting time = turn around time - longest thread burst (the best the process could do with one core per thread).
+    fn finish_process(&mut self, process: &mut Process, thread_finish_times: &[u64]) {
+        let finish_time: u64 = thread_finish_times.iter().copied().max().unwrap();
+        let longest_burst: u64 = Self::thread_bursts(process).into_iter().max().unwrap();
+        for (thread, &thread_finish_time) in process.threads.iter_mut().zip(thread_finish_times) {
+            thread.finish_time = from_ticks(thread_finish_time);
+            thread.remaining_time = 0.0;
+        }
+        process.finish_time = from_ticks(finish_time);
+        process.section_finish_time = process.finish_time;
+        process.turn_around_time = process.finish_time - process.arrival_time;
+        process.waiting_time = process.turn_around_time - from_ticks(longest_burst);
+        process.remaining_time = 0.0;
+        process.transition(ProcessState::Terminated, process.finish_time);
+        self.finished_processes.push(process.clone());
+    }
+
+    /// Time at which the last process finished.
+    pub fn makespan(&self) -> f64 {
+        self.finished_processes
+            .iter()
+            .map(|process| process.finish_time)
+            .fold(0.0, f64::max)
+    }
+
+    /// Fraction of the makespan the cores spent running threads.
+    pub fn utilization(&self) -> f64 {
+        let busy_time: f64 = self
+            .event_list
+            .iter()
+            .map(|event| event.finish_time - event.start_time)
+            .fold(0.0, |total, time| total + time);
+        let makespan: f64 = self.makespan();
+        if makespan > 0.0 { busy_time / (makespan * self.number_of_cores as f64) } else { 0.0 }
+    }
+
+    /// Fraction of the time a process was running during which all its unfinished threads ran together.
+    /// Gang scheduling keeps it at 1; independent thread scheduling usually does not.
+    pub fn coscheduling_ratio(&self, pid: u32) -> f64 {
+        let process: &Process = match self.finished_processes.iter().find(|process| process.pid == pid) {
+            Some(process) => process,
+            None => return 0.0,
+        };
+        let prefix: String = format!("P{}.", pid);
+        let events: Vec<&Event> = self.event_list.iter().filter(|event| event.name.starts_with(&prefix)).collect();
+
+        // Split the lifetime of the process at every slice boundary.
+        let mut boundaries: Vec<u64> = events
+            .iter()
+            .flat_map(|event| [to_ticks(event.start_time), to_ticks(event.finish_time)])
+            .collect();
+        boundaries.sort_unstable();
+        boundaries.dedup();
+
+        let thread_finish_times: Vec<u64> = if process.threads.is_empty() {
+            vec![to_ticks(process.finish_time)]
+        } else {
+            process.threads.iter().map(|thread| to_ticks(thread.finish_time)).collect()
+        };
+
+        let mut running_time: u64 = 0;
+        let mut coscheduled_time: u64 = 0;
+        for window in boundaries.windows(2) {
+            let (start, finish) = (window[0], window[1]);
+            let running: usize = events
+                .iter()
+                .filter(|event| to_ticks(event.start_time) <= start && to_ticks(event.finish_time) >= finish)
+                .count();
+            let alive: usize = thread_finish_times.iter().filter(|&&time| time >= finish).count();
+            if running > 0 {
+                running_time += finish - start;
+                if running == alive {
+                    coscheduled_time += finish - start;
+                }
+            }
+        }
+        if running_time == 0 { 0.0 } else { coscheduled_time as f64 / running_time as f64 }
+    }
+
+    /// Average co-scheduling ratio of the finished processes.
+    pub fn average_coscheduling_ratio(&self) -> f64 {
+        self.finished_processes
+            .iter()
+            .map(|process| self.coscheduling_ratio(process.pid))
+            .sum::<f64>()
+            / self.finished_processes.len() as f64
+    }
+}
+
+// Visualization
+impl GangScheduler {
+    pub fn print(&mut self) {
+        // Define a processes variable to store the finished processes.
+        let processes: Vec<Process> = self.finished_processes.clone();
+
+        println!("Name\t\tThreads\t\tArrival Time\tBurst Time\tTurn Around Time\tWaiting Time\tFinish Time");
+        for process in processes.iter() {
+            println!(
+                "P{}\t\t{}\t\t{:.2}\t\t{:.2}\t\t{:.2}\t\t\t{:.2}\t\t\t{:.2}",
+                process.pid,
+                process.number_of_threads(),
+                process.arrival_time,
+                process.burst_time,
+                process.turn_around_time,
+                process.waiting_time,
+                process.finish_time
+            );
+        }
+
+        // Calculate average waiting time and average turn around time.
+        let average_waiting_time: f64 = processes.iter().map(|process| process.waiting_time).sum::<f64>()
+            / processes.len() as f64;
+        let average_turn_around_time: f64 = processes.iter().map(|process| process.turn_around_time).sum::<f64>()
+            / processes.len() as f64;
+
+        println!(
+            "Average:\t\t\t\t\t\t\t*{:.2}\t\t\t*{:.2}",
+            average_turn_around_time,
+            average_waiting_time,
+        );
+
+        println!("\nMakespan: {:.2}, utilization: {:.2}%, co-scheduling ratio: {:.2}",
+            self.makespan(), self.utilization() * 100.0, self.average_coscheduling_ratio());
+
+        self.ousterhout_matrix();
+        self.gantt_chart();
+    }
+
+    /// Print every version of the Ousterhout matrix.
+    pub fn ousterhout_matrix(&self) {
+        for snapshot in self.matrix_history.iter() {
+            println!("\nOusterhout matrix at {:.2}:", snapshot.time);
+            let header: Vec<String> = (0..self.number_of_cores).map(|core| format!("Core {}", core)).collect();
+            println!("Slice\t{}", header.join("\t"));
+            for (slice, row) in snapshot.rows.iter().enumerate() {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|slot| match slot {
+                        Some((pid, tid)) => format!("P{}.T{}", pid, tid),
+                        None => "-".to_string(),
+                    })
+                    .collect();
+                println!("{}\t{}", slice, cells.join("\t"));
+            }
+        }
+    }
+
+    /// Horizontal chart of the timeline, one row per core.
+    pub fn gantt_chart(&self) {
+        println!("\n\nGantt Chart:\n{}", GanttChart::new().render(&self.event_list));
+    }
+}
+
+// Algorithms
+impl GangScheduler {
+    // Gang scheduling with an Ousterhout matrix: all threads of a process run in the same time slice.
+    pub fn gang(&mut self, time_quantum: f64) {
+        // Check if time quantum is valid.
+        if time_quantum <= 0.0 || more_than_two_decimal_places(time_quantum) {
+            panic!("Invalid time quantum!");
+        }
+
+        // Sort the processes by arrival time.
+        self.sort_by_arrival_time();
+        let mut processes: Vec<Process> = std::mem::take(&mut self.processes);
+        for process in processes.iter() {
+            if process.number_of_threads() > self.number_of_cores {
+                panic!("Process P{} has more threads than there are cores.", process.pid);
+            }
+        }
+
+        let quantum: u64 = to_ticks(time_quantum);
+        let number_of_processes: usize = processes.len();
+        let mut remaining: Vec<Vec<u64>> = processes.iter().map(Self::thread_bursts).collect();
+        let mut thread_finish_times: Vec<Vec<u64>> = remaining.iter().map(|threads| vec![0; threads.len()]).collect();
+        let mut started: Vec<bool> = vec![false; number_of_processes];
+
+        // The matrix holds (process index, thread index) pairs.
+        let mut matrix: Vec<Vec<Option<(usize, usize)>>> = Vec::new();
+        let mut row: usize = 0;
+
+        let mut current_time: u64 = processes.first().map_or(0, |process| to_ticks(process.arrival_time));
+        let mut next_arrival: usize = 0;
+        let mut number_of_finished: usize = 0;
+
+        while number_of_finished < number_of_processes {
+            // Place the newly arrived processes in the first row with enough free cores.
+            let mut changed: bool = false;
+            while next_arrival < number_of_processes && to_ticks(processes[next_arrival].arrival_time) <= current_time {
+                let number_of_threads: usize = remaining[next_arrival].len();
+                let position: Option<usize> = matrix
+                    .iter()
+                    .position(|cells| cells.iter().filter(|cell| cell.is_none()).count() >= number_of_threads);
+                let cells: &mut Vec<Option<(usize, usize)>> = match position {
+                    Some(position) => &mut matrix[position],
+                    None => {
+                        matrix.push(vec![None; self.number_of_cores]);
+                        matrix.last_mut().unwrap()
+                    }
+                };
+                let free_cells = cells.iter_mut().filter(|cell| cell.is_none()).take(number_of_threads);
+                for (thread, cell) in free_cells.enumerate() {
+                    *cell = Some((next_arrival, thread));
+                }
+                let arrival_time: f64 = processes[next_arrival].arrival_time;
+                processes[next_arrival].transition(ProcessState::Ready, arrival_time);
+                next_arrival += 1;
+                changed = true;
+            }
+            if changed {
+                self.snapshot(current_time, &matrix, &processes);
+            }
+
+            // Nothing to run: jump to the next arrival.
+            if matrix.is_empty() {
+                current_time = to_ticks(processes[next_arrival].arrival_time);
+                continue;
+            }
+            row %= matrix.len();
+
+            // Run the whole row for one quantum (or less if all its threads finish earlier).
+            let longest: u64 = matrix[row]
+                .iter()
+                .flatten()
+                .map(|&(index, thread)| remaining[index][thread])
+                .max()
+                .unwrap();
+            let slice: u64 = quantum.min(longest);
+            let mut in_row: Vec<usize> = matrix[row].iter().flatten().map(|&(index, _)| index).collect();
+            in_row.sort_unstable();
+            in_row.dedup();
+            for &index in in_row.iter() {
+                processes[index].transition(ProcessState::Running, from_ticks(current_time));
+            }
+            for (core, cell) in matrix[row].iter().enumerate() {
+                if let Some((index, thread)) = *cell {
+                    if !started[index] {
+                        processes[index].start_time = from_ticks(current_time);
+                        started[index] = true;
+                    }
+                    let time_run: u64 = slice.min(remaining[index][thread]);
+                    if time_run == 0 {
+                        continue;
+                    }
+                    remaining[index][thread] -= time_run;
+                    if remaining[index][thread] == 0 {
+                        thread_finish_times[index][thread] = current_time + time_run;
+                    }
+                    self.event_list.push(Event {
+                        name: Self::thread_name(&processes[index], thread),
+                        kind: EventKind::Process,
+                        core,
+                        start_time: from_ticks(current_time),
+                        finish_time: from_ticks(current_time + time_run),
+                    });
+                }
+            }
+            current_time += slice;
+
+            // Remove the processes whose threads are all done, the others go back to ready.
+            let (done, not_done): (Vec<usize>, Vec<usize>) = in_row
+                .into_iter()
+                .partition(|&index| remaining[index].iter().all(|&time| time == 0));
+            for &index in not_done.iter() {
+                processes[index].transition(ProcessState::Ready, from_ticks(current_time));
+            }
+            for &index in done.iter() {
+                for cell in matrix[row].iter_mut() {
+                    if cell.is_some_and(|(cell_index, _)| cell_index == index) {
+                        *cell = None;
+                    }
+                }
+                let mut process: Process = processes[index].clone();
+                self.finish_process(&mut process, &thread_finish_times[index]);
+                number_of_finished += 1;
+            }
+
+            // Drop the row if it is empty, otherwise move to the next one.
+            if matrix[row].iter().all(|cell| cell.is_none()) {
+                matrix.remove(row);
+            } else {
+                row += 1;
+            }
+            if !done.is_empty() {
+                self.snapshot(current_time, &matrix, &processes);
+            }
+        }
+
+        // Print the result.
+        if !self.quiet {
+            self.print();
+        }
+    }
+
+    // Independent thread scheduling: every thread waits in one global Round Robin queue.
+    pub fn independent(&mut self, time_quantum: f64) {
+        // Check if time quantum is valid.
+        if time_quantum <= 0.0 || more_than_two_decimal_places(time_quantum) {
+            panic!("Invalid time quantum!");
+        }
+
+        // Sort the processes by arrival time.
+        self.sort_by_arrival_time();
+        let mut processes: Vec<Process> = std::mem::take(&mut self.processes);
+
+        let quantum: u64 = to_ticks(time_quantum);
+        let number_of_processes: usize = processes.len();
+        let mut remaining: Vec<Vec<u64>> = processes.iter().map(Self::thread_bursts).collect();
+        let mut thread_finish_times: Vec<Vec<u64>> = remaining.iter().map(|threads| vec![0; threads.len()]).collect();
+        let mut started: Vec<bool> = vec![false; number_of_processes];
+
+        // The queue holds (process index, thread index) pairs, the cores their running slice.
+        let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
+        let mut running: Vec<Option<(usize, usize, u64, u64)>> = vec![None; self.number_of_cores];
+        let mut requeued: Vec<(usize, usize)> = Vec::new();
+        let mut running_threads: Vec<usize> = vec![0; number_of_processes];
+
+        let mut current_time: u64 = processes.first().map_or(0, |process| to_ticks(process.arrival_time));
+        let mut next_arrival: usize = 0;
+        let mut number_of_finished: usize = 0;
+
+        while number_of_finished < number_of_processes {
+            // Queue the threads of the newly arrived processes, then the preempted threads.
+            while next_arrival < number_of_processes && to_ticks(processes[next_arrival].arrival_time) <= current_time {
+                for thread in 0..remaining[next_arrival].len() {
+                    queue.push_back((next_arrival, thread));
+                }
+                let arrival_time: f64 = processes[next_arrival].arrival_time;
+                processes[next_arrival].transition(ProcessState::Ready, arrival_time);
+                next_arrival += 1;
+            }
+            queue.extend(requeued.drain(..));
+
+            // Dispatch a thread on every idle core.
+            for slot in running.iter_mut().filter(|slot| slot.is_none()) {
+                if let Some((index, thread)) = queue.pop_front() {
+                    if !started[index] {
+                        processes[index].start_time = from_ticks(current_time);
+                        started[index] = true;
+                    }
+                    // The process is running as long as one of its threads is.
+                    if running_threads[index] == 0 {
+                        processes[index].transition(ProcessState::Running, from_ticks(current_time));
+                    }
+                    running_threads[index] += 1;
+                    let time_run: u64 = quantum.min(remaining[index][thread]);
+                    *slot = Some((index, thread, current_time, current_time + time_run));
+                }
+            }
+
+            // Find the next time a slice ends or a process arrives.
+            let mut next_time: u64 = u64::MAX;
+            for &(_, _, _, slice_end) in running.iter().flatten() {
+                next_time = next_time.min(slice_end);
+            }
+            if next_arrival < number_of_processes {
+                next_time = next_time.min(to_ticks(processes[next_arrival].arrival_time));
+            }
+            current_time = next_time;
+
+            // End the slices that finish now.
+            for (core, slot) in running.iter_mut().enumerate() {
+                let (index, thread, slice_start, slice_end) = match *slot {
+                    Some(slice) if slice.3 == current_time => slice,
+                    _ => continue,
+                };
+                *slot = None;
+                running_threads[index] -= 1;
+                remaining[index][thread] -= slice_end - slice_start;
+                self.event_list.push(Event {
+                    name: Self::thread_name(&processes[index], thread),
+                    kind: EventKind::Process,
+                    core,
+                    start_time: from_ticks(slice_start),
+                    finish_time: from_ticks(slice_end),
+                });
+
+                if remaining[index][thread] > 0 {
+                    requeued.push((index, thread));
+                } else {
+                    thread_finish_times[index][thread] = slice_end;
+                }
+                if remaining[index].iter().all(|&time| time == 0) {
+                    let mut process: Process = processes[index].clone();
+                    self.finish_process(&mut process, &thread_finish_times[index]);
+                    number_of_finished += 1;
+                } else if running_threads[index] == 0 {
+                    processes[index].transition(ProcessState::Ready, from_ticks(current_time));
+                }
+            }
+        }
+
+        // Print the result.
+        if !self.quiet {
+            self.print();
+        }
+    }
+
+    /// Save a copy of the matrix with pids and tids.
+    fn snapshot(&mut self, current_time: u64, matrix: &[Vec<Option<(usize, usize)>>], processes: &[Process]) {
+        self.matrix_history.push(MatrixSnapshot {
+            time: from_ticks(current_time),
+            rows: matrix
+                .iter()
+                .map(|cells| {
+                    cells
+                        .iter()
+                        .map(|cell| cell.map(|(index, thread)| (processes[index].pid, thread as u32)))
+                        .collect()
+                })
+                .collect(),
+        });
+    }
+}
+
+// Test gang scheduling against independent thread scheduling.
+#[test]
+fn test() {
+    let workload = || -> Vec<Process> {
+        vec![
+            Process::new_with_threads(1, 0.0, vec![2.0, 2.0]),
+            Process::new_with_threads(2, 0.0, vec![2.0]),
+            Process::new_with_threads(3, 0.0, vec![2.0, 2.0]),
+        ]
+    };
+
+    let mut gang: GangScheduler = GangScheduler::new(workload(), 2);
+    gang.quiet = true;
+    gang.gang(1.0);
+    assert_eq!(gang.matrix_history[0].rows.len(), 3);
+    assert_eq!(gang.average_coscheduling_ratio(), 1.0);
+
+    let mut independent: GangScheduler = GangScheduler::new(workload(), 2);
+    independent.quiet = true;
+    independent.independent(1.0);
+    assert!(independent.average_coscheduling_ratio() < 1.0);
+    assert!(independent.utilization() > gang.utilization());
+}
@@ -0,0 +1,400 @@
+use std::collections::VecDeque;
+
+use super::{Process, Event, EventKind, Burst, ProcessState, more_than_two_decimal_places, to_ticks, from_ticks};
+use crate::gantt::GanttChart;
+
+/// Policy used to pick the next process for the CPU.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CpuPolicy {
+    /// First Come First Serve: a process keeps the CPU until its CPU burst ends.
+    Fcfs,
+    /// Shortest Job First on the length of the next CPU burst (nonpreemptive).
+    Sjf,
+    /// Round Robin with a time quantum.
+    Rr { time_quantum: f64 },
+    /// Multilevel Feedback Queue: one quantum per level, highest level first.
+    /// A process that uses its whole quantum moves one level down, a process that blocks keeps its level.
+    /// A process becoming ready on a higher level preempts the running one.
+    Mlfq { time_quanta: Vec<f64> },
+}
+
+pub struct IoScheduler {
+    pub processes: Vec<Process>,
+    pub devices: Vec<String>,
+    pub event_list: Vec<Event>,
+    /// One event per I/O request; the `core` field holds the device index.
+    pub device_event_list: Vec<Event>,
+    pub finished_processes: Vec<Process>,
+    pub quiet: bool,
+}
+
+/// Per-process simulation state.
+struct Progress {
+    bursts: Vec<Burst>,
+    burst: usize,
+    remaining: u64,
+    level: usize,
+    ready_since: u64,
+}
+
+// Common methods
+impl IoScheduler {
+    pub fn new(processes: Vec<Process>, devices: Vec<String>) -> IoScheduler {
+        IoScheduler {
+            processes,
+            devices,
+            event_list: Vec::new(),
+            device_event_list: Vec::new(),
+            finished_processes: Vec::new(),
+            quiet: false,
+        }
+    }
+
+    pub fn sort_by_arrival_time(&mut self) {
+        self.processes.sort_by(|a, b| a.arrival_time.partial_cmp(&b.arrival_time).unwrap());
+    }
+
+    /// Time at which the last process finished.
+    pub fn makespan(&self) -> f64 {
+        self.finished_processes
+            .iter()
+            .map(|process| process.finish_time)
+            .fold(0.0, f64::max)
+    }
+
+    /// Fraction of the time between the first arrival and the makespan the CPU was busy.
+    pub fn cpu_utilization(&self) -> f64 {
+        let first_arrival: f64 = self
+            .finished_processes
+            .iter()
+            .map(|process| process.arrival_time)
+            .fold(f64::INFINITY, f64::min);
+        let busy_time: f64 = self.event_list.iter().map(|event| event.finish_time - event.start_time).sum();
+        let elapsed: f64 = self.makespan() - first_arrival;
+        if elapsed > 0.0 { busy_time / elapsed } else { 0.0 }
+    }
+
+    /// Fraction of the time between the first arrival and the makespan a device was busy.
+    pub fn device_utilization(&self, device: usize) -> f64 {
+        let first_arrival: f64 = self
+            .finished_processes
+            .iter()
+            .map(|process| process.arrival_time)
+            .fold(f64::INFINITY, f64::min);
+        let busy_time: f64 = self
+            .device_event_list
+            .iter()
+            .filter(|event| event.core == device)
+            .map(|event| event.finish_time - event.start_time)
+            .fold(0.0, |total, time| total + time);
+        let elapsed: f64 = self.makespan() - first_arrival;
+        if elapsed > 0.0 { busy_time / elapsed } else { 0.0 }
+    }
+
+    /// Time quantum of a process on the given level (`None` means run until the CPU burst ends).
+    fn quantum(policy: &CpuPolicy, level: usize) -> Option<u64> {
+        match policy {
+            CpuPolicy::Fcfs | CpuPolicy::Sjf => None,
+            CpuPolicy::Rr { time_quantum } => Some(to_ticks(*time_quantum)),
+            CpuPolicy::Mlfq { time_quanta } => Some(to_ticks(time_quanta[level.min(time_quanta.len() - 1)])),
+        }
+    }
+
+    /// Pick the next process to run, if any.
+    fn pick(policy: &CpuPolicy, ready_queues: &mut [VecDeque<usize>], progress: &[Progress]) -> Option<usize> {
+        match policy {
+            CpuPolicy::Sjf => {
+                // Shortest next CPU burst, ties broken by queue order.
+                let queue: &mut VecDeque<usize> = &mut ready_queues[0];
+                let position: usize = (0..queue.len()).min_by_key(|&position| progress[queue[position]].remaining)?;
+                queue.remove(position)
+            }
+            _ => ready_queues.iter_mut().find(|queue| !queue.is_empty())?.pop_front(),
+        }
+    }
+}
+
+// Visualization
+impl IoScheduler {
+    pub fn print(&mut self) {
+        // Define a processes variable to store the finished processes.
+        let processes: Vec<Process> = self.finished_processes.clone();
+
+        println!("Name\t\tArrival Time\tCPU Time\tI/O Time\tTurn Around Time\tWaiting Time\tFinish Time");
+        for process in processes.iter() {
+            println!(
+                "P{}\t\t{:.2}\t\t{:.2}\t\t{:.2}\t\t{:.2}\t\t\t{:.2}\t\t\t{:.2}",
+                process.pid,
+                process.arrival_time,
+                process.burst_time,
+                process.io_time(),
+                process.turn_around_time,
+                process.waiting_time,
+                process.finish_time
+            );
+        }
+
+        // Calculate average waiting time and average turn around time.
+        let average_waiting_time: f64 = processes.iter().map(|process| process.waiting_time).sum::<f64>()
+            / processes.len() as f64;
+        let average_turn_around_time: f64 = processes.iter().map(|process| process.turn_around_time).sum::<f64>()
+            / processes.len() as f64;
+
+        println!(
+            "Average:\t\t\t\t\t\t\t*{:.2}\t\t\t*{:.2}",
+            average_turn_around_time,
+            average_waiting_time,
+        );
+
+        println!("\nCPU utilization: {:.2}%", self.cpu_utilization() * 100.0);
+        for (device, name) in self.devices.iter().enumerate() {
+            println!("{} utilization: {:.2}%", name, self.device_utilization(device) * 100.0);
+        }
+
+        self.gantt_chart();
+    }
+
+    /// Horizontal chart with one row for the CPU and one row per device.
+    pub fn gantt_chart(&self) {
+        let mut rows: Vec<(String, Vec<&Event>)> = vec![(String::from("CPU"), self.event_list.iter().collect())];
+        for (device, name) in self.devices.iter().enumerate() {
+            rows.push((name.clone(), self.device_event_list.iter().filter(|event| event.core == device).collect()));
+        }
+        println!("\n\nGantt Chart:\n{}", GanttChart::new().render_rows(&rows));
+    }
+}
+
+// Algorithms
+impl IoScheduler {
+    // Run the burst sequences with the given CPU policy. Every device serves its queue in FCFS order.
+    pub fn run(&mut self, policy: CpuPolicy) {
+        // Check the policy parameters.
+        let time_quanta: Vec<f64> = match &policy {
+            CpuPolicy::Rr { time_quantum } => vec![*time_quantum],
+            CpuPolicy::Mlfq { time_quanta } => time_quanta.clone(),
+            _ => Vec::new(),
+        };
+        if matches!(policy, CpuPolicy::Mlfq { .. }) && time_quanta.is_empty() {
+            panic!("MLFQ needs at least one level!");
+        }
+        if time_quanta.iter().any(|&quantum| quantum <= 0.0 || more_than_two_decimal_places(quantum)) {
+            panic!("Invalid time quantum!");
+        }
+        let number_of_levels: usize = time_quanta.len().max(1);
+
+        // Sort the processes by arrival time.
+        self.sort_by_arrival_time();
+        let mut processes: Vec<Process> = std::mem::take(&mut self.processes);
+        let number_of_processes: usize = processes.len();
+
+        // Check the burst sequences and the devices they use.
+        for process in processes.iter() {
+            if !Burst::is_valid_sequence(&process.burst_sequence()) {
+                panic!("Process P{} does not alternate CPU and I/O bursts.", process.pid);
+            }
+            for burst in process.burst_sequence() {
+                if let Burst::Io { device, .. } = burst {
+                    if device >= self.devices.len() {
+                        panic!("Process P{} uses device {} which does not exist.", process.pid, device);
+                    }
+                }
+            }
+        }
+
+        let mut progress: Vec<Progress> = processes
+            .iter()
+            .map(|process| {
+                let bursts: Vec<Burst> = process.burst_sequence();
+                let remaining: u64 = to_ticks(bursts[0].time());
+                Progress { bursts, burst: 0, remaining, level: 0, ready_since: to_ticks(process.arrival_time) }
+            })
+            .collect();
+        let mut started: Vec<bool> = vec![false; number_of_processes];
+
+        // CPU: ready queues (one per level) and the running slice (process index, slice start, slice end).
+        let mut ready_queues: Vec<VecDeque<usize>> = vec![VecDeque::new(); number_of_levels];
+        let mut running: Option<(usize, u64, u64)> = None;
+
+        // Devices: one FCFS queue each and the request being served.
+        let mut device_queues: Vec<VecDeque<usize>> = vec![VecDeque::new(); self.devices.len()];
+        let mut serving: Vec<Option<(usize, u64, u64)>> = vec![None; self.devices.len()];
+
+        let mut current_time: u64 = processes.first().map_or(0, |process| to_ticks(process.arrival_time));
+        let mut next_arrival: usize = 0;
+        let mut number_of_finished: usize = 0;
+
+        while number_of_finished < number_of_processes {
+            // Processes that become ready now, in order: arrivals, I/O completions, preempted processes.
+            let mut became_ready: Vec<usize> = Vec::new();
+            let mut preempted: Option<usize> = None;
+
+            // New arrivals.
+            while next_arrival < number_of_processes && to_ticks(processes[next_arrival].arrival_time) <= current_time {
+                became_ready.push(next_arrival);
+                next_arrival += 1;
+            }
+
+            // I/O completions.
+            for (device, slot) in serving.iter_mut().enumerate() {
+                let (index, request_start, request_end) = match *slot {
+                    Some(request) if request.2 == current_time => request,
+                    _ => continue,
+                };
+                *slot = None;
+                self.device_event_list.push(Event {
+                    name: format!("P{}", processes[index].pid),
+                    kind: EventKind::Process,
+                    core: device,
+                    start_time: from_ticks(request_start),
+                    finish_time: from_ticks(request_end),
+                });
+                progress[index].burst += 1;
+                progress[index].remaining = to_ticks(progress[index].bursts[progress[index].burst].time());
+                became_ready.push(index);
+            }
+
+            // End of the CPU slice.
+            if let Some((index, slice_start, slice_end)) = running {
+                if slice_end == current_time {
+                    running = None;
+                    progress[index].remaining -= slice_end - slice_start;
+                    self.event_list.push(Event {
+                        name: format!("P{}", processes[index].pid),
+                        kind: EventKind::Process,
+                        core: 0,
+                        start_time: from_ticks(slice_start),
+                        finish_time: from_ticks(slice_end),
+                    });
+
+                    if progress[index].remaining > 0 {
+                        // The quantum expired: move one level down.
+                        progress[index].level = (progress[index].level + 1).min(number_of_levels - 1);
+                        preempted = Some(index);
+                    } else if progress[index].burst + 1 < progress[index].bursts.len() {
+                        // The CPU burst ended: block on the device of the next burst.
+                        progress[index].burst += 1;
+                        let device: usize = match progress[index].bursts[progress[index].burst] {
+                            Burst::Io { device, .. } => device,
+                            Burst::Cpu(_) => unreachable!(),
+                        };
+                        progress[index].remaining = to_ticks(progress[index].bursts[progress[index].burst].time());
+                        processes[index].transition(ProcessState::Blocked, from_ticks(slice_end));
+                        device_queues[device].push_back(index);
+                    } else {
+                        // The last CPU burst ended: the process is done.
+                        let process: &mut Process = &mut processes[index];
+                        process.finish_time = from_ticks(slice_end);
+                        process.transition(ProcessState::Terminated, process.finish_time);
+                        process.section_finish_time = process.finish_time;
+                        process.turn_around_time = process.finish_time - process.arrival_time;
+                        process.remaining_time = 0.0;
+                        self.finished_processes.push(process.clone());
+                        number_of_finished += 1;
+                    }
+                }
+            }
+            became_ready.extend(preempted);
+            for index in became_ready {
+                processes[index].transition(ProcessState::Ready, from_ticks(current_time));
+                progress[index].ready_since = current_time;
+                ready_queues[progress[index].level].push_back(index);
+            }
+
+            // MLFQ: a ready process on a higher level preempts the running one.
+            if let Some((index, slice_start, _)) = running {
+                let higher_level_ready: bool = ready_queues[..progress[index].level].iter().any(|queue| !queue.is_empty());
+                if higher_level_ready && slice_start < current_time {
+                    running = None;
+                    progress[index].remaining -= current_time - slice_start;
+                    self.event_list.push(Event {
+                        name: format!("P{}", processes[index].pid),
+                        kind: EventKind::Process,
+                        core: 0,
+                        start_time: from_ticks(slice_start),
+                        finish_time: from_ticks(current_time),
+                    });
+                    processes[index].transition(ProcessState::Ready, from_ticks(current_time));
+                    progress[index].ready_since = current_time;
+                    ready_queues[progress[index].level].push_back(index);
+                }
+            }
+
+            // Dispatch a process on the idle CPU.
+            if running.is_none() {
+                if let Some(index) = Self::pick(&policy, &mut ready_queues, &progress) {
+                    if !started[index] {
+                        processes[index].start_time = from_ticks(current_time);
+                        started[index] = true;
+                    }
+                    processes[index].transition(ProcessState::Running, from_ticks(current_time));
+                    processes[index].waiting_time += from_ticks(current_time - progress[index].ready_since);
+                    let time_run: u64 = match Self::quantum(&policy, progress[index].level) {
+                        Some(quantum) => quantum.min(progress[index].remaining),
+                        None => progress[index].remaining,
+                    };
+                    running = Some((index, current_time, current_time + time_run));
+                }
+            }
+
+            // Start serving the next request on every idle device.
+            for (device, slot) in serving.iter_mut().enumerate() {
+                if slot.is_none() {
+                    if let Some(index) = device_queues[device].pop_front() {
+                        *slot = Some((index, current_time, current_time + progress[index].remaining));
+                    }
+                }
+            }
+
+            // Find the next time something happens.
+            let mut next_time: u64 = u64::MAX;
+            if let Some((_, _, slice_end)) = running {
+                next_time = next_time.min(slice_end);
+            }
+            for &(_, _, request_end) in serving.iter().flatten() {
+                next_time = next_time.min(request_end);
+            }
+            if next_arrival < number_of_processes {
+                next_time = next_time.min(to_ticks(processes[next_arrival].arrival_time));
+            }
+            if next_time == u64::MAX {
+                break;
+            }
+            current_time = next_time;
+        }
+
+        // Print the result.
+        if !self.quiet {
+            self.print();
+        }
+    }
+}
+
+// Test an I/O bound process next to a CPU bound one.
+#[test]
+fn test() {
+    let workload = || -> Vec<Process> {
+        vec![
+            Process::new(1, 0.0, 10.0),
+            Process::new_with_bursts(2, 0.0, vec![
+                Burst::Cpu(1.0),
+                Burst::Io { device: 0, time: 3.0 },
+                Burst::Cpu(1.0),
+                Burst::Io { device: 0, time: 3.0 },
+                Burst::Cpu(1.0),
+            ]),
+        ]
+    };
+
+    let mut fcfs: IoScheduler = IoScheduler::new(workload(), vec!["Disk".to_string()]);
+    fcfs.quiet = true;
+    fcfs.run(CpuPolicy::Fcfs);
+    assert_eq!(fcfs.makespan(), 19.0);
+
+    let mut mlfq: IoScheduler = IoScheduler::new(workload(), vec!["Disk".to_string()]);
+    mlfq.quiet = true;
+    mlfq.run(CpuPolicy::Mlfq { time_quanta: vec![2.0, 4.0, 8.0] });
+    let interactive: &Process = mlfq.finished_processes.iter().find(|process| process.pid == 2).unwrap();
+    assert_eq!(interactive.finish_time, 11.0);
+    assert_eq!(mlfq.device_event_list.len(), 2);
+    assert!(mlfq.finished_processes.iter().all(|process| process.state == ProcessState::Terminated));
+}
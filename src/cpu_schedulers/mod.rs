@@ -1,9 +1,18 @@
 pub mod preemptive;
 pub mod nonpreemptive;
+pub mod multicore;
+pub mod realtime;
+pub mod gang;
+pub mod io;
+pub mod prediction;
+#[cfg(test)]
+mod properties;
 
 use std::fmt::{Debug, Display};
+use std::str::FromStr;
 
 /// `Process` struct.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Process {
     pub pid: u32,
     pub arrival_time: f64,
@@ -18,6 +27,19 @@ pub struct Process {
 
     pub start_time: f64,
     pub finish_time: f64,
+
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::mask"))]
+    pub hard_affinity: u64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::mask"))]
+    pub soft_affinity: u64,
+
+    pub threads: Vec<Thread>,
+
+    pub bursts: Vec<Burst>,
+    pub state: ProcessState,
+    pub state_history: Vec<Transition>,
+
+    pub burst_history: Vec<f64>,
 }
 
 /// Implement `Debug` trait for `Process` struct.
@@ -34,6 +56,13 @@ impl Debug for Process {
             .field("section_finish_time", &self.section_finish_time)
             .field("start_time", &self.start_time)
             .field("finish_time", &self.finish_time)
+            .field("hard_affinity", &format_args!("{:#b}", self.hard_affinity))
+            .field("soft_affinity", &format_args!("{:#b}", self.soft_affinity))
+            .field("threads", &self.threads)
+            .field("bursts", &self.bursts)
+            .field("state", &self.state)
+            .field("state_history", &self.state_history)
+            .field("burst_history", &self.burst_history)
             .finish()
     }
 }
@@ -67,14 +96,22 @@ impl Clone for Process {
             section_finish_time: self.section_finish_time,
             start_time: self.start_time,
             finish_time: self.finish_time,
+            hard_affinity: self.hard_affinity,
+            soft_affinity: self.soft_affinity,
+            threads: self.threads.clone(),
+            bursts: self.bursts.clone(),
+            state: self.state,
+            state_history: self.state_history.clone(),
+            burst_history: self.burst_history.clone(),
         }
     }
 }
 
 /// Implement `PartialEq` trait for `Process` struct.
 impl PartialEq for Process {
+    /// Compare `Process` structs by pid, like `Hash` does (the priority queues rely on both agreeing).
     fn eq(&self, other: &Process) -> bool {
-        self.arrival_time == other.arrival_time
+        self.pid == other.pid
     }
 }
 
@@ -85,11 +122,7 @@ impl Eq for Process {}
 impl PartialOrd for Process {
     /// Compare `Process` structs by priority. If priorities are equal, compare by burst time.
     fn partial_cmp(&self, other: &Process) -> Option<std::cmp::Ordering> {
-        if self.priority == other.priority {
-            Some(self.burst_time.partial_cmp(&other.burst_time).unwrap())
-        } else {
-            Some(self.priority.partial_cmp(&other.priority).unwrap())
-        }
+        Some(self.cmp(other))
     }
 }
 
@@ -114,7 +147,7 @@ impl std::hash::Hash for Process {
 
 impl Process {
     /// Constructor for `Process` struct.
-    fn new(pid: u32, arrival_time: f64, burst_time: f64) -> Process {
+    pub fn new(pid: u32, arrival_time: f64, burst_time: f64) -> Process {
         time_check(arrival_time, burst_time);
         Process {
             pid,
@@ -128,11 +161,18 @@ impl Process {
             section_finish_time: 0.0,
             start_time: 0.0,
             finish_time: 0.0,
+            hard_affinity: u64::MAX,
+            soft_affinity: 0,
+            threads: Vec::new(),
+            bursts: Vec::new(),
+            state: ProcessState::New,
+            state_history: Vec::new(),
+            burst_history: Vec::new(),
         }
     }
 
     /// Constructor for `Process` struct with priority.
-    fn new_with_priority(pid: u32, arrival_time: f64, burst_time: f64, priority: u32) -> Process {
+    pub fn new_with_priority(pid: u32, arrival_time: f64, burst_time: f64, priority: u32) -> Process {
         time_check(arrival_time, burst_time);
         Process {
             pid,
@@ -146,12 +186,166 @@ impl Process {
             section_finish_time: 0.0,
             start_time: 0.0,
             finish_time: 0.0,
+            hard_affinity: u64::MAX,
+            soft_affinity: 0,
+            threads: Vec::new(),
+            bursts: Vec::new(),
+            state: ProcessState::New,
+            state_history: Vec::new(),
+            burst_history: Vec::new(),
+        }
+    }
+
+    /// Constructor for `Process` struct with several threads.
+    /// The burst time of the process is the total CPU time of its threads.
+    pub fn new_with_threads(pid: u32, arrival_time: f64, thread_burst_times: Vec<f64>) -> Process {
+        if thread_burst_times.is_empty() {
+            panic!("A process must have at least one thread.");
+        }
+        let threads: Vec<Thread> = thread_burst_times
+            .iter()
+            .enumerate()
+            .map(|(tid, &burst_time)| {
+                time_check(arrival_time, burst_time);
+                Thread { tid: tid as u32, burst_time, remaining_time: burst_time, finish_time: 0.0 }
+            })
+            .collect();
+        let burst_time: f64 = threads.iter().map(|thread| thread.burst_time).sum();
+        let mut process: Process = Process::new(pid, arrival_time, burst_time);
+        process.threads = threads;
+        process
+    }
+
+    /// Number of threads (a process created without threads has one implicit thread).
+    pub fn number_of_threads(&self) -> usize {
+        self.threads.len().max(1)
+    }
+
+    /// Constructor for `Process` struct with alternating CPU and I/O bursts.
+    /// The sequence must start and end with a CPU burst; the burst time of the process is its total CPU time.
+    pub fn new_with_bursts(pid: u32, arrival_time: f64, bursts: Vec<Burst>) -> Process {
+        if !Burst::is_valid_sequence(&bursts) {
+            panic!("A burst sequence must alternate CPU and I/O bursts, starting and ending with a CPU burst.");
+        }
+        for burst in bursts.iter() {
+            time_check(arrival_time, burst.time());
+        }
+        let burst_time: f64 = bursts
+            .iter()
+            .filter_map(|burst| match burst {
+                Burst::Cpu(time) => Some(*time),
+                Burst::Io { .. } => None,
+            })
+            .sum();
+        let mut process: Process = Process::new(pid, arrival_time, burst_time);
+        process.bursts = bursts;
+        process
+    }
+
+    /// The burst sequence of the process (a single CPU burst if none was given).
+    pub fn burst_sequence(&self) -> Vec<Burst> {
+        if self.bursts.is_empty() {
+            vec![Burst::Cpu(self.burst_time)]
+        } else {
+            self.bursts.clone()
+        }
+    }
+
+    /// Total time the process spends on I/O devices.
+    pub fn io_time(&self) -> f64 {
+        self.bursts
+            .iter()
+            .filter_map(|burst| match burst {
+                Burst::Io { time, .. } => Some(*time),
+                Burst::Cpu(_) => None,
+            })
+            .fold(0.0, |total, time| total + time)
+    }
+
+    /// Set the lengths of the previous CPU bursts of the process, oldest first (used to predict the next one).
+    pub fn set_burst_history(&mut self, burst_history: Vec<f64>) {
+        if burst_history.iter().any(|&burst_time| burst_time < 0.0) {
+            panic!("Burst history must only contain non negative times.");
+        }
+        self.burst_history = burst_history;
+    }
+
+    /// Move the process to another state at the given time.
+    /// Panics if the transition is not allowed or goes back in time.
+    pub fn transition(&mut self, state: ProcessState, time: f64) {
+        if !self.state.can_transition_to(state) {
+            panic!("Invalid transition of P{} from {:?} to {:?} at {:.2}.", self.pid, self.state, state, time);
+        }
+        if let Some(last) = self.state_history.last() {
+            if time < last.time {
+                panic!("Transition of P{} at {:.2} happens before the previous one at {:.2}.", self.pid, time, last.time);
+            }
+        }
+        self.state_history.push(Transition { time, from: self.state, to: state });
+        self.state = state;
+    }
+
+    /// Swap the process out: Ready -> Ready/Suspended, Blocked -> Blocked/Suspended.
+    pub fn suspend(&mut self, time: f64) {
+        match self.state {
+            ProcessState::Ready => self.transition(ProcessState::ReadySuspended, time),
+            ProcessState::Blocked => self.transition(ProcessState::BlockedSuspended, time),
+            state => panic!("P{} cannot be suspended from {:?}.", self.pid, state),
+        }
+    }
+
+    /// Swap the process back in: Ready/Suspended -> Ready, Blocked/Suspended -> Blocked.
+    pub fn resume(&mut self, time: f64) {
+        match self.state {
+            ProcessState::ReadySuspended => self.transition(ProcessState::Ready, time),
+            ProcessState::BlockedSuspended => self.transition(ProcessState::Blocked, time),
+            state => panic!("P{} cannot be resumed from {:?}.", self.pid, state),
         }
     }
+
+    /// The state history as one line, e.g. `P1: New -(0.00)-> Ready -(1.00)-> Running -(4.00)-> Terminated`.
+    pub fn state_diagram(&self) -> String {
+        let mut diagram: String = format!("P{}: {:?}", self.pid, ProcessState::New);
+        for transition in self.state_history.iter() {
+            diagram.push_str(&format!(" -({:.2})-> {:?}", transition.time, transition.to));
+        }
+        diagram
+    }
+
+    /// Set the CPU affinity masks of the process.
+    /// Bit `i` of a mask stands for core `i`. The hard mask lists the cores the process may run on,
+    /// the soft mask lists the cores it prefers (the scheduler may still migrate it elsewhere).
+    pub fn set_affinity(&mut self, hard_affinity: u64, soft_affinity: u64) {
+        if hard_affinity == 0 {
+            panic!("Hard affinity mask must allow at least one core.");
+        }
+        self.hard_affinity = hard_affinity;
+        self.soft_affinity = soft_affinity & hard_affinity;
+    }
+
+    /// Check if the process may run on the given core.
+    pub fn can_run_on(&self, core: usize) -> bool {
+        core < 64 && self.hard_affinity & (1 << core) != 0
+    }
+
+    /// Check if the process prefers the given core.
+    pub fn prefers(&self, core: usize) -> bool {
+        core < 64 && self.soft_affinity & (1 << core) != 0
+    }
+}
+
+/// Print the state history of every process, one line per process.
+pub fn print_state_history(processes: &[Process]) {
+    println!("\nState history:");
+    for process in processes.iter() {
+        println!("{}", process.state_diagram());
+    }
 }
 
-fn more_than_two_decimal_places(num: f64) -> bool {
-    (num * 100.0) != (num * 100.0).ceil()
+pub(crate) fn more_than_two_decimal_places(num: f64) -> bool {
+    // Infinite and NaN times have no decimal places to check, reject them too.
+    // Compare with a tolerance: 1.1 * 100.0 is 110.00000000000001 in binary floating point.
+    !num.is_finite() || ((num * 100.0) - (num * 100.0).round()).abs() > 1e-6
 }
 
 fn time_check(arrival_time: f64, burst_time: f64) {
@@ -165,9 +359,312 @@ fn time_check(arrival_time: f64, burst_time: f64) {
     }
 }
 
+/// A CPU burst, or an I/O burst on one device.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Burst {
+    Cpu(f64),
+    Io { device: usize, time: f64 },
+}
+
+impl Burst {
+    /// Length of the burst.
+    pub fn time(&self) -> f64 {
+        match self {
+            Burst::Cpu(time) => *time,
+            Burst::Io { time, .. } => *time,
+        }
+    }
+
+    /// Check that the bursts alternate between CPU and I/O, starting and ending with a CPU burst.
+    pub fn is_valid_sequence(bursts: &[Burst]) -> bool {
+        bursts.len() % 2 == 1
+            && bursts
+                .iter()
+                .enumerate()
+                .all(|(position, burst)| matches!(burst, Burst::Cpu(_)) == (position % 2 == 0))
+    }
+}
+
+/// State of a process in the seven-state model.
+/// The five-state model is the same without the two suspended states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ProcessState {
+    New,
+    Ready,
+    Running,
+    /// Waiting for an I/O device.
+    Blocked,
+    Terminated,
+    /// Ready, but swapped out to secondary storage.
+    ReadySuspended,
+    /// Blocked, and swapped out to secondary storage.
+    BlockedSuspended,
+}
+
+impl ProcessState {
+    /// States a process may move to from this state.
+    pub fn allowed_transitions(&self) -> &'static [ProcessState] {
+        use ProcessState::*;
+        match self {
+            New => &[Ready, ReadySuspended],
+            Ready => &[Running, ReadySuspended],
+            Running => &[Ready, Blocked, Terminated],
+            Blocked => &[Ready, BlockedSuspended],
+            Terminated => &[],
+            ReadySuspended => &[Ready],
+            BlockedSuspended => &[Blocked, ReadySuspended],
+        }
+    }
+
+    pub fn can_transition_to(&self, state: ProcessState) -> bool {
+        self.allowed_transitions().contains(&state)
+    }
+}
+
+/// `Transition` struct: a state change of a process.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transition {
+    pub time: f64,
+    pub from: ProcessState,
+    pub to: ProcessState,
+}
+
+/// `Thread` struct.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Thread {
+    pub tid: u32,
+    pub burst_time: f64,
+    pub remaining_time: f64,
+    pub finish_time: f64,
+}
+
+/// What happens during an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum EventKind {
+    /// A process (or thread, task...) runs.
+    Process,
+    /// The dispatcher switches from one process to another.
+    ContextSwitch,
+}
+
 /// `Event` struct.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Event {
     pub name: String,
-    pub start_time: u32,
-    pub finish_time: u32,
+    pub kind: EventKind,
+    pub core: usize,
+    pub start_time: f64,
+    pub finish_time: f64,
+}
+
+impl Event {
+    /// Pid of the process of the event, read from its name (`P3` or `P3.T1` give 3).
+    pub fn pid(&self) -> Option<u32> {
+        if self.kind != EventKind::Process {
+            return None;
+        }
+        let name: &str = self.name.strip_prefix('P')?;
+        name.split('.').next()?.parse().ok()
+    }
+
+    /// Length of the event.
+    pub fn duration(&self) -> f64 {
+        self.finish_time - self.start_time
+    }
+}
+
+/// Implement `Debug` trait for `Event` struct.
+impl Debug for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Event")
+            .field("name", &self.name)
+            .field("kind", &self.kind)
+            .field("core", &self.core)
+            .field("start_time", &self.start_time)
+            .field("finish_time", &self.finish_time)
+            .finish()
+    }
+}
+
+/// Implement `Clone` trait for `Event` struct.
+impl Clone for Event {
+    fn clone(&self) -> Event {
+        Event {
+            name: self.name.clone(),
+            kind: self.kind,
+            core: self.core,
+            start_time: self.start_time,
+            finish_time: self.finish_time,
+        }
+    }
+}
+
+/// Cost of handing the CPU to a process.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ContextSwitch {
+    /// Switching is free.
+    None,
+    /// Every dispatch costs the same, even when the CPU goes back to the process that just ran.
+    Fixed(f64),
+    /// Only switching to a different process costs something; resuming the same process is free.
+    PerSwitch(f64),
+    /// Separate costs for resuming the same process and for switching to a new one.
+    Split { same_process: f64, new_process: f64 },
+}
+
+impl ContextSwitch {
+    /// Cost of one dispatch.
+    pub fn cost(&self, same_process: bool) -> f64 {
+        match *self {
+            ContextSwitch::None => 0.0,
+            ContextSwitch::Fixed(cost) => cost,
+            ContextSwitch::PerSwitch(cost) => if same_process { 0.0 } else { cost },
+            ContextSwitch::Split { same_process: same, new_process: new } => if same_process { same } else { new },
+        }
+    }
+
+    /// Check that every cost is a non negative number with at most two decimal places.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let valid = |cost: f64| cost.is_finite() && cost >= 0.0 && !more_than_two_decimal_places(cost);
+        if valid(self.cost(true)) && valid(self.cost(false)) {
+            Ok(())
+        } else {
+            Err(String::from("Context switch cost must be a non negative real number with less than 3 decimal places."))
+        }
+    }
+
+    fn check(&self) {
+        if let Err(error) = self.validate() {
+            panic!("{}", error);
+        }
+    }
+
+    /// Cost of dispatching `process` after the events already in `event_list` (on core 0).
+    /// The CPU goes back to the same process if it is the last one that ran.
+    fn cost_after(&self, event_list: &[Event], process: &Process) -> f64 {
+        let last_process: Option<&Event> = event_list
+            .iter()
+            .rev()
+            .find(|event| event.kind == EventKind::Process);
+        let same_process: bool = last_process.is_some_and(|event| event.name == format!("P{}", process.pid));
+        self.cost(same_process)
+    }
+}
+
+/// Parse a context switch cost: `none`, `fixed <cost>`, `per_switch <cost>` or `split <same process> <new process>`.
+impl FromStr for ContextSwitch {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<ContextSwitch, String> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let costs: Vec<f64> = words
+            .iter()
+            .skip(1)
+            .map(|word| word.parse::<f64>().map_err(|_| format!("Invalid cost `{}` in `{}`.", word, text)))
+            .collect::<Result<Vec<f64>, String>>()?;
+        let context_switch: ContextSwitch = match (words.first().map(|word| word.to_lowercase()).as_deref(), costs.as_slice()) {
+            (Some("none"), []) => ContextSwitch::None,
+            (Some("fixed"), [cost]) => ContextSwitch::Fixed(*cost),
+            (Some("per_switch"), [cost]) => ContextSwitch::PerSwitch(*cost),
+            (Some("split"), [same_process, new_process]) => ContextSwitch::Split { same_process: *same_process, new_process: *new_process },
+            _ => return Err(format!("Invalid context switch `{}`.", text)),
+        };
+        context_switch.validate().map_err(|_| format!("Invalid context switch cost in `{}`.", text))?;
+        Ok(context_switch)
+    }
+}
+
+/// Number of context switches in a timeline.
+pub fn number_of_context_switches(event_list: &[Event]) -> usize {
+    event_list.iter().filter(|event| event.kind == EventKind::ContextSwitch).count()
+}
+
+/// Total time spent switching in a timeline.
+pub fn context_switch_overhead(event_list: &[Event]) -> f64 {
+    event_list
+        .iter()
+        .filter(|event| event.kind == EventKind::ContextSwitch)
+        .map(|event| event.finish_time - event.start_time)
+        .fold(0.0, |total, time| total + time)
+}
+
+/// CPU efficiency = useful time / (useful time + switching time).
+pub fn cpu_efficiency(event_list: &[Event]) -> f64 {
+    let useful_time: f64 = event_list
+        .iter()
+        .filter(|event| event.kind == EventKind::Process)
+        .map(|event| event.finish_time - event.start_time)
+        .fold(0.0, |total, time| total + time);
+    let overhead: f64 = context_switch_overhead(event_list);
+    if useful_time + overhead > 0.0 { useful_time / (useful_time + overhead) } else { 1.0 }
+}
+
+/// Convert a time (with at most two decimal places) to an integer number of hundredths.
+pub(crate) fn to_ticks(time: f64) -> u64 {
+    (time * 100.0).round() as u64
+}
+
+/// Convert an integer number of hundredths back to a time.
+fn from_ticks(ticks: u64) -> f64 {
+    ticks as f64 / 100.0
+}
+
+// Test the decimal places check, on both sides of the tolerance.
+#[test]
+fn test() {
+    // Times with at most two decimal places, including those that are inexact in binary.
+    for num in [0.0, 0.01, 0.1, 0.29, 1.1, 2.3, 4.35, 12.34, 100.99] {
+        assert!(!more_than_two_decimal_places(num), "{} has at most two decimal places", num);
+    }
+
+    // Times with more decimal places.
+    for num in [0.001, 0.005, 0.125, 1.005, 1.234, 12.345] {
+        assert!(more_than_two_decimal_places(num), "{} has more than two decimal places", num);
+    }
+
+    // Floating point noise below the tolerance is ignored, a real third decimal is not.
+    assert!(!more_than_two_decimal_places(1.1 + 1e-9));
+    assert!(!more_than_two_decimal_places(1.1 - 1e-9));
+    assert!(more_than_two_decimal_places(1.1 + 1e-7));
+    assert!(more_than_two_decimal_places(1.1 - 1e-7));
+
+    // Infinite and NaN times are rejected.
+    for num in [f64::INFINITY, f64::NEG_INFINITY, f64::NAN] {
+        assert!(more_than_two_decimal_places(num), "{} is not a valid time", num);
+    }
+    assert!(std::panic::catch_unwind(|| Process::new(1, f64::NAN, f64::NAN)).is_err());
+    assert!(std::panic::catch_unwind(|| Process::new(1, 0.0, f64::INFINITY)).is_err());
+
+    // Processes accept such times.
+    let process: Process = Process::new(1, 1.1, 2.3);
+    assert_eq!(process.burst_time, 2.3);
+
+    // Processes are identified by their pid, whatever their arrival time.
+    assert!(Process::new(1, 0.0, 1.0) != Process::new(2, 0.0, 1.0));
+    assert!(Process::new(1, 0.0, 1.0) == Process::new(1, 2.0, 3.0));
+
+    // Burst sequences alternate CPU and I/O bursts.
+    let io: Burst = Burst::Io { device: 0, time: 1.0 };
+    assert!(Burst::is_valid_sequence(&[Burst::Cpu(1.0)]));
+    assert!(Burst::is_valid_sequence(&[Burst::Cpu(1.0), io, Burst::Cpu(2.0)]));
+    assert!(!Burst::is_valid_sequence(&[]));
+    assert!(!Burst::is_valid_sequence(&[Burst::Cpu(1.0), Burst::Cpu(2.0)]));
+    assert!(!Burst::is_valid_sequence(&[Burst::Cpu(1.0), io, io, Burst::Cpu(2.0)]));
+    assert!(!Burst::is_valid_sequence(&[Burst::Cpu(1.0), io]));
+
+    // Context switches are parsed with the costs the schedulers accept.
+    assert_eq!("split 0 0.5".parse::<ContextSwitch>(), Ok(ContextSwitch::Split { same_process: 0.0, new_process: 0.5 }));
+    for text in ["fixed -1", "per_switch 0.125", "fixed inf", "fixed NaN"] {
+        assert!(text.parse::<ContextSwitch>().is_err(), "{}", text);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,468 @@
+use std::collections::VecDeque;
+
+use super::{Process, ProcessState, Event, EventKind, more_than_two_decimal_places, to_ticks, from_ticks};
+use crate::gantt::GanttChart;
+
+/// Load balancing policy used between the per-core run queues.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoadBalancing {
+    /// Processes stay on the core they were placed on when they arrived.
+    None,
+    /// Every `interval` time units, queued processes are pushed from the busiest core to the least busy one.
+    PushMigration { interval: f64 },
+    /// A core that runs out of work pulls (steals) a queued process from the busiest core.
+    WorkStealing,
+    /// Push migration and work stealing together.
+    PushAndPull { interval: f64 },
+}
+
+/// How a process was moved to another run queue.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MigrationKind {
+    Push,
+    Pull,
+}
+
+/// `Migration` struct.
+#[derive(Debug, Clone)]
+pub struct Migration {
+    pub pid: u32,
+    pub time: f64,
+    pub from_core: usize,
+    pub to_core: usize,
+    pub kind: MigrationKind,
+}
+
+pub struct MulticoreScheduler {
+    pub processes: Vec<Process>,
+    pub number_of_cores: usize,
+    pub load_balancing: LoadBalancing,
+    pub migration_cost: f64,
+    pub event_list: Vec<Event>,
+    pub migration_list: Vec<Migration>,
+    pub finished_processes: Vec<Process>,
+    pub quiet: bool,
+}
+
+// Common methods
+impl MulticoreScheduler {
+    pub fn new(processes: Vec<Process>, number_of_cores: usize) -> MulticoreScheduler {
+        if number_of_cores == 0 || number_of_cores > 64 {
+            panic!("Number of cores must be between 1 and 64.");
+        }
+        if let Some(process) = processes.iter().find(|process| (0..number_of_cores).all(|core| !process.can_run_on(core))) {
+            panic!("Process P{} cannot run on any of the {} cores.", process.pid, number_of_cores);
+        }
+        MulticoreScheduler {
+            processes,
+            number_of_cores,
+            load_balancing: LoadBalancing::None,
+            migration_cost: 0.0,
+            event_list: Vec::new(),
+            migration_list: Vec::new(),
+            finished_processes: Vec::new(),
+            quiet: false,
+        }
+    }
+
+    pub fn set_load_balancing(&mut self, load_balancing: LoadBalancing) {
+        if let LoadBalancing::PushMigration { interval } | LoadBalancing::PushAndPull { interval } = load_balancing {
+            if interval <= 0.0 || more_than_two_decimal_places(interval) {
+                panic!("Invalid load balancing interval!");
+            }
+        }
+        self.load_balancing = load_balancing;
+    }
+
+    /// The migration cost is paid every time a process is dispatched on a different core than the one
+    /// it last ran on (cold caches, TLB refill...). It shows up as a context switch slice before the process runs.
+    pub fn set_migration_cost(&mut self, migration_cost: f64) {
+        if migration_cost < 0.0 || more_than_two_decimal_places(migration_cost) {
+            panic!("Invalid migration cost!");
+        }
+        self.migration_cost = migration_cost;
+    }
+
+    pub fn sort_by_arrival_time(&mut self) {
+        self.processes.sort_by(|a, b| a.arrival_time.partial_cmp(&b.arrival_time).unwrap());
+    }
+
+    /// Time at which the last process finished.
+    pub fn makespan(&self) -> f64 {
+        self.finished_processes
+            .iter()
+            .map(|process| process.finish_time)
+            .fold(0.0, f64::max)
+    }
+
+    /// Fraction of the makespan each core spent running processes (migration slices excluded).
+    pub fn utilization(&self) -> Vec<f64> {
+        let makespan: f64 = self.makespan();
+        (0..self.number_of_cores)
+            .map(|core| {
+                let busy_time: f64 = self
+                    .event_list
+                    .iter()
+                    .filter(|event| event.core == core && event.kind == EventKind::Process)
+                    .map(|event| event.finish_time - event.start_time)
+                    .fold(0.0, |total, time| total + time);
+                if makespan > 0.0 { busy_time / makespan } else { 0.0 }
+            })
+            .collect()
+    }
+
+    /// Number of processes on a core (queued and running).
+    fn load(queues: &[VecDeque<usize>], running: &[Option<(usize, u64, u64)>], core: usize) -> usize {
+        queues[core].len() + running[core].is_some() as usize
+    }
+
+    /// Choose the run queue of a newly arrived process:
+    /// the least loaded preferred core, or the least loaded allowed core if no core is preferred.
+    /// `new` made sure that every process is allowed on at least one core.
+    fn place(&self, process: &Process, queues: &[VecDeque<usize>], running: &[Option<(usize, u64, u64)>]) -> usize {
+        let allowed: Vec<usize> = (0..self.number_of_cores)
+            .filter(|&core| process.can_run_on(core))
+            .collect();
+
+        let preferred: Vec<usize> = allowed.iter().copied().filter(|&core| process.prefers(core)).collect();
+        let candidates: &Vec<usize> = if preferred.is_empty() { &allowed } else { &preferred };
+
+        *candidates
+            .iter()
+            .min_by_key(|&&core| Self::load(queues, running, core))
+            .unwrap()
+    }
+
+    /// Find a queued process on `from_core` that may be moved to `to_core`.
+    /// Processes that do not prefer `from_core` are taken first, starting from the tail of the queue.
+    fn find_candidate(processes: &[Process], queue: &VecDeque<usize>, from_core: usize, to_core: usize) -> Option<usize> {
+        let movable = |&position: &usize| processes[queue[position]].can_run_on(to_core);
+        let positions: Vec<usize> = (0..queue.len()).rev().filter(movable).collect();
+        positions
+            .iter()
+            .copied()
+            .find(|&position| !processes[queue[position]].prefers(from_core))
+            .or_else(|| positions.first().copied())
+    }
+}
+
+// Load balancing
+impl MulticoreScheduler {
+    /// Push migration: move queued processes from the busiest to the least busy cores
+    /// until no allowed move reduces the imbalance.
+    fn push_migrate(&mut self, current_time: u64, processes: &[Process], queues: &mut [VecDeque<usize>], running: &[Option<(usize, u64, u64)>]) {
+        loop {
+            // Order the cores by load.
+            let mut cores: Vec<usize> = (0..self.number_of_cores).collect();
+            cores.sort_by_key(|&core| std::cmp::Reverse(Self::load(queues, running, core)));
+
+            // Find the first (busiest source, least busy target) pair with a movable process.
+            let mut chosen: Option<(usize, usize, usize)> = None;
+            'search: for &from_core in cores.iter() {
+                for &to_core in cores.iter().rev() {
+                    if Self::load(queues, running, from_core) < Self::load(queues, running, to_core) + 2 {
+                        break;
+                    }
+                    if let Some(position) = Self::find_candidate(processes, &queues[from_core], from_core, to_core) {
+                        chosen = Some((from_core, to_core, position));
+                        break 'search;
+                    }
+                }
+            }
+
+            // Move the process, or stop if the queues are balanced.
+            match chosen {
+                Some((from_core, to_core, position)) => {
+                    let index: usize = queues[from_core].remove(position).unwrap();
+                    queues[to_core].push_back(index);
+                    self.migration_list.push(Migration {
+                        pid: processes[index].pid,
+                        time: from_ticks(current_time),
+                        from_core,
+                        to_core,
+                        kind: MigrationKind::Push,
+                    });
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Work stealing: an idle core with an empty run queue pulls a queued process from the busiest core.
+    fn steal(&mut self, core: usize, current_time: u64, processes: &[Process], queues: &mut [VecDeque<usize>]) {
+        // Order the other cores by queue length.
+        let mut victims: Vec<usize> = (0..self.number_of_cores)
+            .filter(|&victim| victim != core && !queues[victim].is_empty())
+            .collect();
+        victims.sort_by_key(|&victim| std::cmp::Reverse(queues[victim].len()));
+
+        for victim in victims {
+            if let Some(position) = Self::find_candidate(processes, &queues[victim], victim, core) {
+                let index: usize = queues[victim].remove(position).unwrap();
+                queues[core].push_back(index);
+                self.migration_list.push(Migration {
+                    pid: processes[index].pid,
+                    time: from_ticks(current_time),
+                    from_core: victim,
+                    to_core: core,
+                    kind: MigrationKind::Pull,
+                });
+                return;
+            }
+        }
+    }
+}
+
+// Visualization
+impl MulticoreScheduler {
+    pub fn print(&mut self) {
+        // Define a processes variable to store the finished processes.
+        let processes: Vec<Process> = self.finished_processes.clone();
+
+        println!("Name\t\tArrival Time\tBurst Time\tTurn Around Time\tWaiting Time\tFinish Time");
+        for process in processes.iter() {
+            println!(
+                "P{}\t\t{:.2}\t\t{:.2}\t\t{:.2}\t\t\t{:.2}\t\t\t{:.2}",
+                process.pid,
+                process.arrival_time,
+                process.burst_time,
+                process.turn_around_time,
+                process.waiting_time,
+                process.finish_time
+            );
+        }
+
+        // Calculate average waiting time and average turn around time.
+        let average_waiting_time: f64 = processes.iter().map(|process| process.waiting_time).sum::<f64>()
+            / processes.len() as f64;
+        let average_turn_around_time: f64 = processes.iter().map(|process| process.turn_around_time).sum::<f64>()
+            / processes.len() as f64;
+
+        println!(
+            "Average:\t\t\t\t\t*{:.2}\t\t\t*{:.2}",
+            average_turn_around_time,
+            average_waiting_time,
+        );
+
+        println!("\nLoad balancing: {:?}, migration cost: {:.2}", self.load_balancing, self.migration_cost);
+        println!("Makespan: {:.2}, migrations: {}", self.makespan(), self.migration_list.len());
+        for (core, utilization) in self.utilization().iter().enumerate() {
+            println!("Core {}: utilization {:.2}%", core, utilization * 100.0);
+        }
+
+        self.gantt_chart();
+    }
+
+    /// Horizontal chart of the timeline, one row per core.
+    pub fn gantt_chart(&self) {
+        println!("\n\nGantt Chart:\n{}", GanttChart::new().render(&self.event_list));
+    }
+}
+
+// Algorithms
+impl MulticoreScheduler {
+    // Round Robin (RR) on every core, with one run queue per core.
+    pub fn rr(&mut self, time_quantum: f64) {
+        // Check if time quantum is valid.
+        if time_quantum <= 0.0 || more_than_two_decimal_places(time_quantum) {
+            panic!("Invalid time quantum!");
+        }
+
+        // Sort the processes by arrival time.
+        self.sort_by_arrival_time();
+
+        // Work in hundredths so that times add up exactly.
+        let quantum: u64 = to_ticks(time_quantum);
+        let migration_cost: u64 = to_ticks(self.migration_cost);
+        let balance_interval: Option<u64> = match self.load_balancing {
+            LoadBalancing::PushMigration { interval } | LoadBalancing::PushAndPull { interval } => Some(to_ticks(interval)),
+            _ => None,
+        };
+        let work_stealing: bool = matches!(
+            self.load_balancing,
+            LoadBalancing::WorkStealing | LoadBalancing::PushAndPull { .. }
+        );
+
+        // Per-process state, indexed like `processes`.
+        let mut processes: Vec<Process> = std::mem::take(&mut self.processes);
+        let number_of_processes: usize = processes.len();
+        let mut remaining: Vec<u64> = processes.iter().map(|process| to_ticks(process.burst_time)).collect();
+        let mut ready_since: Vec<u64> = processes.iter().map(|process| to_ticks(process.arrival_time)).collect();
+        let mut last_core: Vec<Option<usize>> = vec![None; number_of_processes];
+
+        // Per-core state: the run queue and the running slice (process index, slice start, slice end).
+        let mut queues: Vec<VecDeque<usize>> = vec![VecDeque::new(); self.number_of_cores];
+        let mut running: Vec<Option<(usize, u64, u64)>> = vec![None; self.number_of_cores];
+
+        let mut current_time: u64 = processes.first().map_or(0, |process| to_ticks(process.arrival_time));
+        let mut next_balance: Option<u64> = balance_interval.map(|interval| current_time + interval);
+        let mut next_arrival: usize = 0;
+        let mut number_of_finished: usize = 0;
+        let mut requeued: Vec<(usize, usize)> = Vec::new();
+
+        while number_of_finished < number_of_processes {
+            // Place the newly arrived processes.
+            while next_arrival < number_of_processes && to_ticks(processes[next_arrival].arrival_time) <= current_time {
+                let core: usize = self.place(&processes[next_arrival], &queues, &running);
+                let arrival_time: f64 = processes[next_arrival].arrival_time;
+                processes[next_arrival].transition(ProcessState::Ready, arrival_time);
+                queues[core].push_back(next_arrival);
+                next_arrival += 1;
+            }
+
+            // Put the preempted processes back at the tail of their run queue (after the new arrivals).
+            for (core, index) in requeued.drain(..) {
+                queues[core].push_back(index);
+            }
+
+            // Push migration.
+            if let (Some(interval), Some(balance_time)) = (balance_interval, next_balance) {
+                if current_time >= balance_time {
+                    self.push_migrate(current_time, &processes, &mut queues, &running);
+                    let mut balance_time: u64 = balance_time;
+                    while balance_time <= current_time {
+                        balance_time += interval;
+                    }
+                    next_balance = Some(balance_time);
+                }
+            }
+
+            // Dispatch a process on every idle core.
+            for core in 0..self.number_of_cores {
+                if running[core].is_some() {
+                    continue;
+                }
+                if queues[core].is_empty() && work_stealing {
+                    self.steal(core, current_time, &processes, &mut queues);
+                }
+                if let Some(index) = queues[core].pop_front() {
+                    // Pay the migration cost if the process last ran on another core.
+                    let mut slice_start: u64 = current_time;
+                    if migration_cost > 0 && last_core[index].is_some_and(|previous| previous != core) {
+                        slice_start += migration_cost;
+                        self.event_list.push(Event {
+                            name: "CS".to_string(),
+                            kind: EventKind::ContextSwitch,
+                            core,
+                            start_time: from_ticks(current_time),
+                            finish_time: from_ticks(slice_start),
+                        });
+                    }
+                    if last_core[index].is_none() {
+                        processes[index].start_time = from_ticks(slice_start);
+                    }
+                    // The migration counts as waiting, like a context switch.
+                    processes[index].waiting_time += from_ticks(slice_start - ready_since[index]);
+                    processes[index].transition(ProcessState::Running, from_ticks(slice_start));
+                    last_core[index] = Some(core);
+
+                    let time_run: u64 = quantum.min(remaining[index]);
+                    running[core] = Some((index, slice_start, slice_start + time_run));
+                }
+            }
+
+            // Find the next time something happens: a slice ends, a process arrives or the queues are balanced.
+            let mut next_time: u64 = u64::MAX;
+            for &(_, _, slice_end) in running.iter().flatten() {
+                next_time = next_time.min(slice_end);
+            }
+            if next_arrival < number_of_processes {
+                next_time = next_time.min(to_ticks(processes[next_arrival].arrival_time));
+            }
+            if let Some(balance_time) = next_balance {
+                if running.iter().any(|slot| slot.is_some()) {
+                    next_time = next_time.min(balance_time);
+                }
+            }
+            current_time = next_time;
+
+            // End the slices that finish now.
+            for (core, slot) in running.iter_mut().enumerate() {
+                let (index, slice_start, slice_end) = match *slot {
+                    Some(slice) if slice.2 == current_time => slice,
+                    _ => continue,
+                };
+                *slot = None;
+                remaining[index] -= slice_end - slice_start;
+                self.event_list.push(Event {
+                    name: format!("P{}", processes[index].pid),
+                    kind: EventKind::Process,
+                    core,
+                    start_time: from_ticks(slice_start),
+                    finish_time: from_ticks(slice_end),
+                });
+
+                if remaining[index] == 0 {
+                    // The process is done.
+                    let process: &mut Process = &mut processes[index];
+                    process.finish_time = from_ticks(slice_end);
+                    process.section_finish_time = process.finish_time;
+                    process.turn_around_time = process.finish_time - process.arrival_time;
+                    process.remaining_time = 0.0;
+                    process.transition(ProcessState::Terminated, process.finish_time);
+                    self.finished_processes.push(process.clone());
+                    number_of_finished += 1;
+                } else {
+                    ready_since[index] = current_time;
+                    processes[index].transition(ProcessState::Ready, from_ticks(current_time));
+                    requeued.push((core, index));
+                }
+            }
+        }
+
+        // Print the result.
+        if !self.quiet {
+            self.print();
+        }
+    }
+}
+
+// Test the load balancing policies.
+#[test]
+fn test() {
+    let workload = || -> Vec<Process> {
+        let mut processes: Vec<Process> = vec![
+            Process::new(1, 0.0, 4.0),
+            Process::new(2, 0.0, 4.0),
+            Process::new(3, 0.0, 4.0),
+            Process::new(4, 0.0, 4.0),
+        ];
+        // Every process prefers core 0, P4 may only run there.
+        for process in processes.iter_mut() {
+            process.set_affinity(u64::MAX, 0b01);
+        }
+        processes[3].set_affinity(0b01, 0b01);
+        processes
+    };
+
+    let mut pinned: MulticoreScheduler = MulticoreScheduler::new(workload(), 2);
+    pinned.quiet = true;
+    pinned.rr(2.0);
+    assert_eq!(pinned.makespan(), 16.0);
+    assert!(pinned.migration_list.is_empty());
+
+    let mut stealing: MulticoreScheduler = MulticoreScheduler::new(workload(), 2);
+    stealing.quiet = true;
+    stealing.set_load_balancing(LoadBalancing::WorkStealing);
+    stealing.set_migration_cost(0.5);
+    stealing.rr(2.0);
+    assert!(stealing.makespan() < 16.0);
+    assert!(stealing.migration_list.iter().all(|migration| migration.pid != 4));
+    assert!(stealing.event_list.iter().all(|event| event.name != "P4" || event.core == 0));
+
+    // P2 ran on core 0 and is stolen by core 1: it pays the migration in a context switch slice,
+    // which does not count as running time.
+    let context_switches: Vec<(usize, f64, f64)> = stealing.event_list
+        .iter()
+        .filter(|event| event.kind == EventKind::ContextSwitch)
+        .map(|event| (event.core, event.start_time, event.finish_time))
+        .collect();
+    assert_eq!(context_switches, vec![(1, 4.0, 4.5)]);
+    assert!(stealing.event_list.iter().any(|event| event.name == "P2" && event.core == 1 && event.start_time == 4.5));
+    let running_time: f64 = stealing.event_list
+        .iter()
+        .filter(|event| event.kind == EventKind::Process)
+        .map(|event| event.finish_time - event.start_time)
+        .sum();
+    assert_eq!(running_time, 16.0);
+}
\ No newline at end of file
@@ -1,16 +1,40 @@
+use std::cmp::Ordering;
+
 use priority_queue::DoublePriorityQueue;
 
-use super::Process;
+use super::{Process, ProcessState, Event, EventKind, ContextSwitch, number_of_context_switches, context_switch_overhead, cpu_efficiency};
+use super::{to_ticks, prediction::{ExponentialAverage, PredictionReport}};
+use crate::metrics::Metrics;
+use crate::gantt::GanttChart;
 
 pub struct NonpreemptiveScheduler {
     pub processes: Vec<Process>,
+    pub event_list: Vec<Event>,
     pub finished_processes: Vec<Process>,
+    pub context_switch: ContextSwitch,
+    pub quiet: bool,
 }
 
 // Common methods
 impl NonpreemptiveScheduler {
     pub fn new(processes: Vec<Process>) -> NonpreemptiveScheduler {
-        NonpreemptiveScheduler { processes, finished_processes: Vec::new() }
+        NonpreemptiveScheduler {
+            processes,
+            event_list: Vec::new(),
+            finished_processes: Vec::new(),
+            context_switch: ContextSwitch::None,
+            quiet: false,
+        }
+    }
+
+    /// Metrics of the last run, derived from the event timeline.
+    pub fn metrics(&self) -> Metrics {
+        Metrics::new(&self.finished_processes, &self.event_list, 1)
+    }
+
+    pub fn set_context_switch(&mut self, context_switch: ContextSwitch) {
+        context_switch.check();
+        self.context_switch = context_switch;
     }
 
     pub fn sort_by_arrival_time(&mut self) {
@@ -24,11 +48,36 @@ impl NonpreemptiveScheduler {
         process.turn_around_time = turn_around_time;
         process.remaining_time = 0.0;
         process.section_finish_time = finish_time;
+
+        // Record the context switch and the run in the timeline.
+        let context_switch_time: f64 = self.context_switch.cost_after(&self.event_list, process);
+        if context_switch_time > 0.0 {
+            self.event_list.push(Event {
+                name: "CS".to_string(),
+                kind: EventKind::ContextSwitch,
+                core: 0,
+                start_time: start_time - context_switch_time,
+                finish_time: start_time,
+            });
+        }
+        self.event_list.push(Event {
+            name: format!("P{}", process.pid),
+            kind: EventKind::Process,
+            core: 0,
+            start_time,
+            finish_time,
+        });
+
+        // Record the lifecycle of the process: it arrives, waits, runs once and terminates.
+        process.transition(ProcessState::Ready, process.arrival_time);
+        process.transition(ProcessState::Running, start_time);
+        process.transition(ProcessState::Terminated, finish_time);
     }
 
     pub fn calculate_time(&self, current_time: f64, process: &Process) -> (f64, f64, f64, f64) {
-        // Start time = current time.
-        let start_time: f64 = current_time;
+        // Start time = current time (or the arrival time if the CPU is idle until then) + context switch time.
+        let start_time: f64 = current_time.max(process.arrival_time)
+            + self.context_switch.cost_after(&self.event_list, process);
 
         // Finish time = start time + burst time.
         let finish_time: f64 = start_time + process.burst_time;
@@ -51,27 +100,25 @@ impl NonpreemptiveScheduler {
         let processes: Vec<Process> = self.finished_processes.clone();
 
         println!("Name\t\tArrival Time\tBurst Time\tTurn Around Time\tWaiting Time\tFinish Time");
-        for i in 0..processes.len() {
+        for process in processes.iter() {
             println!(
                 "P{}\t\t{:.2}\t\t{:.2}\t\t{:.2}\t\t\t{:.2}\t\t\t{:.2}",
-                processes[i].pid,
-                processes[i].arrival_time,
-                processes[i].burst_time,
-                processes[i].turn_around_time,
-                processes[i].waiting_time,
-                processes[i].finish_time
+                process.pid,
+                process.arrival_time,
+                process.burst_time,
+                process.turn_around_time,
+                process.waiting_time,
+                process.finish_time
             );
         }
         // Calculate average waiting time and average turn around time using list comprehension.
-        let average_waiting_time: f64 = self
-            .processes
+        let average_waiting_time: f64 = processes
             .iter()
             .map(|process| process.waiting_time)
             .sum::<f64>()
             / processes.len() as f64;
         
-        let average_turn_around_time: f64 = self
-            .processes
+        let average_turn_around_time: f64 = processes
             .iter()
             .map(|process| process.turn_around_time)
             .sum::<f64>()
@@ -83,30 +130,21 @@ impl NonpreemptiveScheduler {
             average_waiting_time,
         );
 
-        self.gantt_chart(&processes);
+        if self.context_switch != ContextSwitch::None {
+            println!(
+                "Context switches: {}, overhead: {:.2}, CPU efficiency: {:.2}%",
+                number_of_context_switches(&self.event_list),
+                context_switch_overhead(&self.event_list),
+                cpu_efficiency(&self.event_list) * 100.0,
+            );
+        }
+
+        self.gantt_chart();
     }
 
-    // Idea from: https://github.com/marvinjason/CPUScheduler
-    pub fn gantt_chart(&self, processes: &Vec<Process>) {
-        let mut gantt_chart: String = "\n\nGantt Chart:\n".to_string();
-        let mut time: f64 = 0.0;
-        let number_of_processes = processes.len();
-        if number_of_processes == 1 {
-            gantt_chart.push_str(&format!("{}\n", processes[0].waiting_time));
-            gantt_chart.push_str(&format!("|    P{}\n", processes[0].pid));
-            gantt_chart.push_str(&format!("{}\n", processes[0].finish_time));
-        } else {
-            gantt_chart.push_str(&format!("{}\n", processes[0].waiting_time));
-            gantt_chart.push_str(&format!("|    P{}\n", processes[0].pid));
-
-            for i in 1..processes.len() {
-                time += processes[i - 1].burst_time;
-                gantt_chart.push_str(&format!("{}\n", time));
-                gantt_chart.push_str(&format!("|    P{}\n", processes[i].pid));
-            }
-            gantt_chart.push_str(&format!("{}\n", processes[number_of_processes - 1].finish_time));
-        }
-        println!("{}", gantt_chart);
+    /// Horizontal chart of the timeline.
+    pub fn gantt_chart(&self) {
+        println!("\n\nGantt Chart:\n{}", GanttChart::new().render(&self.event_list));
     }
 }
 
@@ -139,85 +177,118 @@ impl NonpreemptiveScheduler {
         });
 
         // Print the result.
-        self.print();
+        if !self.quiet {
+            self.print();
+        }
     }
 
     // Shortest Job First (SJF).
     // Ref: https://github.com/KaoSon2004/OS/blob/main/SJF.cs
     pub fn sjf(&mut self) {
+        self.shortest_first(burst_key);
+
+        // Print the result.
+        if !self.quiet {
+            self.print();
+        }
+    }
+
+    // Predictive Shortest Job First.
+    // Same as SJF, but ordered by the burst predicted from the burst history of each process,
+    // since a real OS does not know the next burst. Returns the decisions next to the oracle SJF result.
+    pub fn predictive_sjf(&mut self, estimator: &ExponentialAverage) -> PredictionReport {
+        // Run the oracle SJF on a copy of the workload.
+        let mut oracle: NonpreemptiveScheduler = NonpreemptiveScheduler::new(self.processes.clone());
+        oracle.context_switch = self.context_switch;
+        oracle.quiet = true;
+        oracle.sjf();
+
+        self.shortest_first(|process: &Process| predicted_burst_key(estimator, process));
+        let report: PredictionReport = PredictionReport::new(
+            estimator, &self.finished_processes, &self.event_list, &oracle.finished_processes
+        );
+
+        // Print the result.
+        if !self.quiet {
+            self.print();
+            report.print();
+        }
+        report
+    }
+
+    // Priority Scheduling with aging.
+    // The priority of a waiting process improves by `aging_rate` levels per time unit, so low priority processes cannot starve.
+    pub fn ps_with_aging(&mut self, aging_rate: f64) {
+        if !aging_rate.is_finite() || aging_rate < 0.0 {
+            panic!("Aging rate must be a finite non negative number.");
+        }
+
         // Sort the processes by their arrival time.
         self.sort_by_arrival_time();
 
-        // A queue to store the processes that have arrived.
-        let mut queue: DoublePriorityQueue<Process, u32> = DoublePriorityQueue::new();
+        // The arrived processes; their priority changes with time, so they are not kept in a priority queue.
+        let mut queue: Vec<Process> = Vec::new();
 
         // Start the loop (while the queue or the processes list is not empty).
-        let mut current_time: f64 = self.processes[0].arrival_time;
+        let mut current_time: f64 = self.processes.first().map_or(0.0, |process| process.arrival_time);
         while !queue.is_empty() || !self.processes.is_empty() {
-            // While the processes list is not empty and the first process in the list has not arrived yet.
-            while !self.processes.is_empty() && self.processes[0].arrival_time <= current_time {
-                // Pop the first process from the processes list.
-                let process: Process = self.processes.remove(0);
-                let burst_time: f64 = process.burst_time;
-
-                // Add it to the queue.
-                queue.push(process, burst_time as u32);
+            // Move the arrived processes to the queue (compared in hundredths, as the clock drifts).
+            while !self.processes.is_empty() && to_ticks(self.processes[0].arrival_time) <= to_ticks(current_time) {
+                queue.push(self.processes.remove(0));
             }
 
-            // If the queue is not empty.
-            if !queue.is_empty() {
-                // Pop the process with the shortest burst time.
-                let mut process: Process = queue.pop_min().unwrap().0;
-
-                // Calculate the start time, finish time, waiting time and turn around time.
-                let time_tuple: (f64, f64, f64, f64) = self.calculate_time(current_time, &process);
-
-                // Update the selected process.
-                self.update_process(&mut process,
-                    time_tuple.0, time_tuple.1, 
-                    time_tuple.2, time_tuple.3);
+            // If the queue is empty: jump to the next arrival.
+            if queue.is_empty() {
+                current_time = self.processes[0].arrival_time;
+                continue;
+            }
 
-                // Update the current time.
-                current_time = time_tuple.1;
+            // Pick the process with the smallest aged priority (ties: earliest arrival, then smallest pid).
+            let position: usize = (0..queue.len())
+                .min_by(|&a, &b| aged_priority_order(&queue[a], &queue[b], aging_rate, current_time))
+                .unwrap();
+            let mut process: Process = queue.remove(position);
 
-                // Add the process to the finished processes list.
-                self.finished_processes.push(process); 
-            }
-            // If the queue is empty: Update the current time.
-            else {
-                current_time += 0.01;
-            }
+            // Calculate the times, update the process and the current time.
+            let time_tuple: (f64, f64, f64, f64) = self.calculate_time(current_time, &process);
+            self.update_process(&mut process,
+                time_tuple.0, time_tuple.1,
+                time_tuple.2, time_tuple.3);
+            current_time = time_tuple.1;
+            self.finished_processes.push(process);
         }
 
         // Print the result.
-        self.print();
+        if !self.quiet {
+            self.print();
+        }
     }
 
-    // Priority Scheduling (PS).
-    // Basically SJF but Smallest-Priority-First.
-    pub fn ps(&mut self) {
+    // Run the arrived process with the smallest key until it finishes (ties: earliest arrival, then smallest pid).
+    fn shortest_first(&mut self, key: impl Fn(&Process) -> u64) {
         // Sort the processes by their arrival time.
         self.sort_by_arrival_time();
 
         // A queue to store the processes that have arrived.
-        let mut queue: DoublePriorityQueue<Process, u32> = DoublePriorityQueue::new();
+        let mut queue: DoublePriorityQueue<Process, (u64, u64, u32)> = DoublePriorityQueue::new();
 
         // Start the loop (while the queue or the processes list is not empty).
         let mut current_time: f64 = self.processes[0].arrival_time;
         while !queue.is_empty() || !self.processes.is_empty() {
-            // While the processes list is not empty and the first process in the list has not arrived yet.
-            while !self.processes.is_empty() && self.processes[0].arrival_time <= current_time {
+            // While the processes list is not empty and the first process in the list has arrived.
+            // Compare in hundredths: the clock is a sum of f64 times and drifts off the arrival times.
+            while !self.processes.is_empty() && to_ticks(self.processes[0].arrival_time) <= to_ticks(current_time) {
                 // Pop the first process from the processes list.
                 let process: Process = self.processes.remove(0);
-                let priority: u32 = process.priority;
+                let priority: (u64, u64, u32) = shortest_first_order(key(&process), &process);
 
                 // Add it to the queue.
-                queue.push(process, priority as u32);
+                queue.push(process, priority);
             }
 
             // If the queue is not empty.
             if !queue.is_empty() {
-                // Pop the process with the shortest burst time.
+                // Pop the process with the smallest key.
                 let mut process: Process = queue.pop_min().unwrap().0;
 
                 // Calculate the start time, finish time, waiting time and turn around time.
@@ -225,29 +296,71 @@ impl NonpreemptiveScheduler {
 
                 // Update the selected process.
                 self.update_process(&mut process,
-                    time_tuple.0, time_tuple.1, 
+                    time_tuple.0, time_tuple.1,
                     time_tuple.2, time_tuple.3);
 
                 // Update the current time.
                 current_time = time_tuple.1;
 
                 // Add the process to the finished processes list.
-                self.finished_processes.push(process); 
+                self.finished_processes.push(process);
             }
-            // If the queue is empty: Update the current time.
+            // If the queue is empty: jump to the next arrival.
             else {
-                current_time += 0.01;
+                current_time = self.processes[0].arrival_time;
             }
         }
+    }
+
+    // Priority Scheduling (PS).
+    // Basically SJF but Smallest-Priority-First.
+    pub fn ps(&mut self) {
+        self.shortest_first(priority_key);
 
         // Print the result.
-        self.print();
+        if !self.quiet {
+            self.print();
+        }
     }
 }
 
+// Selection keys, shared with the step-wise simulation.
+
+/// Key of SJF: the burst time, in hundredths.
+pub(crate) fn burst_key(process: &Process) -> u64 {
+    to_ticks(process.burst_time)
+}
+
+/// Key of (preemptive) priority scheduling: the priority, smallest first.
+pub(crate) fn priority_key(process: &Process) -> u64 {
+    process.priority as u64
+}
+
+/// Key of predictive SJF: the predicted burst, in hundredths.
+pub(crate) fn predicted_burst_key(estimator: &ExponentialAverage, process: &Process) -> u64 {
+    to_ticks(estimator.predict(&process.burst_history))
+}
+
+/// Order of the ready processes of the shortest-first algorithms: the key, then earliest arrival, then smallest pid.
+pub(crate) fn shortest_first_order(key: u64, process: &Process) -> (u64, u64, u32) {
+    (key, to_ticks(process.arrival_time), process.pid)
+}
+
+/// Order of the ready processes of priority scheduling with aging at `current_time`:
+/// smallest aged priority, then earliest arrival, then smallest pid.
+pub(crate) fn aged_priority_order(a: &Process, b: &Process, aging_rate: f64, current_time: f64) -> Ordering {
+    let aged_priority = |process: &Process| -> f64 { process.priority as f64 - aging_rate * (current_time - process.arrival_time) };
+    aged_priority(a)
+        .total_cmp(&aged_priority(b))
+        .then(to_ticks(a.arrival_time).cmp(&to_ticks(b.arrival_time)))
+        .then(a.pid.cmp(&b.pid))
+}
+
 // Test the SJF, cfgtest
 #[test]
 fn test() {
+    use crate::workload::{WorkloadGenerator, BurstDistribution, PriorityDistribution};
+
     let mut scheduler: NonpreemptiveScheduler = NonpreemptiveScheduler::new(
         vec![
             Process::new(1, 0.0, 3.0),
@@ -261,4 +374,68 @@ fn test() {
 
     scheduler.sjf();
 
+    // FCFS leaves the CPU idle until the next arrival.
+    let mut scheduler: NonpreemptiveScheduler = NonpreemptiveScheduler::new(
+        vec![
+            Process::new(1, 0.0, 2.0),
+            Process::new(2, 5.0, 1.0),
+        ]
+    );
+    scheduler.quiet = true;
+    scheduler.fcfs();
+    let process: &Process = &scheduler.finished_processes[1];
+    assert_eq!((process.start_time, process.finish_time, process.waiting_time), (5.0, 6.0, 0.0));
+
+    // SJF compares the exact burst times and jumps over the idle gap.
+    let mut scheduler: NonpreemptiveScheduler = NonpreemptiveScheduler::new(
+        vec![
+            Process::new(1, 0.0, 2.0),
+            Process::new(2, 1.0, 1.5),
+            Process::new(3, 1.0, 1.2),
+            Process::new(4, 7.3, 1.0),
+        ]
+    );
+    scheduler.quiet = true;
+    scheduler.sjf();
+    let times: Vec<(u32, f64, f64)> = scheduler.finished_processes.iter().map(|process| (process.pid, process.start_time, process.finish_time)).collect();
+    assert_eq!(times, vec![(1, 0.0, 2.0), (3, 2.0, 3.2), (2, 3.2, 4.7), (4, 7.3, 8.3)]);
+
+    // The clock is a sum of hundredths: P14 arrives at 19.56, exactly when P13 finishes, and its 0.18 burst runs before P11.
+    let mut generator: WorkloadGenerator = WorkloadGenerator::new(634, 20);
+    generator.set_bursts(BurstDistribution::Exponential { mean: 3.0 });
+    let mut scheduler: NonpreemptiveScheduler = NonpreemptiveScheduler::new(generator.generate());
+    scheduler.set_context_switch(ContextSwitch::Fixed(0.25));
+    scheduler.quiet = true;
+    scheduler.sjf();
+    let order: Vec<u32> = scheduler.finished_processes.iter().map(|process| process.pid).collect();
+    let position = |pid: u32| order.iter().position(|other| *other == pid);
+    assert!(position(14) < position(11), "{:?}", order);
+    assert_eq!(to_ticks(scheduler.finished_processes[position(14).unwrap()].start_time), 1981);
+
+    // Same with aging: P7 arrives at 19.51, exactly when P3 finishes, and its priority wins over P6.
+    let mut generator: WorkloadGenerator = WorkloadGenerator::new(219, 20);
+    generator.set_bursts(BurstDistribution::Exponential { mean: 3.0 });
+    generator.set_priorities(PriorityDistribution::Uniform { min: 0, max: 9 });
+    let mut scheduler: NonpreemptiveScheduler = NonpreemptiveScheduler::new(generator.generate());
+    scheduler.set_context_switch(ContextSwitch::Fixed(0.25));
+    scheduler.quiet = true;
+    scheduler.ps_with_aging(0.1);
+    let order: Vec<u32> = scheduler.finished_processes.iter().map(|process| process.pid).collect();
+    let position = |pid: u32| order.iter().position(|other| *other == pid);
+    assert!(position(7) < position(6), "{:?}", order);
+    assert_eq!(to_ticks(scheduler.finished_processes[position(7).unwrap()].start_time), 1976);
+
+    // PS runs on the same loop: smallest priority first, ties by arrival, and no stepping over idle time.
+    let mut scheduler: NonpreemptiveScheduler = NonpreemptiveScheduler::new(
+        vec![
+            Process::new_with_priority(1, 0.0, 2.0, 3),
+            Process::new_with_priority(2, 0.5, 1.0, 2),
+            Process::new_with_priority(3, 1.0, 1.0, 1),
+            Process::new_with_priority(4, 6.3, 1.0, 1),
+        ]
+    );
+    scheduler.quiet = true;
+    scheduler.ps();
+    let times: Vec<(u32, f64, f64)> = scheduler.finished_processes.iter().map(|process| (process.pid, process.start_time, process.finish_time)).collect();
+    assert_eq!(times, vec![(1, 0.0, 2.0), (3, 2.0, 3.0), (2, 3.0, 4.0), (4, 6.3, 7.3)]);
 }
\ No newline at end of file
@@ -0,0 +1,149 @@
+use super::{Process, Event, EventKind};
+
+/// Exponential averaging of the previous CPU bursts: τ(n+1) = α·t(n) + (1−α)·τ(n).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExponentialAverage {
+    pub alpha: f64,
+    pub initial_guess: f64,
+}
+
+impl ExponentialAverage {
+    /// Constructor for `ExponentialAverage` struct, with α in [0, 1] and the initial guess τ0.
+    pub fn new(alpha: f64, initial_guess: f64) -> ExponentialAverage {
+        if !(0.0..=1.0).contains(&alpha) || initial_guess < 0.0 {
+            panic!("Alpha must be between 0 and 1 and the initial guess must be non negative.");
+        }
+        ExponentialAverage { alpha, initial_guess }
+    }
+
+    /// Every prediction τ0, τ1, ..., τn made while going through the burst history.
+    pub fn predictions(&self, burst_history: &[f64]) -> Vec<f64> {
+        let mut predictions: Vec<f64> = vec![self.initial_guess];
+        for &burst_time in burst_history {
+            let previous: f64 = *predictions.last().unwrap();
+            predictions.push(self.alpha * burst_time + (1.0 - self.alpha) * previous);
+        }
+        predictions
+    }
+
+    /// Prediction of the next CPU burst.
+    pub fn predict(&self, burst_history: &[f64]) -> f64 {
+        *self.predictions(burst_history).last().unwrap()
+    }
+}
+
+/// One scheduling decision of a predictive scheduler.
+#[derive(Debug, Clone)]
+pub struct Decision {
+    pub time: f64,
+    pub pid: u32,
+    /// Predicted (remaining) burst of the chosen process.
+    pub predicted_time: f64,
+    /// Actual (remaining) burst of the chosen process.
+    pub actual_time: f64,
+    /// Predicted - actual.
+    pub error: f64,
+    /// The process an oracle knowing the real bursts would have chosen.
+    pub oracle_pid: u32,
+}
+
+/// Predictive scheduling decisions next to the result of the oracle (true burst) scheduler.
+#[derive(Debug, Clone)]
+pub struct PredictionReport {
+    pub decisions: Vec<Decision>,
+    pub mean_absolute_error: f64,
+    pub average_waiting_time: f64,
+    pub average_turn_around_time: f64,
+    pub oracle_average_waiting_time: f64,
+    pub oracle_average_turn_around_time: f64,
+}
+
+impl PredictionReport {
+    /// Rebuild the decisions from the timeline: every slice starts with a decision.
+    pub fn new(estimator: &ExponentialAverage, finished_processes: &[Process], event_list: &[Event], oracle_processes: &[Process]) -> PredictionReport {
+        let executed_before = |pid: u32, time: f64| -> f64 {
+            event_list
+                .iter()
+                .filter(|event| event.kind == EventKind::Process && event.name == format!("P{}", pid) && event.finish_time <= time)
+                .map(|event| event.finish_time - event.start_time)
+                .sum()
+        };
+        let mut decisions: Vec<Decision> = Vec::new();
+        for event in event_list.iter().filter(|event| event.kind == EventKind::Process) {
+            let process: &Process = match finished_processes.iter().find(|process| format!("P{}", process.pid) == event.name) {
+                Some(process) => process,
+                None => continue,
+            };
+            let executed_time: f64 = executed_before(process.pid, event.start_time);
+            let predicted_time: f64 = (estimator.predict(&process.burst_history) - executed_time).max(0.0);
+            let actual_time: f64 = process.burst_time - executed_time;
+
+            // The oracle picks the ready process with the shortest actual remaining time.
+            let oracle_pid: u32 = finished_processes
+                .iter()
+                .filter(|other| other.arrival_time <= event.start_time && other.finish_time > event.start_time)
+                .map(|other| (other.burst_time - executed_before(other.pid, event.start_time), other.pid))
+                .min_by(|a, b| a.partial_cmp(b).unwrap())
+                .map_or(process.pid, |(_, pid)| pid);
+
+            decisions.push(Decision {
+                time: event.start_time,
+                pid: process.pid,
+                predicted_time,
+                actual_time,
+                error: predicted_time - actual_time,
+                oracle_pid,
+            });
+        }
+
+        let average = |processes: &[Process], time: fn(&Process) -> f64| -> f64 {
+            processes.iter().map(time).sum::<f64>() / processes.len() as f64
+        };
+        PredictionReport {
+            mean_absolute_error: decisions.iter().map(|decision| decision.error.abs()).sum::<f64>() / decisions.len().max(1) as f64,
+            decisions,
+            average_waiting_time: average(finished_processes, |process| process.waiting_time),
+            average_turn_around_time: average(finished_processes, |process| process.turn_around_time),
+            oracle_average_waiting_time: average(oracle_processes, |process| process.waiting_time),
+            oracle_average_turn_around_time: average(oracle_processes, |process| process.turn_around_time),
+        }
+    }
+
+    pub fn print(&self) {
+        println!("\nTime\t\tChosen\t\tPredicted\tActual\t\tError\t\tOracle");
+        for decision in self.decisions.iter() {
+            println!(
+                "{:.2}\t\tP{}\t\t{:.2}\t\t{:.2}\t\t{:+.2}\t\tP{}",
+                decision.time, decision.pid, decision.predicted_time, decision.actual_time, decision.error, decision.oracle_pid
+            );
+        }
+        println!("Mean absolute error: {:.2}", self.mean_absolute_error);
+        println!("\t\tTurn Around Time\tWaiting Time");
+        println!("Predictive:\t{:.2}\t\t\t{:.2}", self.average_turn_around_time, self.average_waiting_time);
+        println!("Oracle:\t\t{:.2}\t\t\t{:.2}", self.oracle_average_turn_around_time, self.oracle_average_waiting_time);
+    }
+}
+
+// Test the exponential average with the textbook example (α = 1/2, τ0 = 10).
+#[test]
+fn test() {
+    use super::nonpreemptive::NonpreemptiveScheduler;
+
+    let estimator: ExponentialAverage = ExponentialAverage::new(0.5, 10.0);
+    let predictions: Vec<f64> = estimator.predictions(&[6.0, 4.0, 6.0, 4.0, 13.0, 13.0, 13.0]);
+    assert_eq!(predictions, vec![10.0, 8.0, 6.0, 6.0, 5.0, 9.0, 11.0, 12.0]);
+
+    // P1 looks short from its history but is not: the predictive SJF runs it first, the oracle does not.
+    let mut p1: Process = Process::new(1, 0.0, 6.0);
+    p1.set_burst_history(vec![2.0, 2.0]);
+    let mut p2: Process = Process::new(2, 0.0, 3.0);
+    p2.set_burst_history(vec![8.0, 8.0]);
+    let mut scheduler: NonpreemptiveScheduler = NonpreemptiveScheduler::new(vec![p1, p2]);
+    scheduler.quiet = true;
+    let report: PredictionReport = scheduler.predictive_sjf(&estimator);
+    assert_eq!(report.decisions[0].pid, 1);
+    assert_eq!(report.decisions[0].oracle_pid, 2);
+    assert_eq!(report.decisions[0].error, -2.0);
+    assert_eq!(report.average_waiting_time, 3.0);
+    assert_eq!(report.oracle_average_waiting_time, 1.5);
+}
@@ -1,17 +1,40 @@
-use priority_queue::DoublePriorityQueue;
+use std::collections::VecDeque;
 
-use super::{Process, more_than_two_decimal_places, Event};
+use super::{Process, ProcessState, more_than_two_decimal_places, Event, EventKind, ContextSwitch, to_ticks, from_ticks};
+use super::{number_of_context_switches, context_switch_overhead, cpu_efficiency};
+use super::prediction::{ExponentialAverage, PredictionReport};
+use super::nonpreemptive::priority_key;
+use crate::metrics::Metrics;
+use crate::gantt::GanttChart;
 
 pub struct PreemptiveScheduler {
     pub processes: Vec<Process>,
     pub event_list: Vec<Event>,
     pub finished_processes: Vec<Process>,
+    pub context_switch: ContextSwitch,
+    pub quiet: bool,
 }
 
 // Common methods
 impl PreemptiveScheduler {
     pub fn new(processes: Vec<Process>) -> PreemptiveScheduler {
-        PreemptiveScheduler { processes, finished_processes: Vec::new(), event_list: Vec::new() }
+        PreemptiveScheduler {
+            processes,
+            finished_processes: Vec::new(),
+            event_list: Vec::new(),
+            context_switch: ContextSwitch::None,
+            quiet: false,
+        }
+    }
+
+    /// Metrics of the last run, derived from the event timeline.
+    pub fn metrics(&self) -> Metrics {
+        Metrics::new(&self.finished_processes, &self.event_list, 1)
+    }
+
+    pub fn set_context_switch(&mut self, context_switch: ContextSwitch) {
+        context_switch.check();
+        self.context_switch = context_switch;
     }
 
     pub fn sort_by_arrival_time(&mut self) {
@@ -24,7 +47,73 @@ impl PreemptiveScheduler {
         process.waiting_time = waiting_time;
         process.turn_around_time = turn_around_time;
         process.remaining_time = 0.0;
-        todo!()
+        process.section_finish_time = finish_time;
+    }
+
+    /// Move the processes that have arrived by `current_time` to the ready queue.
+    pub fn admit(&mut self, current_time: u64, queue: &mut VecDeque<Process>) {
+        while !self.processes.is_empty() && to_ticks(self.processes[0].arrival_time) <= current_time {
+            let mut process: Process = self.processes.remove(0);
+            process.section_finish_time = process.arrival_time;
+            process.transition(ProcessState::Ready, process.arrival_time);
+            queue.push_back(process);
+        }
+    }
+
+    /// Switch the CPU to `process`: record the context switch slice and return the time it ends.
+    pub fn switch_to(&mut self, process: &Process, current_time: u64) -> u64 {
+        let context_switch_time: u64 = to_ticks(self.context_switch.cost_after(&self.event_list, process));
+        if context_switch_time > 0 {
+            self.event_list.push(Event {
+                name: "CS".to_string(),
+                kind: EventKind::ContextSwitch,
+                core: 0,
+                start_time: from_ticks(current_time),
+                finish_time: from_ticks(current_time + context_switch_time),
+            });
+        }
+        current_time + context_switch_time
+    }
+
+    /// Give the CPU to a ready process.
+    pub fn dispatch(&mut self, process: &mut Process, current_time: u64) {
+        if process.state_history.iter().all(|transition| transition.to != ProcessState::Running) {
+            process.start_time = from_ticks(current_time);
+        }
+        process.waiting_time += from_ticks(current_time) - process.section_finish_time;
+        process.transition(ProcessState::Running, from_ticks(current_time));
+    }
+
+    /// Run the process from `start_time` to `finish_time`, extending its previous slice if they touch.
+    pub fn run(&mut self, process: &mut Process, start_time: u64, finish_time: u64, merge: bool) {
+        process.remaining_time = from_ticks(to_ticks(process.remaining_time) - (finish_time - start_time));
+        process.section_finish_time = from_ticks(finish_time);
+
+        let name: String = format!("P{}", process.pid);
+        if let Some(event) = self.event_list.last_mut() {
+            if merge && event.name == name && to_ticks(event.finish_time) == start_time {
+                event.finish_time = from_ticks(finish_time);
+                return;
+            }
+        }
+        self.event_list.push(Event {
+            name,
+            kind: EventKind::Process,
+            core: 0,
+            start_time: from_ticks(start_time),
+            finish_time: from_ticks(finish_time),
+        });
+    }
+
+    /// Record the end of a process.
+    pub fn finish(&mut self, mut process: Process, current_time: u64) {
+        let finish_time: f64 = from_ticks(current_time);
+        let turn_around_time: f64 = finish_time - process.arrival_time;
+        let waiting_time: f64 = process.waiting_time;
+        let start_time: f64 = process.start_time;
+        self.update_process(&mut process, start_time, finish_time, waiting_time, turn_around_time);
+        process.transition(ProcessState::Terminated, finish_time);
+        self.finished_processes.push(process);
     }
 
     pub fn calculate_time(&self, current_time: f64, process: &Process) -> (f64, f64, f64, f64) {
@@ -52,27 +141,25 @@ impl PreemptiveScheduler {
         let processes: Vec<Process> = self.finished_processes.clone();
 
         println!("Name\t\tArrival Time\tBurst Time\tTurn Around Time\tWaiting Time\tFinish Time");
-        for i in 0..processes.len() {
+        for process in processes.iter() {
             println!(
                 "P{}\t\t{:.2}\t\t{:.2}\t\t{:.2}\t\t\t{:.2}\t\t\t{:.2}",
-                processes[i].pid,
-                processes[i].arrival_time,
-                processes[i].burst_time,
-                processes[i].turn_around_time,
-                processes[i].waiting_time,
-                processes[i].finish_time
+                process.pid,
+                process.arrival_time,
+                process.burst_time,
+                process.turn_around_time,
+                process.waiting_time,
+                process.finish_time
             );
         }
         // Calculate average waiting time and average turn around time using list comprehension.
-        let average_waiting_time: f64 = self
-            .processes
+        let average_waiting_time: f64 = processes
             .iter()
             .map(|process| process.waiting_time)
             .sum::<f64>()
             / processes.len() as f64;
         
-        let average_turn_around_time: f64 = self
-            .processes
+        let average_turn_around_time: f64 = processes
             .iter()
             .map(|process| process.turn_around_time)
             .sum::<f64>()
@@ -84,30 +171,21 @@ impl PreemptiveScheduler {
             average_waiting_time,
         );
 
-        self.gantt_chart(&processes);
-    }
-
-    // Idea from: https://github.com/marvinjason/CPUScheduler
-    pub fn gantt_chart(&self, processes: &Vec<Process>) {
-        let mut gantt_chart: String = "\n\nGantt Chart:\n".to_string();
-        let mut time: f64 = 0.0;
-        let number_of_processes = processes.len();
-        if number_of_processes == 1 {
-            gantt_chart.push_str(&format!("{}\n", processes[0].waiting_time));
-            gantt_chart.push_str(&format!("|    P{}\n", processes[0].pid));
-            gantt_chart.push_str(&format!("{}\n", processes[0].finish_time));
-        } else {
-            gantt_chart.push_str(&format!("{}\n", processes[0].waiting_time));
-            gantt_chart.push_str(&format!("|    P{}\n", processes[0].pid));
-
-            for i in 1..processes.len() {
-                time += processes[i - 1].burst_time;
-                gantt_chart.push_str(&format!("{}\n", time));
-                gantt_chart.push_str(&format!("|    P{}\n", processes[i].pid));
-            }
-            gantt_chart.push_str(&format!("{}\n", processes[number_of_processes - 1].finish_time));
+        if self.context_switch != ContextSwitch::None {
+            println!(
+                "Context switches: {}, overhead: {:.2}, CPU efficiency: {:.2}%",
+                number_of_context_switches(&self.event_list),
+                context_switch_overhead(&self.event_list),
+                cpu_efficiency(&self.event_list) * 100.0,
+            );
         }
-        println!("{}", gantt_chart);
+
+        self.gantt_chart();
+    }
+
+    /// Horizontal chart of the timeline.
+    pub fn gantt_chart(&self) {
+        println!("\n\nGantt Chart:\n{}", GanttChart::new().render(&self.event_list));
     }
 }
 
@@ -119,46 +197,257 @@ impl PreemptiveScheduler {
         if time_quantum <= 0.0 || more_than_two_decimal_places(time_quantum) {
             panic!("Invalid time quantum!");
         }
-
-        // Count the number of processes.
-        let number_of_processes: usize = self.processes.len();
+        let quantum: u64 = to_ticks(time_quantum);
 
         // Sort the processes by arrival time.
         self.sort_by_arrival_time();
 
-        // The current time.
-        let mut current_time: f64 = 0.0;
+        // The ready queue and the current time.
+        let mut queue: VecDeque<Process> = VecDeque::new();
+        let mut current_time: u64 = self.processes.first().map_or(0, |process| to_ticks(process.arrival_time));
 
         // While there are still processes to be executed.
-        while !self.processes.is_empty() {
-            // Get the first process.
-            let mut current_process: Process = self.processes.remove(0);
+        while !queue.is_empty() || !self.processes.is_empty() {
+            // Move the arrived processes to the queue.
+            self.admit(current_time, &mut queue);
 
-            // Alter the current process' remaining time.
-            let time_run: f64 = if current_process.remaining_time > time_quantum {
-                time_quantum
-            } else {
-                current_process.remaining_time
+            // If the queue is empty: jump to the next arrival.
+            let mut process: Process = match queue.pop_front() {
+                Some(process) => process,
+                None => {
+                    current_time = to_ticks(self.processes[0].arrival_time);
+                    continue;
+                }
             };
 
-            current_process.remaining_time -= time_run;
-
-            // Calculate the times.
+            // Run the process for one quantum or until it finishes.
+            let time_run: u64 = quantum.min(to_ticks(process.remaining_time));
+            current_time = self.switch_to(&process, current_time);
+            self.dispatch(&mut process, current_time);
+            self.run(&mut process, current_time, current_time + time_run, false);
             current_time += time_run;
-            current_process.waiting_time += current_time - time_run - current_process.section_finish_time;
-            current_process.section_finish_time = current_time;
+
+            // The processes that arrived during the slice are queued before the preempted one.
+            self.admit(current_time, &mut queue);
+            if to_ticks(process.remaining_time) == 0 {
+                self.finish(process, current_time);
+            } else {
+                process.transition(ProcessState::Ready, from_ticks(current_time));
+                queue.push_back(process);
+            }
+        }
+
+        // Print the result.
+        if !self.quiet {
+            self.print();
         }
     }
 
     // Shortest Remaining Time First (SRTF).
     // Basically SJF but with preemption.
     pub fn srtf(&mut self) {
-        
+        self.preemptive(remaining_time_key);
+
+        // Print the result.
+        if !self.quiet {
+            self.print();
+        }
+    }
+
+    // Predictive Shortest Remaining Time First.
+    // Same as SRTF, but the remaining time is the predicted burst (from the burst history) minus the time already run.
+    // Returns the decisions next to the oracle SRTF result.
+    pub fn predictive_srtf(&mut self, estimator: &ExponentialAverage) -> PredictionReport {
+        // Run the oracle SRTF on a copy of the workload.
+        let mut oracle: PreemptiveScheduler = PreemptiveScheduler::new(self.processes.clone());
+        oracle.context_switch = self.context_switch;
+        oracle.quiet = true;
+        oracle.srtf();
+
+        self.preemptive(|process: &Process| predicted_remaining_time_key(estimator, process));
+        let report: PredictionReport = PredictionReport::new(
+            estimator, &self.finished_processes, &self.event_list, &oracle.finished_processes
+        );
+
+        // Print the result.
+        if !self.quiet {
+            self.print();
+            report.print();
+        }
+        report
     }
 
     // Priority Scheduling (PS).
     // Basically SJF but Smallest-Priority-First.
     pub fn ps(&mut self) {
-        
+        self.preemptive(priority_key);
+
+        // Print the result.
+        if !self.quiet {
+            self.print();
+        }
     }
+
+    // Run the ready process with the smallest key; a newly arrived process with a smaller key preempts it.
+    // Ties keep the running process, then favour the process that has been ready the longest.
+    fn preemptive(&mut self, key: impl Fn(&Process) -> u64) {
+        // Sort the processes by arrival time.
+        self.sort_by_arrival_time();
+
+        let mut queue: VecDeque<Process> = VecDeque::new();
+        let mut running: Option<Process> = None;
+        let mut current_time: u64 = self.processes.first().map_or(0, |process| to_ticks(process.arrival_time));
+
+        while running.is_some() || !queue.is_empty() || !self.processes.is_empty() {
+            // Move the arrived processes to the queue.
+            self.admit(current_time, &mut queue);
+
+            // Find the best ready process (the queue is in the order processes became ready, so the first minimum wins ties).
+            let best: Option<usize> = (0..queue.len()).min_by_key(|&position| key(&queue[position]));
+
+            // Preempt the running process if the best ready process has a smaller key.
+            if let (Some(position), Some(process)) = (best, running.as_ref()) {
+                if key(&queue[position]) < key(process) {
+                    let mut process: Process = running.take().unwrap();
+                    process.transition(ProcessState::Ready, from_ticks(current_time));
+                    queue.push_back(process);
+                }
+            }
+
+            // Dispatch the best ready process on the idle CPU.
+            if running.is_none() {
+                let best: Option<usize> = (0..queue.len()).min_by_key(|&position| key(&queue[position]));
+                match best {
+                    Some(position) => {
+                        let mut process: Process = queue.remove(position).unwrap();
+                        current_time = self.switch_to(&process, current_time);
+
+                        // A process that arrived during the context switch with a smaller key preempts the process
+                        // before it runs (it stays where it was in the queue, the arrivals are queued after it).
+                        self.admit(current_time, &mut queue);
+                        if queue.iter().any(|ready| key(ready) < key(&process)) {
+                            queue.insert(position, process);
+                            continue;
+                        }
+                        self.dispatch(&mut process, current_time);
+                        running = Some(process);
+                    }
+                    None => {
+                        // If the queue is empty: jump to the next arrival.
+                        current_time = to_ticks(self.processes[0].arrival_time);
+                        continue;
+                    }
+                }
+            }
+
+            // Run until the process finishes or the next process arrives.
+            let mut process: Process = running.take().unwrap();
+            let mut next_time: u64 = current_time + to_ticks(process.remaining_time);
+            if let Some(next_process) = self.processes.first() {
+                next_time = next_time.min(to_ticks(next_process.arrival_time));
+            }
+            self.run(&mut process, current_time, next_time, true);
+            current_time = next_time;
+
+            if to_ticks(process.remaining_time) == 0 {
+                self.finish(process, current_time);
+            } else {
+                running = Some(process);
+            }
+        }
+    }
+}
+
+// Selection keys, shared with the step-wise simulation.
+
+/// Key of SRTF: the remaining time, in hundredths.
+pub(crate) fn remaining_time_key(process: &Process) -> u64 {
+    to_ticks(process.remaining_time)
+}
+
+/// Key of predictive SRTF: the predicted burst minus the time already run, in hundredths.
+pub(crate) fn predicted_remaining_time_key(estimator: &ExponentialAverage, process: &Process) -> u64 {
+    let executed_time: f64 = process.burst_time - process.remaining_time;
+    to_ticks((estimator.predict(&process.burst_history) - executed_time).max(0.0))
+}
+
+// Test RR, SRTF and PS, with the state history of the processes.
+#[test]
+fn test() {
+    let mut scheduler: PreemptiveScheduler = PreemptiveScheduler::new(
+        vec![
+            Process::new(1, 0.0, 5.0),
+            Process::new(2, 1.0, 3.0),
+            Process::new(3, 2.0, 1.0),
+        ]
+    );
+    scheduler.quiet = true;
+    scheduler.rr(2.0);
+    let finish_times: Vec<(u32, f64)> = scheduler.finished_processes.iter().map(|process| (process.pid, process.finish_time)).collect();
+    assert_eq!(finish_times, vec![(3, 5.0), (2, 8.0), (1, 9.0)]);
+    assert_eq!(
+        scheduler.finished_processes[2].state_diagram(),
+        "P1: New -(0.00)-> Ready -(0.00)-> Running -(2.00)-> Ready -(5.00)-> Running -(7.00)-> Ready -(8.00)-> Running -(9.00)-> Terminated"
+    );
+
+    // Silberschatz's SRTF example: average waiting time 6.5.
+    let mut scheduler: PreemptiveScheduler = PreemptiveScheduler::new(
+        vec![
+            Process::new(1, 0.0, 8.0),
+            Process::new(2, 1.0, 4.0),
+            Process::new(3, 2.0, 9.0),
+            Process::new(4, 3.0, 5.0),
+        ]
+    );
+    scheduler.quiet = true;
+    scheduler.srtf();
+    let total_waiting_time: f64 = scheduler.finished_processes.iter().map(|process| process.waiting_time).sum();
+    assert_eq!(total_waiting_time / 4.0, 6.5);
+
+    // A newly arrived process with a smaller priority preempts the running one; the CPU idles until 6.
+    let mut scheduler: PreemptiveScheduler = PreemptiveScheduler::new(
+        vec![
+            Process::new_with_priority(1, 0.0, 4.0, 3),
+            Process::new_with_priority(2, 1.0, 2.0, 1),
+            Process::new_with_priority(3, 6.0, 1.0, 2),
+        ]
+    );
+    scheduler.quiet = true;
+    scheduler.ps();
+    let finish_times: Vec<(u32, f64, f64)> = scheduler.finished_processes.iter().map(|process| (process.pid, process.finish_time, process.waiting_time)).collect();
+    assert_eq!(finish_times, vec![(2, 3.0, 0.0), (1, 6.0, 2.0), (3, 7.0, 0.0)]);
+
+    // A smaller quantum means more context switches and a lower CPU efficiency.
+    let efficiency = |time_quantum: f64| -> f64 {
+        let mut scheduler: PreemptiveScheduler = PreemptiveScheduler::new(
+            vec![Process::new(1, 0.0, 4.0), Process::new(2, 0.0, 4.0)]
+        );
+        scheduler.set_context_switch(ContextSwitch::PerSwitch(0.1));
+        scheduler.quiet = true;
+        scheduler.rr(time_quantum);
+        cpu_efficiency(&scheduler.event_list)
+    };
+    assert!(efficiency(0.5) < efficiency(2.0));
+    assert!((efficiency(4.0) - 8.0 / 8.2).abs() < 1e-9);
+
+    // P2 arrives during the context switch to P1 and has a shorter remaining time: it preempts P1 before P1 runs.
+    let mut scheduler: PreemptiveScheduler = PreemptiveScheduler::new(vec![Process::new(1, 0.0, 5.0), Process::new(2, 0.5, 1.0)]);
+    scheduler.set_context_switch(ContextSwitch::PerSwitch(1.0));
+    scheduler.quiet = true;
+    scheduler.srtf();
+    let timeline: Vec<(String, f64, f64)> = scheduler.event_list.iter().map(|event| (event.name.clone(), event.start_time, event.finish_time)).collect();
+    assert_eq!(
+        timeline,
+        vec![
+            (String::from("CS"), 0.0, 1.0),
+            (String::from("CS"), 1.0, 2.0),
+            (String::from("P2"), 2.0, 3.0),
+            (String::from("CS"), 3.0, 4.0),
+            (String::from("P1"), 4.0, 9.0),
+        ]
+    );
+    assert_eq!(
+        scheduler.finished_processes[1].state_diagram(),
+        "P1: New -(0.00)-> Ready -(4.00)-> Running -(9.00)-> Terminated"
+    );
 }
\ No newline at end of file
@@ -0,0 +1,223 @@
+// Property based tests: every scheduler runs on many generated workloads and the invariants are checked on each result.
+// A failing case reports its seed; `WorkloadGenerator::generate_with_seed` rebuilds the workload.
+
+use super::{Process, Burst, ContextSwitch};
+use super::multicore::{MulticoreScheduler, LoadBalancing};
+use super::gang::GangScheduler;
+use super::io::{IoScheduler, CpuPolicy};
+use super::realtime::{RealTimeScheduler, Task};
+use crate::schedule::{Algorithm, ScheduleResult};
+use crate::validator::{Validator, run_and_validate};
+use crate::workload::{WorkloadGenerator, Arrivals, BurstDistribution, PriorityDistribution, IoMix, Rng};
+
+const NUMBER_OF_CASES: u64 = 100;
+
+/// A small workload whose shape depends on the seed.
+fn generator(seed: u64) -> WorkloadGenerator {
+    let mut generator: WorkloadGenerator = WorkloadGenerator::new(seed, 1 + (seed % 12) as usize);
+    generator.set_bursts(match seed % 4 {
+        0 => BurstDistribution::Exponential { mean: 4.0 },
+        1 => BurstDistribution::Uniform { min: 0.5, max: 10.0 },
+        2 => BurstDistribution::Bimodal { short_mean: 1.0, long_mean: 12.0, long_fraction: 0.2 },
+        _ => BurstDistribution::Pareto { scale: 1.0, shape: 1.5 },
+    });
+    generator.set_arrivals(if seed.is_multiple_of(5) { Arrivals::Simultaneous } else { Arrivals::Poisson { rate: 0.4 } });
+    generator.set_priorities(PriorityDistribution::Uniform { min: 0, max: 4 });
+    generator
+}
+
+fn average_waiting_time(result: &ScheduleResult) -> f64 {
+    result.finished_processes.iter().map(|process| process.waiting_time).sum::<f64>() / result.finished_processes.len() as f64
+}
+
+// Every algorithm keeps the invariants, with and without context switch costs.
+#[test]
+fn conservation() {
+    let context_switches: [ContextSwitch; 3] = [ContextSwitch::None, ContextSwitch::Fixed(0.1), ContextSwitch::PerSwitch(0.25)];
+    for seed in 0..NUMBER_OF_CASES {
+        let workload: Vec<Process> = generator(seed).generate();
+        let context_switch: ContextSwitch = context_switches[seed as usize % 3];
+        let algorithms: Vec<Algorithm> = Algorithm::all(0.5 + (seed % 4) as f64, vec![1.0, 2.0, 4.0], 0.5, 0.5, 3.0);
+        for algorithm in algorithms.iter().filter(|algorithm| algorithm.check_context_switch(context_switch).is_ok()) {
+            let result: ScheduleResult = run_and_validate(algorithm, &workload, context_switch);
+            assert_eq!(result.finished_processes.len(), workload.len(), "{} lost processes (seed {})", algorithm.name(), seed);
+            for process in result.finished_processes.iter() {
+                assert!(
+                    (process.finish_time - process.arrival_time - process.turn_around_time).abs() < 1e-6,
+                    "{}: P{} turn around time (seed {})", algorithm.name(), process.pid, seed
+                );
+            }
+        }
+    }
+}
+
+// With simultaneous arrivals SJF minimizes the average waiting time among nonpreemptive algorithms,
+// and SRTF minimizes it among all algorithms whatever the arrivals.
+#[test]
+fn optimality() {
+    for seed in 0..NUMBER_OF_CASES {
+        let mut generator: WorkloadGenerator = generator(seed);
+        let workload: Vec<Process> = generator.generate();
+        let srtf: f64 = average_waiting_time(&run_and_validate(&Algorithm::Srtf, &workload, ContextSwitch::None));
+        for algorithm in Algorithm::all(2.0, vec![1.0, 2.0], 0.5, 0.5, 3.0) {
+            let other: f64 = average_waiting_time(&run_and_validate(&algorithm, &workload, ContextSwitch::None));
+            assert!(srtf <= other + 1e-6, "SRTF {:.2} > {} {:.2} (seed {})", srtf, algorithm.name(), other, seed);
+        }
+
+        generator.set_arrivals(Arrivals::Simultaneous);
+        let workload: Vec<Process> = generator.generate();
+        let sjf: f64 = average_waiting_time(&run_and_validate(&Algorithm::Sjf, &workload, ContextSwitch::None));
+        for algorithm in [Algorithm::Fcfs, Algorithm::Priority, Algorithm::PriorityWithAging { aging_rate: 1.0 }] {
+            let other: f64 = average_waiting_time(&run_and_validate(&algorithm, &workload, ContextSwitch::None));
+            assert!(sjf <= other + 1e-6, "SJF {:.2} > {} {:.2} (seed {})", sjf, algorithm.name(), other, seed);
+        }
+    }
+}
+
+// With context switch costs, SRTF never starts a slice while an arrived process has a shorter remaining time,
+// including the processes that arrived during the context switch before the slice.
+#[test]
+fn srtf_with_context_switch() {
+    for seed in 0..NUMBER_OF_CASES {
+        let workload: Vec<Process> = generator(seed).generate();
+        let result: ScheduleResult = run_and_validate(&Algorithm::Srtf, &workload, ContextSwitch::PerSwitch(0.5));
+        let remaining_time = |process: &Process, time: f64| -> f64 {
+            let run_time: f64 = result
+                .event_list
+                .iter()
+                .filter(|event| event.pid() == Some(process.pid) && event.finish_time <= time + 1e-6)
+                .map(|event| event.duration())
+                .sum();
+            process.burst_time - run_time
+        };
+        for event in result.event_list.iter() {
+            let Some(running) = workload.iter().find(|process| event.pid() == Some(process.pid)) else {
+                continue;
+            };
+            let running_remaining_time: f64 = remaining_time(running, event.start_time);
+            for process in workload.iter() {
+                let remaining: f64 = remaining_time(process, event.start_time);
+                if process.pid != running.pid && process.arrival_time <= event.start_time + 1e-6 && remaining > 1e-6 {
+                    assert!(
+                        remaining >= running_remaining_time - 1e-6,
+                        "P{} runs at {:.2} while P{} has less left (seed {})", running.pid, event.start_time, process.pid, seed
+                    );
+                }
+            }
+        }
+    }
+}
+
+// FCFS runs the processes in arrival order (ties in workload order).
+#[test]
+fn fcfs_order() {
+    for seed in 0..NUMBER_OF_CASES {
+        let workload: Vec<Process> = generator(seed).generate();
+        let result: ScheduleResult = run_and_validate(&Algorithm::Fcfs, &workload, ContextSwitch::Fixed(0.05));
+        let mut processes: Vec<&Process> = result.finished_processes.iter().collect();
+        processes.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+        let order: Vec<u32> = processes.iter().map(|process| process.pid).collect();
+        let expected: Vec<u32> = workload.iter().map(|process| process.pid).collect();
+        assert_eq!(order, expected, "seed {}", seed);
+    }
+}
+
+// The multi-core, gang and I/O schedulers conserve the work of every process.
+#[test]
+fn other_schedulers() {
+    let load_balancing: [LoadBalancing; 4] = [
+        LoadBalancing::None,
+        LoadBalancing::PushMigration { interval: 2.0 },
+        LoadBalancing::WorkStealing,
+        LoadBalancing::PushAndPull { interval: 3.0 },
+    ];
+    for seed in 0..NUMBER_OF_CASES / 4 {
+        let workload: Vec<Process> = generator(seed).generate();
+        let number_of_cores: usize = 1 + seed as usize % 4;
+
+        let mut scheduler: MulticoreScheduler = MulticoreScheduler::new(workload.clone(), number_of_cores);
+        scheduler.quiet = true;
+        scheduler.set_load_balancing(load_balancing[seed as usize % 4]);
+        scheduler.set_migration_cost(0.25);
+        scheduler.rr(1.5);
+        assert_eq!(scheduler.finished_processes.len(), workload.len(), "multicore (seed {})", seed);
+        Validator::new().assert_valid(&workload, &scheduler.event_list, &scheduler.finished_processes);
+
+        // Split every process into up to `number_of_cores` threads.
+        let mut rng: Rng = Rng::new(seed);
+        let threaded: Vec<Process> = workload
+            .iter()
+            .map(|process| {
+                let number_of_threads: u64 = rng.uniform_integer(1, number_of_cores as u64);
+                let bursts: Vec<f64> = (0..number_of_threads).map(|_| BurstDistribution::Uniform { min: 0.5, max: 4.0 }.sample(&mut rng)).collect();
+                Process::new_with_threads(process.pid, process.arrival_time, bursts)
+            })
+            .collect();
+        for gang in [true, false] {
+            let mut scheduler: GangScheduler = GangScheduler::new(threaded.clone(), number_of_cores);
+            scheduler.quiet = true;
+            if gang { scheduler.gang(1.0) } else { scheduler.independent(1.0) }
+            assert_eq!(scheduler.finished_processes.len(), threaded.len(), "gang (seed {})", seed);
+            Validator::new().assert_valid(&threaded, &scheduler.event_list, &scheduler.finished_processes);
+        }
+
+        let mut generator: WorkloadGenerator = generator(seed);
+        generator.set_io_mix(IoMix {
+            io_bound_fraction: 0.5,
+            cpu_bursts: 3,
+            cpu_burst: BurstDistribution::Exponential { mean: 1.0 },
+            io_burst: BurstDistribution::Uniform { min: 1.0, max: 5.0 },
+            number_of_devices: 2,
+        });
+        let workload: Vec<Process> = generator.generate();
+        let policies: [CpuPolicy; 4] = [
+            CpuPolicy::Fcfs,
+            CpuPolicy::Sjf,
+            CpuPolicy::Rr { time_quantum: 1.0 },
+            CpuPolicy::Mlfq { time_quanta: vec![0.5, 1.0, 2.0] },
+        ];
+        for policy in policies {
+            let nonpreemptive: bool = matches!(policy, CpuPolicy::Fcfs | CpuPolicy::Sjf);
+            let mut scheduler: IoScheduler = IoScheduler::new(workload.clone(), vec!["disk".to_string(), "network".to_string()]);
+            scheduler.quiet = true;
+            scheduler.run(policy);
+            assert_eq!(scheduler.finished_processes.len(), workload.len(), "I/O (seed {})", seed);
+            Validator { nonpreemptive, work_conserving: true }.assert_valid(&workload, &scheduler.event_list, &scheduler.finished_processes);
+
+            // Every I/O burst is served once, for its whole length.
+            let io_time: f64 = workload.iter().map(|process| process.io_time()).sum();
+            let device_time: f64 = scheduler.device_event_list.iter().map(|event| event.duration()).sum();
+            assert!((io_time - device_time).abs() < 1e-6, "I/O time (seed {})", seed);
+            let io_bursts: usize = workload
+                .iter()
+                .flat_map(|process| process.burst_sequence())
+                .filter(|burst| matches!(burst, Burst::Io { .. }))
+                .count();
+            assert_eq!(scheduler.device_event_list.len(), io_bursts, "I/O bursts (seed {})", seed);
+        }
+    }
+}
+
+// On one core, EDF meets every deadline of an implicit deadline task set with a utilization of at most 1.
+#[test]
+fn edf_feasibility() {
+    let periods: [f64; 4] = [2.0, 4.0, 5.0, 10.0];
+    for seed in 0..NUMBER_OF_CASES / 4 {
+        let mut rng: Rng = Rng::new(seed);
+        let mut tasks: Vec<Task> = Vec::new();
+        let mut utilization: f64 = 0.0;
+        for id in 1..=4 {
+            let period: f64 = periods[rng.uniform_integer(0, 3) as usize];
+            let execution_time: f64 = ((rng.uniform(0.05, 0.4) * period) * 100.0).floor() / 100.0;
+            if utilization + execution_time / period > 1.0 {
+                break;
+            }
+            utilization += execution_time / period;
+            tasks.push(Task::new(id, execution_time.max(0.01), period));
+        }
+        let mut scheduler: RealTimeScheduler = RealTimeScheduler::new(tasks, 1);
+        scheduler.quiet = true;
+        scheduler.global_edf(20.0);
+        assert!(scheduler.deadline_misses.is_empty(), "U = {:.3} (seed {})", utilization, seed);
+    }
+}
@@ -0,0 +1,535 @@
+use super::{Event, EventKind, more_than_two_decimal_places, to_ticks, from_ticks};
+use crate::gantt::GanttChart;
+
+/// `Task` struct: a periodic real-time task released at time 0 and then every `period`.
+#[derive(Debug, Clone)]
+pub struct Task {
+    pub id: u32,
+    pub execution_time: f64,
+    pub period: f64,
+    pub deadline: f64,
+}
+
+impl Task {
+    /// Constructor for `Task` struct with an implicit deadline (deadline = period).
+    pub fn new(id: u32, execution_time: f64, period: f64) -> Task {
+        Task::new_with_deadline(id, execution_time, period, period)
+    }
+
+    /// Constructor for `Task` struct with a constrained deadline (deadline <= period).
+    pub fn new_with_deadline(id: u32, execution_time: f64, period: f64, deadline: f64) -> Task {
+        let positive: bool = execution_time > 0.0 && period > 0.0 && deadline > 0.0;
+        let more_than_two_decimal_places: bool = more_than_two_decimal_places(execution_time)
+            || more_than_two_decimal_places(period)
+            || more_than_two_decimal_places(deadline);
+        if !positive || more_than_two_decimal_places || deadline > period {
+            panic!("Task parameters must be positive real numbers with less than 3 decimal places and deadline <= period.");
+        }
+        Task { id, execution_time, period, deadline }
+    }
+
+    /// Utilization = execution time / period.
+    pub fn utilization(&self) -> f64 {
+        self.execution_time / self.period
+    }
+
+    /// Density = execution time / min(deadline, period).
+    pub fn density(&self) -> f64 {
+        self.execution_time / self.deadline.min(self.period)
+    }
+}
+
+/// Priority assignment used on each core.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RealTimePolicy {
+    /// Earliest Deadline First: the job with the earliest absolute deadline runs.
+    Edf,
+    /// Rate Monotonic: the task with the shortest period runs.
+    RateMonotonic,
+}
+
+/// Heuristic used to assign tasks to cores. Tasks are sorted by decreasing utilization first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinPacking {
+    /// The first core that passes the admission test.
+    FirstFitDecreasing,
+    /// The admissible core with the least spare capacity.
+    BestFitDecreasing,
+    /// The admissible core with the most spare capacity.
+    WorstFitDecreasing,
+}
+
+/// Why a task could not be placed on one core.
+#[derive(Debug, Clone)]
+pub struct Rejection {
+    pub core: usize,
+    pub reason: String,
+}
+
+/// A task that did not fit on any core, with the reason for every core.
+#[derive(Debug, Clone)]
+pub struct FailedTask {
+    pub id: u32,
+    pub utilization: f64,
+    pub rejections: Vec<Rejection>,
+}
+
+/// Result of a task-to-core assignment.
+#[derive(Debug, Clone)]
+pub struct Partition {
+    pub policy: RealTimePolicy,
+    pub bin_packing: BinPacking,
+    pub cores: Vec<Vec<u32>>,
+    pub utilization: Vec<f64>,
+    pub failed_tasks: Vec<FailedTask>,
+}
+
+impl Partition {
+    /// The partition is feasible if every task was assigned to a core.
+    pub fn is_feasible(&self) -> bool {
+        self.failed_tasks.is_empty()
+    }
+
+    pub fn print(&self) {
+        println!("Partition ({:?}, {:?}): {}", self.policy, self.bin_packing,
+            if self.is_feasible() { "feasible" } else { "infeasible" });
+        for (core, tasks) in self.cores.iter().enumerate() {
+            let names: Vec<String> = tasks.iter().map(|id| format!("T{}", id)).collect();
+            println!("Core {}:\tU = {:.3}\t{}", core, self.utilization[core], names.join(", "));
+        }
+        for failed_task in self.failed_tasks.iter() {
+            println!("T{} (U = {:.3}) did not fit:", failed_task.id, failed_task.utilization);
+            for rejection in failed_task.rejections.iter() {
+                println!("\tCore {}: {}", rejection.core, rejection.reason);
+            }
+        }
+    }
+}
+
+/// `DeadlineMiss` struct.
+#[derive(Debug, Clone)]
+pub struct DeadlineMiss {
+    pub task_id: u32,
+    pub job: u32,
+    pub deadline: f64,
+    pub remaining_time: f64,
+}
+
+/// Outcome of the Dhall's effect check.
+#[derive(Debug, Clone)]
+pub struct DhallReport {
+    pub total_utilization: f64,
+    pub max_utilization: f64,
+    /// Goossens-Funk-Baruah bound m(1 - u_max) + u_max: G-EDF is guaranteed to succeed below it.
+    pub gfb_bound: f64,
+    pub global_edf_misses: Vec<DeadlineMiss>,
+    pub partitioned_edf_feasible: bool,
+    /// A heavy task missed its deadline under G-EDF although partitioned EDF schedules the same task set.
+    pub detected: bool,
+}
+
+/// A job released by a task during the simulation.
+struct Job {
+    task: usize,
+    number: u32,
+    deadline: u64,
+    remaining: u64,
+}
+
+pub struct RealTimeScheduler {
+    pub tasks: Vec<Task>,
+    pub number_of_cores: usize,
+    pub event_list: Vec<Event>,
+    pub deadline_misses: Vec<DeadlineMiss>,
+    pub quiet: bool,
+}
+
+// Common methods
+impl RealTimeScheduler {
+    pub fn new(tasks: Vec<Task>, number_of_cores: usize) -> RealTimeScheduler {
+        if number_of_cores == 0 {
+            panic!("Number of cores must be at least 1.");
+        }
+        RealTimeScheduler { tasks, number_of_cores, event_list: Vec::new(), deadline_misses: Vec::new(), quiet: false }
+    }
+
+    pub fn total_utilization(&self) -> f64 {
+        self.tasks.iter().map(|task| task.utilization()).sum()
+    }
+
+    /// Least common multiple of the task periods.
+    pub fn hyperperiod(&self) -> f64 {
+        fn gcd(a: u64, b: u64) -> u64 {
+            if b == 0 { a } else { gcd(b, a % b) }
+        }
+        let ticks: u64 = self
+            .tasks
+            .iter()
+            .map(|task| to_ticks(task.period))
+            .fold(1, |lcm, period| lcm / gcd(lcm, period) * period);
+        from_ticks(ticks)
+    }
+
+    /// Admission test of `task` on a core that already holds `assigned`.
+    /// EDF uses the density bound, RM uses response time analysis.
+    fn admit(&self, policy: RealTimePolicy, assigned: &[usize], task: usize) -> Result<(), String> {
+        match policy {
+            RealTimePolicy::Edf => {
+                let density: f64 = assigned.iter().map(|&index| self.tasks[index].density()).sum::<f64>();
+                let new_density: f64 = self.tasks[task].density();
+                if density + new_density <= 1.0 + 1e-9 {
+                    Ok(())
+                } else {
+                    Err(format!("density {:.3} + {:.3} > 1.000", density, new_density))
+                }
+            }
+            RealTimePolicy::RateMonotonic => {
+                // Sort the tasks of the core by period (shortest period = highest priority).
+                let mut core_tasks: Vec<usize> = assigned.to_vec();
+                core_tasks.push(task);
+                core_tasks.sort_by_key(|&index| (to_ticks(self.tasks[index].period), self.tasks[index].id));
+
+                // R = C_i + sum over higher priority tasks of ceil(R / T_j) * C_j.
+                for (position, &index) in core_tasks.iter().enumerate() {
+                    let execution_time: u64 = to_ticks(self.tasks[index].execution_time);
+                    let deadline: u64 = to_ticks(self.tasks[index].deadline);
+                    let mut response_time: u64 = execution_time;
+                    loop {
+                        let interference: u64 = core_tasks[..position]
+                            .iter()
+                            .map(|&higher| {
+                                let period: u64 = to_ticks(self.tasks[higher].period);
+                                response_time.div_ceil(period) * to_ticks(self.tasks[higher].execution_time)
+                            })
+                            .sum();
+                        let next_response_time: u64 = execution_time + interference;
+                        if next_response_time > deadline {
+                            return Err(format!(
+                                "response time of T{} exceeds its deadline {:.2}",
+                                self.tasks[index].id, self.tasks[index].deadline
+                            ));
+                        }
+                        if next_response_time == response_time {
+                            break;
+                        }
+                        response_time = next_response_time;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Assign the tasks to cores with a bin-packing heuristic.
+    pub fn partition(&self, policy: RealTimePolicy, bin_packing: BinPacking) -> Partition {
+        // Sort the tasks by decreasing utilization.
+        let mut order: Vec<usize> = (0..self.tasks.len()).collect();
+        order.sort_by(|&a, &b| {
+            self.tasks[b].utilization()
+                .partial_cmp(&self.tasks[a].utilization())
+                .unwrap()
+                .then(self.tasks[a].id.cmp(&self.tasks[b].id))
+        });
+
+        let mut assigned: Vec<Vec<usize>> = vec![Vec::new(); self.number_of_cores];
+        let mut utilization: Vec<f64> = vec![0.0; self.number_of_cores];
+        let mut failed_tasks: Vec<FailedTask> = Vec::new();
+
+        for task in order {
+            // Run the admission test on every core.
+            let mut admissible: Vec<usize> = Vec::new();
+            let mut rejections: Vec<Rejection> = Vec::new();
+            for (core, core_tasks) in assigned.iter().enumerate() {
+                match self.admit(policy, core_tasks, task) {
+                    Ok(()) => admissible.push(core),
+                    Err(reason) => rejections.push(Rejection { core, reason }),
+                }
+            }
+
+            // Pick a core according to the heuristic.
+            let chosen: Option<usize> = match bin_packing {
+                BinPacking::FirstFitDecreasing => admissible.first().copied(),
+                BinPacking::BestFitDecreasing => admissible
+                    .iter()
+                    .copied()
+                    .max_by(|&a, &b| utilization[a].partial_cmp(&utilization[b]).unwrap().then(b.cmp(&a))),
+                BinPacking::WorstFitDecreasing => admissible
+                    .iter()
+                    .copied()
+                    .min_by(|&a, &b| utilization[a].partial_cmp(&utilization[b]).unwrap().then(a.cmp(&b))),
+            };
+
+            match chosen {
+                Some(core) => {
+                    assigned[core].push(task);
+                    utilization[core] += self.tasks[task].utilization();
+                }
+                None => failed_tasks.push(FailedTask {
+                    id: self.tasks[task].id,
+                    utilization: self.tasks[task].utilization(),
+                    rejections,
+                }),
+            }
+        }
+
+        Partition {
+            policy,
+            bin_packing,
+            cores: assigned
+                .iter()
+                .map(|tasks| tasks.iter().map(|&index| self.tasks[index].id).collect())
+                .collect(),
+            utilization,
+            failed_tasks,
+        }
+    }
+
+    /// Simulate `tasks` on `cores` until `horizon` (in ticks) with a global scheduler.
+    /// A partitioned scheduler is simulated by calling this once per core.
+    fn simulate(&mut self, tasks: &[usize], cores: &[usize], policy: RealTimePolicy, horizon: u64) {
+        let mut jobs: Vec<Job> = Vec::new();
+        let mut next_release: Vec<u64> = vec![0; tasks.len()];
+        let mut job_count: Vec<u32> = vec![0; tasks.len()];
+        let mut running: Vec<Option<(usize, u32)>> = vec![None; cores.len()];
+        let mut current_time: u64 = 0;
+
+        while current_time < horizon {
+            // Release the new jobs.
+            for (position, &task) in tasks.iter().enumerate() {
+                while next_release[position] <= current_time {
+                    jobs.push(Job {
+                        task,
+                        number: job_count[position],
+                        deadline: next_release[position] + to_ticks(self.tasks[task].deadline),
+                        remaining: to_ticks(self.tasks[task].execution_time),
+                    });
+                    job_count[position] += 1;
+                    next_release[position] += to_ticks(self.tasks[task].period);
+                }
+            }
+
+            // Drop the jobs that missed their deadline.
+            for job in jobs.iter().filter(|job| job.deadline <= current_time) {
+                self.deadline_misses.push(DeadlineMiss {
+                    task_id: self.tasks[job.task].id,
+                    job: job.number,
+                    deadline: from_ticks(job.deadline),
+                    remaining_time: from_ticks(job.remaining),
+                });
+            }
+            jobs.retain(|job| job.deadline > current_time);
+
+            // Sort the active jobs by priority and select one job per core.
+            jobs.sort_by_key(|job| match policy {
+                RealTimePolicy::Edf => (job.deadline, self.tasks[job.task].id),
+                RealTimePolicy::RateMonotonic => (to_ticks(self.tasks[job.task].period), self.tasks[job.task].id),
+            });
+            let selected: usize = jobs.len().min(cores.len());
+
+            // Keep the selected jobs on the core they already run on, then fill the free cores.
+            let mut assignment: Vec<Option<usize>> = vec![None; cores.len()];
+            for (slot, running_job) in running.iter().enumerate() {
+                if let Some(running_job) = running_job {
+                    assignment[slot] = jobs[..selected]
+                        .iter()
+                        .position(|job| (job.task, job.number) == *running_job);
+                }
+            }
+            for position in 0..selected {
+                if !assignment.contains(&Some(position)) {
+                    let slot: usize = assignment.iter().position(|slot| slot.is_none()).unwrap();
+                    assignment[slot] = Some(position);
+                }
+            }
+
+            // Run until the next release, completion, deadline or the horizon.
+            let mut next_time: u64 = horizon;
+            next_time = next_time.min(*next_release.iter().min().unwrap_or(&horizon));
+            for job in jobs.iter() {
+                next_time = next_time.min(job.deadline);
+            }
+            for position in assignment.iter().flatten() {
+                next_time = next_time.min(current_time + jobs[*position].remaining);
+            }
+
+            for (slot, position) in assignment.iter().enumerate() {
+                running[slot] = None;
+                if let Some(position) = *position {
+                    let job: &mut Job = &mut jobs[position];
+                    job.remaining -= next_time - current_time;
+                    running[slot] = Some((job.task, job.number));
+                    let name: String = format!("T{}", self.tasks[job.task].id);
+                    self.push_event(name, cores[slot], current_time, next_time);
+                }
+            }
+            jobs.retain(|job| job.remaining > 0);
+            current_time = next_time;
+        }
+
+        // The jobs due at the horizon that did not finish also missed their deadline.
+        for job in jobs.iter().filter(|job| job.deadline <= horizon) {
+            self.deadline_misses.push(DeadlineMiss {
+                task_id: self.tasks[job.task].id,
+                job: job.number,
+                deadline: from_ticks(job.deadline),
+                remaining_time: from_ticks(job.remaining),
+            });
+        }
+    }
+
+    /// Add a slice to the timeline, merging it with the previous slice of the same task on the same core.
+    fn push_event(&mut self, name: String, core: usize, start_time: u64, finish_time: u64) {
+        let previous: Option<&mut Event> = self.event_list.iter_mut().rev().find(|event| event.core == core);
+        if let Some(event) = previous {
+            if event.name == name && to_ticks(event.finish_time) == start_time {
+                event.finish_time = from_ticks(finish_time);
+                return;
+            }
+        }
+        self.event_list.push(Event {
+            name,
+            kind: EventKind::Process,
+            core,
+            start_time: from_ticks(start_time),
+            finish_time: from_ticks(finish_time),
+        });
+    }
+}
+
+// Visualization
+impl RealTimeScheduler {
+    pub fn print(&self) {
+        println!("Task\t\tExecution Time\tPeriod\t\tDeadline\tUtilization");
+        for task in self.tasks.iter() {
+            println!(
+                "T{}\t\t{:.2}\t\t{:.2}\t\t{:.2}\t\t{:.3}",
+                task.id, task.execution_time, task.period, task.deadline, task.utilization()
+            );
+        }
+        println!("Total utilization: {:.3} on {} cores", self.total_utilization(), self.number_of_cores);
+
+        if self.deadline_misses.is_empty() {
+            println!("No deadline missed.");
+        }
+        for miss in self.deadline_misses.iter() {
+            println!(
+                "Deadline miss: T{} job {} (deadline {:.2}, {:.2} left)",
+                miss.task_id, miss.job, miss.deadline, miss.remaining_time
+            );
+        }
+
+        self.gantt_chart();
+    }
+
+    /// Horizontal chart of the timeline, one row per core.
+    pub fn gantt_chart(&self) {
+        println!("\n\nGantt Chart:\n{}", GanttChart::new().render(&self.event_list));
+    }
+}
+
+// Algorithms
+impl RealTimeScheduler {
+    // Global Earliest Deadline First (G-EDF): one ready queue shared by all cores.
+    pub fn global_edf(&mut self, horizon: f64) {
+        self.event_list.clear();
+        self.deadline_misses.clear();
+
+        let tasks: Vec<usize> = (0..self.tasks.len()).collect();
+        let cores: Vec<usize> = (0..self.number_of_cores).collect();
+        self.simulate(&tasks, &cores, RealTimePolicy::Edf, to_ticks(horizon));
+
+        // Print the result.
+        if !self.quiet {
+            self.print();
+        }
+    }
+
+    // Partitioned EDF / RM: the tasks are assigned to cores, then every core is scheduled on its own.
+    // The tasks that do not fit are not simulated; they are listed in the returned partition.
+    pub fn partitioned(&mut self, policy: RealTimePolicy, bin_packing: BinPacking, horizon: f64) -> Partition {
+        self.event_list.clear();
+        self.deadline_misses.clear();
+
+        let partition: Partition = self.partition(policy, bin_packing);
+        for (core, ids) in partition.cores.iter().enumerate() {
+            let tasks: Vec<usize> = ids
+                .iter()
+                .map(|id| self.tasks.iter().position(|task| task.id == *id).unwrap())
+                .collect();
+            self.simulate(&tasks, &[core], policy, to_ticks(horizon));
+        }
+        self.event_list.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap().then(a.core.cmp(&b.core)));
+
+        // Print the result.
+        if !self.quiet {
+            partition.print();
+            self.print();
+        }
+        partition
+    }
+
+    /// Check for Dhall's effect: G-EDF misses a deadline of a heavy task (utilization > 0.5)
+    /// although the task set is easy enough to be partitioned under EDF.
+    pub fn dhall_effect(&mut self, horizon: f64) -> DhallReport {
+        self.event_list.clear();
+        self.deadline_misses.clear();
+
+        let tasks: Vec<usize> = (0..self.tasks.len()).collect();
+        let cores: Vec<usize> = (0..self.number_of_cores).collect();
+        self.simulate(&tasks, &cores, RealTimePolicy::Edf, to_ticks(horizon));
+
+        let max_utilization: f64 = self.tasks.iter().map(|task| task.utilization()).fold(0.0, f64::max);
+        let partitioned_edf_feasible: bool = self
+            .partition(RealTimePolicy::Edf, BinPacking::FirstFitDecreasing)
+            .is_feasible();
+        let heavy_task_missed: bool = self.deadline_misses.iter().any(|miss| {
+            self.tasks
+                .iter()
+                .any(|task| task.id == miss.task_id && task.utilization() > 0.5)
+        });
+
+        DhallReport {
+            total_utilization: self.total_utilization(),
+            max_utilization,
+            gfb_bound: self.number_of_cores as f64 * (1.0 - max_utilization) + max_utilization,
+            global_edf_misses: self.deadline_misses.clone(),
+            partitioned_edf_feasible,
+            detected: heavy_task_missed && partitioned_edf_feasible,
+        }
+    }
+}
+
+// Test Dhall's effect and the bin-packing report.
+#[test]
+fn test() {
+    // Two light tasks and one heavy task on two cores.
+    let mut scheduler: RealTimeScheduler = RealTimeScheduler::new(
+        vec![
+            Task::new(1, 0.2, 1.0),
+            Task::new(2, 0.2, 1.0),
+            Task::new(3, 1.0, 1.1),
+        ],
+        2,
+    );
+    scheduler.quiet = true;
+    let report: DhallReport = scheduler.dhall_effect(11.0);
+    assert!(report.detected);
+    assert!(report.total_utilization > report.gfb_bound);
+
+    let partition: Partition = scheduler.partitioned(RealTimePolicy::Edf, BinPacking::FirstFitDecreasing, 11.0);
+    assert!(partition.is_feasible());
+    assert!(scheduler.deadline_misses.is_empty());
+
+    // Three heavy tasks cannot be packed on two cores.
+    let scheduler: RealTimeScheduler = RealTimeScheduler::new(
+        vec![
+            Task::new(1, 6.0, 10.0),
+            Task::new(2, 6.0, 10.0),
+            Task::new(3, 6.0, 10.0),
+        ],
+        2,
+    );
+    let partition: Partition = scheduler.partition(RealTimePolicy::RateMonotonic, BinPacking::WorstFitDecreasing);
+    assert_eq!(partition.failed_tasks.len(), 1);
+    assert_eq!(partition.failed_tasks[0].rejections.len(), 2);
+}
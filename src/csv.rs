@@ -0,0 +1,235 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::cpu_schedulers::{Process, Event, EventKind, more_than_two_decimal_places};
+
+/// A problem in one row of a CSV file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvError {
+    /// 1-based line number (0 when the file itself cannot be read).
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+/// Split a CSV line into fields. Fields may be quoted, with `""` for a quote inside a quoted field.
+fn split_line(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields: Vec<String> = Vec::new();
+    let mut field: String = String::new();
+    let mut quoted: bool = false;
+    let mut characters = line.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '"' if quoted && characters.peek() == Some(&'"') => {
+                field.push('"');
+                characters.next();
+            }
+            '"' => quoted = !quoted,
+            _ if character == delimiter && !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            _ => field.push(character),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+/// Quote a field if needed.
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Columns of a process table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Column {
+    Pid,
+    Arrival,
+    Burst,
+    Priority,
+}
+
+/// The column a header names, if it is one we know.
+fn column(header: &str) -> Option<Column> {
+    let header: String = header.to_lowercase().replace([' ', '-'], "_");
+    match header.as_str() {
+        "pid" | "id" | "process" | "name" => Some(Column::Pid),
+        "arrival" | "arrival_time" | "at" => Some(Column::Arrival),
+        "burst" | "burst_time" | "cpu_burst" | "bt" => Some(Column::Burst),
+        "priority" | "prio" => Some(Column::Priority),
+        _ => None,
+    }
+}
+
+/// Parse a time of a process table (or of a golden file).
+pub(crate) fn parse_time(field: &str, name: &str) -> Result<f64, String> {
+    let time: f64 = field.parse().map_err(|_| format!("{} `{}` is not a number", name, field))?;
+    if time < 0.0 || more_than_two_decimal_places(time) || !time.is_finite() {
+        return Err(format!("{} `{}` must be non negative with at most two decimal places", name, field));
+    }
+    Ok(time)
+}
+
+/// Read a process table.
+///
+/// The first row is a header if one of its fields is not a number; the columns are then found by name
+/// (`pid`, `arrival`, `burst`, `priority` and a few aliases such as `arrival time` or `burst_time`)
+/// and the other columns are ignored. Without a header the columns are pid, arrival, burst and an optional priority.
+/// A pid may be written `P3`. Fields are separated by commas, or by semicolons if the first line has no comma.
+/// Every bad row is reported.
+pub fn read_processes(text: &str) -> Result<Vec<Process>, Vec<CsvError>> {
+    let text: &str = text.trim_start_matches('\u{feff}');
+    let lines: Vec<(usize, &str)> = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .filter(|(_, line)| !line.trim().is_empty())
+        .collect();
+    let delimiter: char = match lines.first() {
+        Some((_, line)) if !line.contains(',') && line.contains(';') => ';',
+        _ => ',',
+    };
+
+    // Find the columns.
+    let mut columns: Vec<Option<Column>> = vec![Some(Column::Pid), Some(Column::Arrival), Some(Column::Burst), Some(Column::Priority)];
+    let mut rows: &[(usize, &str)] = &lines;
+    if let Some((line, header)) = lines.first() {
+        let fields: Vec<String> = split_line(header, delimiter);
+        let is_header: bool = fields.iter().any(|field| field.trim_start_matches(['P', 'p']).parse::<f64>().is_err());
+        if is_header {
+            columns = fields.iter().map(|field| column(field)).collect();
+            for (required, name) in [(Column::Pid, "pid"), (Column::Arrival, "arrival"), (Column::Burst, "burst")] {
+                if !columns.contains(&Some(required)) {
+                    return Err(vec![CsvError { line: *line, message: format!("missing `{}` column", name) }]);
+                }
+            }
+            rows = &lines[1..];
+        }
+    }
+
+    let mut processes: Vec<Process> = Vec::new();
+    let mut errors: Vec<CsvError> = Vec::new();
+    for &(line, row) in rows {
+        let fields: Vec<String> = split_line(row, delimiter);
+        let field = |wanted: Column| -> Option<&str> {
+            columns
+                .iter()
+                .position(|column| *column == Some(wanted))
+                .and_then(|position| fields.get(position))
+                .map(|field| field.as_str())
+                .filter(|field| !field.is_empty())
+        };
+        let parsed: Result<Process, String> = (|| {
+            let pid_field: &str = field(Column::Pid).ok_or("missing pid")?;
+            let pid: u32 = pid_field
+                .trim_start_matches(['P', 'p'])
+                .parse()
+                .map_err(|_| format!("pid `{}` is not a non negative integer", pid_field))?;
+            let arrival_time: f64 = parse_time(field(Column::Arrival).ok_or("missing arrival time")?, "arrival time")?;
+            let burst_time: f64 = parse_time(field(Column::Burst).ok_or("missing burst time")?, "burst time")?;
+            let priority: u32 = match field(Column::Priority) {
+                Some(priority) => priority.parse().map_err(|_| format!("priority `{}` is not a non negative integer", priority))?,
+                None => 0,
+            };
+            if processes.iter().any(|process| process.pid == pid) {
+                return Err(format!("duplicate pid {}", pid));
+            }
+            Ok(Process::new_with_priority(pid, arrival_time, burst_time, priority))
+        })();
+        match parsed {
+            Ok(process) => processes.push(process),
+            Err(message) => errors.push(CsvError { line, message }),
+        }
+    }
+
+    if errors.is_empty() { Ok(processes) } else { Err(errors) }
+}
+
+/// Read a process table from a file.
+pub fn load_processes(path: &Path) -> Result<Vec<Process>, Vec<CsvError>> {
+    let text: String = fs::read_to_string(path)
+        .map_err(|error| vec![CsvError { line: 0, message: format!("{}: {}", path.display(), error) }])?;
+    read_processes(&text)
+}
+
+/// Write a process table that `read_processes` reads back.
+pub fn write_processes(processes: &[Process]) -> String {
+    let mut csv: String = String::from("pid,arrival_time,burst_time,priority\n");
+    for process in processes.iter() {
+        csv += &format!("{},{:.2},{:.2},{}\n", process.pid, process.arrival_time, process.burst_time, process.priority);
+    }
+    csv
+}
+
+/// Write the results of the finished processes.
+pub fn write_results(finished_processes: &[Process]) -> String {
+    let mut csv: String = String::from("pid,arrival_time,burst_time,priority,start_time,finish_time,waiting_time,turn_around_time\n");
+    for process in finished_processes.iter() {
+        csv += &format!(
+            "{},{:.2},{:.2},{},{:.2},{:.2},{:.2},{:.2}\n",
+            process.pid,
+            process.arrival_time,
+            process.burst_time,
+            process.priority,
+            process.start_time,
+            process.finish_time,
+            process.waiting_time,
+            process.turn_around_time
+        );
+    }
+    csv
+}
+
+/// Write a timeline, one slice per row.
+pub fn write_timeline(event_list: &[Event]) -> String {
+    let mut csv: String = String::from("name,kind,core,start_time,finish_time\n");
+    for event in event_list.iter() {
+        let kind: &str = match event.kind {
+            EventKind::Process => "process",
+            EventKind::ContextSwitch => "context_switch",
+        };
+        csv += &format!("{},{},{},{:.2},{:.2}\n", escape(&event.name), kind, event.core, event.start_time, event.finish_time);
+    }
+    csv
+}
+
+// Test a spreadsheet export with a header, bad rows and a round trip.
+#[test]
+fn test() {
+    let text: &str = "\u{feff}Process;Arrival Time;Burst Time;Priority;Comment\nP1;0;8;2;\"first; long\"\nP2;1;4;1;\n";
+    let processes: Vec<Process> = read_processes(text).unwrap();
+    assert_eq!(processes.len(), 2);
+    assert_eq!((processes[0].pid, processes[0].burst_time, processes[0].priority), (1, 8.0, 2));
+    assert_eq!(processes[1].arrival_time, 1.0);
+
+    // Without a header, and with every bad row reported.
+    let errors: Vec<CsvError> = read_processes("1,0,3\n2,x,3\n\n3,1,0.001\n1,2,2\n").unwrap_err();
+    let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+    assert_eq!(messages, vec![
+        "line 2: arrival time `x` is not a number",
+        "line 4: burst time `0.001` must be non negative with at most two decimal places",
+        "line 5: duplicate pid 1",
+    ]);
+    assert_eq!(read_processes("name,burst\nP1,3\n").unwrap_err()[0].to_string(), "line 1: missing `arrival` column");
+
+    // Round trip.
+    let csv: String = write_processes(&processes);
+    let again: Vec<Process> = read_processes(&csv).unwrap();
+    assert_eq!(write_processes(&again), csv);
+
+    let result = crate::schedule::Algorithm::Fcfs.run(&processes, crate::cpu_schedulers::ContextSwitch::None);
+    assert_eq!(write_results(&result.finished_processes).lines().nth(2), Some("2,1.00,4.00,1,8.00,12.00,7.00,11.00"));
+    assert_eq!(write_timeline(&result.event_list).lines().nth(1), Some("P1,process,0,0.00,8.00"));
+}
@@ -0,0 +1,262 @@
+use std::thread;
+
+use crate::cpu_schedulers::ContextSwitch;
+use crate::metrics::Metric;
+use crate::schedule::Algorithm;
+use crate::workload::WorkloadGenerator;
+
+/// Two-sided 95% critical values of Student's t distribution for 1 to 30 degrees of freedom.
+const T_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+    2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+    2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+];
+
+/// Two-sided 95% critical values of Student's t distribution for 30, 40, 60, 120 and infinitely many degrees of freedom.
+const T_95_ROWS: [(f64, f64); 5] = [(30.0, 2.042), (40.0, 2.021), (60.0, 2.000), (120.0, 1.980), (f64::INFINITY, 1.960)];
+
+/// Two-sided 95% critical value of Student's t distribution.
+/// Past 30 degrees of freedom, it is interpolated linearly in 1 / degrees of freedom between the rows of `T_95_ROWS`.
+pub fn t_critical_value(degrees_of_freedom: usize) -> f64 {
+    match degrees_of_freedom {
+        0 => f64::INFINITY,
+        1..=30 => T_95[degrees_of_freedom - 1],
+        _ => {
+            let degrees_of_freedom: f64 = degrees_of_freedom as f64;
+            let row: usize = T_95_ROWS.iter().position(|&(row_degrees, _)| row_degrees >= degrees_of_freedom).unwrap();
+            let ((lower_degrees, lower_value), (upper_degrees, upper_value)) = (T_95_ROWS[row - 1], T_95_ROWS[row]);
+            let fraction: f64 = (1.0 / lower_degrees - 1.0 / degrees_of_freedom) / (1.0 / lower_degrees - 1.0 / upper_degrees);
+            lower_value + (upper_value - lower_value) * fraction
+        }
+    }
+}
+
+/// Mean, sample standard deviation and 95% confidence interval of the mean.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub number_of_samples: usize,
+    pub mean: f64,
+    pub standard_deviation: f64,
+    /// Half width of the 95% confidence interval.
+    pub margin_of_error: f64,
+}
+
+impl Summary {
+    pub fn new(samples: &[f64]) -> Summary {
+        let number_of_samples: usize = samples.len();
+        if number_of_samples == 0 {
+            panic!("No samples to summarize!");
+        }
+        let mean: f64 = samples.iter().sum::<f64>() / number_of_samples as f64;
+        let variance: f64 = if number_of_samples > 1 {
+            samples.iter().map(|sample| (sample - mean).powi(2)).sum::<f64>() / (number_of_samples - 1) as f64
+        } else {
+            0.0
+        };
+        let standard_deviation: f64 = variance.sqrt();
+        Summary {
+            number_of_samples,
+            mean,
+            standard_deviation,
+            margin_of_error: t_critical_value(number_of_samples - 1) * standard_deviation / (number_of_samples as f64).sqrt(),
+        }
+    }
+
+    /// (lower bound, upper bound) of the 95% confidence interval.
+    pub fn confidence_interval(&self) -> (f64, f64) {
+        (self.mean - self.margin_of_error, self.mean + self.margin_of_error)
+    }
+}
+
+/// Many replications of one or more algorithms, each on a workload generated with a different seed.
+#[derive(Debug, Clone)]
+pub struct Experiment {
+    pub generator: WorkloadGenerator,
+    pub algorithms: Vec<Algorithm>,
+    pub context_switch: ContextSwitch,
+    pub replications: usize,
+    pub number_of_threads: usize,
+}
+
+impl Experiment {
+    /// Replication `i` uses the seed `generator.seed + i`.
+    pub fn new(generator: WorkloadGenerator, algorithms: Vec<Algorithm>, replications: usize) -> Experiment {
+        if algorithms.is_empty() || replications == 0 {
+            panic!("An experiment needs at least one algorithm and one replication.");
+        }
+        let experiment: Experiment = Experiment { generator, algorithms, context_switch: ContextSwitch::None, replications, number_of_threads: 1 };
+        experiment.check();
+        experiment
+    }
+
+    pub fn set_context_switch(&mut self, context_switch: ContextSwitch) {
+        self.context_switch = context_switch;
+        self.check();
+    }
+
+    /// Panic on the parameters the schedulers would panic on, before any worker thread is spawned.
+    fn check(&self) {
+        let checked: Result<(), String> = self.context_switch.validate().and_then(|()| {
+            self.algorithms
+                .iter()
+                .try_for_each(|algorithm| algorithm.check().and_then(|()| algorithm.check_context_switch(self.context_switch)))
+        });
+        if let Err(error) = checked {
+            panic!("Invalid experiment: {}", error);
+        }
+    }
+
+    pub fn set_number_of_threads(&mut self, number_of_threads: usize) {
+        if number_of_threads == 0 {
+            panic!("Number of threads must be positive.");
+        }
+        self.number_of_threads = number_of_threads;
+    }
+
+    /// Metric values (in `Metric::ALL` order) of every algorithm for one replication.
+    fn replicate(&self, replication: usize) -> Vec<Vec<f64>> {
+        let processes = self.generator.generate_with_seed(self.generator.seed.wrapping_add(replication as u64));
+        self.algorithms
+            .iter()
+            .map(|algorithm| {
+                let result = algorithm.run(&processes, self.context_switch);
+                Metric::ALL.iter().map(|metric| metric.value(&result.metrics)).collect()
+            })
+            .collect()
+    }
+
+    /// Run the replications, split in contiguous chunks over the threads.
+    pub fn run(&self) -> ExperimentReport {
+        self.check();
+        let chunk_size: usize = self.replications.div_ceil(self.number_of_threads);
+        let replications: Vec<Vec<Vec<f64>>> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..self.replications)
+                .step_by(chunk_size)
+                .map(|start| {
+                    let end: usize = (start + chunk_size).min(self.replications);
+                    scope.spawn(move || (start..end).map(|replication| self.replicate(replication)).collect::<Vec<_>>())
+                })
+                .collect();
+            handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+        });
+
+        // Regroup by algorithm: values[algorithm][replication][metric].
+        let values: Vec<Vec<Vec<f64>>> = (0..self.algorithms.len())
+            .map(|algorithm| replications.iter().map(|replication| replication[algorithm].clone()).collect())
+            .collect();
+        ExperimentReport {
+            algorithms: self.algorithms.iter().map(|algorithm| algorithm.name()).collect(),
+            values,
+        }
+    }
+}
+
+/// Significance of the difference between two algorithms on one metric.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PairedDifference {
+    /// Summary of (other - best) over the replications; every replication uses the same workload for both.
+    pub difference: Summary,
+    /// Whether the 95% confidence interval of the difference excludes 0.
+    pub significant: bool,
+}
+
+/// The metric values of every replication.
+#[derive(Debug, Clone)]
+pub struct ExperimentReport {
+    pub algorithms: Vec<String>,
+    /// values[algorithm][replication][metric], with the metrics in `Metric::ALL` order.
+    pub values: Vec<Vec<Vec<f64>>>,
+}
+
+impl ExperimentReport {
+    fn samples(&self, algorithm: usize, metric: Metric) -> Vec<f64> {
+        let index: usize = Metric::ALL.iter().position(|other| *other == metric).unwrap();
+        self.values[algorithm].iter().map(|replication| replication[index]).collect()
+    }
+
+    pub fn summary(&self, algorithm: usize, metric: Metric) -> Summary {
+        Summary::new(&self.samples(algorithm, metric))
+    }
+
+    /// Index of the algorithm with the best mean on the metric.
+    pub fn best(&self, metric: Metric) -> usize {
+        (0..self.algorithms.len())
+            .min_by(|&a, &b| {
+                let ordering = self.summary(a, metric).mean.partial_cmp(&self.summary(b, metric).mean).unwrap();
+                if metric.higher_is_better() { ordering.reverse() } else { ordering }
+            })
+            .unwrap()
+    }
+
+    /// Paired comparison of an algorithm against the best one on the metric.
+    pub fn against_best(&self, algorithm: usize, metric: Metric) -> PairedDifference {
+        let best: Vec<f64> = self.samples(self.best(metric), metric);
+        let differences: Vec<f64> = self
+            .samples(algorithm, metric)
+            .iter()
+            .zip(best.iter())
+            .map(|(other, best)| other - best)
+            .collect();
+        let difference: Summary = Summary::new(&differences);
+        let (lower, upper) = difference.confidence_interval();
+        PairedDifference { difference, significant: lower > 0.0 || upper < 0.0 }
+    }
+
+    pub fn print(&self, metrics: &[Metric]) {
+        for metric in metrics.iter() {
+            let best: usize = self.best(*metric);
+            println!("{} ({} replications)", metric.name(), self.values[0].len());
+            println!("Algorithm\t\tMean\t\tStd Dev\t\t95% CI\t\t\tvs best");
+            for (algorithm, name) in self.algorithms.iter().enumerate() {
+                let summary: Summary = self.summary(algorithm, *metric);
+                let (lower, upper) = summary.confidence_interval();
+                let hint: String = if algorithm == best {
+                    String::from("best")
+                } else {
+                    let paired: PairedDifference = self.against_best(algorithm, *metric);
+                    format!(
+                        "{:+.2} ({})",
+                        paired.difference.mean,
+                        if paired.significant { "significant" } else { "not significant" }
+                    )
+                };
+                println!(
+                    "{:<16}\t{:.2}\t\t{:.2}\t\t[{:.2}, {:.2}]\t\t{}",
+                    name, summary.mean, summary.standard_deviation, lower, upper, hint
+                );
+            }
+            println!();
+        }
+    }
+}
+
+// Test that SJF beats FCFS on many random workloads, and that threads do not change the result.
+#[test]
+fn test() {
+    let summary: Summary = Summary::new(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+    assert_eq!(summary.mean, 3.0);
+    assert!((summary.standard_deviation - 2.5f64.sqrt()).abs() < 1e-9);
+    assert!((summary.margin_of_error - 2.776 * 2.5f64.sqrt() / 5f64.sqrt()).abs() < 1e-9);
+    assert_eq!(t_critical_value(40), 2.021);
+    assert!((t_critical_value(35) - 2.030).abs() < 1e-3);
+    assert!((t_critical_value(90) - 1.987).abs() < 1e-3);
+    assert!(t_critical_value(1000) > 1.960 && t_critical_value(1000) < 1.980);
+
+    let generator: WorkloadGenerator = WorkloadGenerator::new(7, 30);
+    let mut experiment: Experiment = Experiment::new(generator, vec![Algorithm::Fcfs, Algorithm::Sjf], 20);
+    let report: ExperimentReport = experiment.run();
+    assert_eq!(report.best(Metric::AverageWaitingTime), 1);
+    assert!(report.against_best(0, Metric::AverageWaitingTime).significant);
+    // The same workloads keep the CPU equally busy.
+    assert!(!report.against_best(0, Metric::CpuUtilization).significant);
+
+    experiment.set_number_of_threads(3);
+    assert_eq!(experiment.run().values, report.values);
+
+    // Invalid parameters panic up front with the reason, not inside a worker thread.
+    experiment.algorithms.push(Algorithm::Rr { time_quantum: 0.0 });
+    let error = std::panic::catch_unwind(|| experiment.run()).unwrap_err();
+    assert!(error.downcast_ref::<String>().unwrap().starts_with("Invalid experiment: "));
+    experiment.algorithms.pop();
+    assert!(std::panic::catch_unwind(move || experiment.set_context_switch(ContextSwitch::Fixed(-1.0))).is_err());
+}
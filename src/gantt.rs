@@ -0,0 +1,621 @@
+use std::env;
+
+use crate::cpu_schedulers::{Event, EventKind};
+use crate::cpu_schedulers::realtime::{Task, DeadlineMiss};
+
+/// Tolerance used when comparing times (the schedulers work in hundredths).
+const EPSILON: f64 = 1e-6;
+
+/// Width used when the terminal width is unknown.
+const DEFAULT_WIDTH: usize = 80;
+
+/// Foreground colors given to the processes, in turn.
+const COLORS: [u8; 6] = [31, 32, 33, 34, 35, 36];
+
+/// Characters of a chart.
+struct Style {
+    horizontal: char,
+    vertical: char,
+    top: [char; 3],
+    bottom: [char; 3],
+    process_fill: char,
+    context_switch_fill: char,
+    idle_fill: char,
+}
+
+const UNICODE: Style = Style {
+    horizontal: '─',
+    vertical: '│',
+    top: ['┌', '┬', '┐'],
+    bottom: ['└', '┴', '┘'],
+    process_fill: '█',
+    context_switch_fill: '▒',
+    idle_fill: '░',
+};
+
+const ASCII: Style = Style {
+    horizontal: '-',
+    vertical: '|',
+    top: ['+', '+', '+'],
+    bottom: ['+', '+', '+'],
+    process_fill: '#',
+    context_switch_fill: '~',
+    idle_fill: '.',
+};
+
+/// Width of the terminal, from the `COLUMNS` environment variable.
+pub fn terminal_width() -> usize {
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse().ok())
+        .filter(|&columns: &usize| columns > 0)
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+/// A time without trailing zeros (`8`, `8.5`, `8.25`).
+pub(crate) fn format_time(time: f64) -> String {
+    let time: String = format!("{:.2}", time);
+    time.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Number picking the color of an event: the pid of a process (`P3`, `P3.T1`), the id of a task (`T2`)...
+pub(crate) fn color_key(event: &Event) -> u32 {
+    let number: Option<u32> = row_name(&event.name)
+        .trim_start_matches(|character: char| character.is_alphabetic())
+        .parse()
+        .ok();
+    number.unwrap_or_else(|| event.name.bytes().map(u32::from).sum())
+}
+
+/// Name of the process of an event, without the thread (`P3.T1` gives `P3`).
+fn row_name(name: &str) -> &str {
+    name.split('.').next().unwrap_or(name)
+}
+
+/// A slice of a row: an event, or idle time when there is no event.
+struct Segment<'a> {
+    event: Option<&'a Event>,
+    start_time: f64,
+    finish_time: f64,
+}
+
+/// The events of a row in time order, with idle segments in the gaps up to `finish_time`.
+fn segments<'a>(events: &[&'a Event], finish_time: f64) -> Vec<Segment<'a>> {
+    let mut events: Vec<&Event> = events.to_vec();
+    events.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut time: f64 = 0.0;
+    for event in events {
+        if event.start_time > time + EPSILON {
+            segments.push(Segment { event: None, start_time: time, finish_time: event.start_time });
+        }
+        segments.push(Segment { event: Some(event), start_time: event.start_time, finish_time: event.finish_time });
+        time = time.max(event.finish_time);
+    }
+    if finish_time > time + EPSILON {
+        segments.push(Segment { event: None, start_time: time, finish_time });
+    }
+    segments
+}
+
+/// Horizontal Gantt chart of an event timeline: one row per core, widths proportional to the durations,
+/// idle time shown as its own blocks and the times of every boundary under the chart.
+///
+/// ```text
+/// ┌───────────┬────────┬───────────────┐
+/// │    P1     │░░░░░░░░│      P2       │
+/// └───────────┴────────┴───────────────┘
+/// 0           3        5               9
+/// ```
+#[derive(Debug, Clone)]
+pub struct GanttChart {
+    /// Width of the chart in characters, row labels included.
+    pub width: usize,
+    /// Box drawing characters, or plain ASCII.
+    pub unicode: bool,
+    /// Color the processes with ANSI escape codes.
+    pub colors: bool,
+}
+
+impl Default for GanttChart {
+    fn default() -> GanttChart {
+        GanttChart::new()
+    }
+}
+
+// Common methods
+impl GanttChart {
+    /// A Unicode chart without colors, as wide as the terminal.
+    pub fn new() -> GanttChart {
+        GanttChart { width: terminal_width(), unicode: true, colors: false }
+    }
+
+    pub fn set_width(&mut self, width: usize) -> &mut GanttChart {
+        self.width = width;
+        self
+    }
+
+    pub fn set_unicode(&mut self, unicode: bool) -> &mut GanttChart {
+        self.unicode = unicode;
+        self
+    }
+
+    pub fn set_colors(&mut self, colors: bool) -> &mut GanttChart {
+        self.colors = colors;
+        self
+    }
+
+    fn style(&self) -> &'static Style {
+        if self.unicode { &UNICODE } else { &ASCII }
+    }
+
+    /// Color of a process, the same for all its slices (and threads).
+    fn color(event: &Event) -> u8 {
+        COLORS[color_key(event) as usize % COLORS.len()]
+    }
+}
+
+// Visualization
+impl GanttChart {
+    /// Chart of a timeline, one row per core (rows are labelled `Core n` when there are several cores).
+    pub fn render(&self, event_list: &[Event]) -> String {
+        let mut cores: Vec<usize> = event_list.iter().map(|event| event.core).collect();
+        cores.sort_unstable();
+        cores.dedup();
+        let rows: Vec<(String, Vec<&Event>)> = cores
+            .iter()
+            .map(|&core| {
+                let label: String = if cores.len() > 1 { format!("Core {}", core) } else { String::new() };
+                (label, event_list.iter().filter(|event| event.core == core).collect())
+            })
+            .collect();
+        self.render_rows(&rows)
+    }
+
+    /// Chart of labelled rows sharing one time axis (e.g. the CPU and the I/O devices).
+    pub fn render_rows(&self, rows: &[(String, Vec<&Event>)]) -> String {
+        let style: &Style = self.style();
+        let finish_time: f64 = rows
+            .iter()
+            .flat_map(|(_, events)| events.iter().map(|event| event.finish_time))
+            .fold(0.0, f64::max);
+        if finish_time <= 0.0 {
+            return String::new();
+        }
+
+        // Split every row into segments, with idle segments in the gaps, up to the end of the chart.
+        let segments: Vec<Vec<Segment>> = rows.iter().map(|(_, events)| segments(events, finish_time)).collect();
+
+        // Place the boundaries: proportional to the time, but at least two columns apart so that every segment shows
+        // (one column apart, or even merged, when there are too many segments for the width of the chart).
+        let mut boundaries: Vec<f64> = segments
+            .iter()
+            .flatten()
+            .flat_map(|segment| [segment.start_time, segment.finish_time])
+            .collect();
+        boundaries.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        boundaries.dedup_by(|a, b| (*a - *b).abs() < EPSILON);
+        let label_width: usize = rows.iter().map(|(label, _)| label.chars().count()).max().unwrap_or(0);
+        let prefix_width: usize = if label_width > 0 { label_width + 1 } else { 0 };
+        let available: usize = self.width.saturating_sub(prefix_width + 1);
+        let gaps: usize = boundaries.len() - 1;
+        let minimum_gap: usize = if 2 * gaps <= available { 2 } else if gaps <= available { 1 } else { 0 };
+        let mut columns: Vec<usize> = Vec::new();
+        for &boundary in boundaries.iter() {
+            let column: usize = (boundary / finish_time * available as f64).round() as usize;
+            columns.push(match columns.last() {
+                Some(&previous) => column.max(previous + minimum_gap),
+                None => column,
+            });
+        }
+        // Then push the boundaries that went past the width back to the left.
+        let mut limit: usize = available;
+        for column in columns.iter_mut().rev() {
+            *column = (*column).min(limit);
+            limit = column.saturating_sub(minimum_gap);
+        }
+        let column = |time: f64| -> usize {
+            let index: usize = boundaries.iter().position(|&boundary| (boundary - time).abs() < EPSILON).unwrap();
+            columns[index]
+        };
+
+        let mut chart: String = String::new();
+        for ((label, _), segments) in rows.iter().zip(segments.iter()) {
+            let mut top: String = " ".repeat(prefix_width);
+            let mut middle: String = format!("{:<width$}", label, width = prefix_width);
+            let mut bottom: String = " ".repeat(prefix_width);
+            // Segments merged into a single column are left out.
+            let visible = segments.iter().filter(|segment| column(segment.finish_time) > column(segment.start_time));
+            for (index, segment) in visible.enumerate() {
+                let width: usize = column(segment.finish_time) - column(segment.start_time) - 1;
+                top.push(if index == 0 { style.top[0] } else { style.top[1] });
+                bottom.push(if index == 0 { style.bottom[0] } else { style.bottom[1] });
+                middle.push(style.vertical);
+                top.extend(std::iter::repeat_n(style.horizontal, width));
+                bottom.extend(std::iter::repeat_n(style.horizontal, width));
+
+                // The name of the process if it fits, otherwise a fill.
+                let (text, color): (String, Option<u8>) = match segment.event {
+                    None => (style.idle_fill.to_string().repeat(width), None),
+                    Some(event) => {
+                        let fill: char = match event.kind {
+                            EventKind::Process => style.process_fill,
+                            EventKind::ContextSwitch => style.context_switch_fill,
+                        };
+                        let text: String = if event.name.chars().count() <= width {
+                            format!("{:^width$}", event.name, width = width)
+                        } else {
+                            fill.to_string().repeat(width)
+                        };
+                        let color: u8 = match event.kind {
+                            EventKind::Process => GanttChart::color(event),
+                            EventKind::ContextSwitch => 90,
+                        };
+                        (text, Some(color))
+                    }
+                };
+                match color {
+                    Some(color) if self.colors => middle.push_str(&format!("\x1b[{}m{}\x1b[0m", color, text)),
+                    _ => middle.push_str(&text),
+                }
+            }
+            top.push(style.top[2]);
+            middle.push(style.vertical);
+            bottom.push(style.bottom[2]);
+            chart += &format!("{}\n{}\n{}\n", top, middle, bottom);
+        }
+
+        // Time labels under the boundaries, skipping those that would overlap (the last one is always shown,
+        // moved left if it would go past the width).
+        let mut labels: Vec<(usize, String)> = Vec::new();
+        for (index, (&boundary, &column)) in boundaries.iter().zip(columns.iter()).enumerate() {
+            let label: String = format_time(boundary);
+            let last: bool = index == boundaries.len() - 1;
+            let column: usize = if last { column.min((available + 1).saturating_sub(label.len())) } else { column };
+            if last {
+                while labels.last().is_some_and(|(start, text)| start + text.len() >= column) {
+                    labels.pop();
+                }
+            } else if labels.last().is_some_and(|(start, text)| start + text.len() >= column) {
+                continue;
+            }
+            labels.push((column, label));
+        }
+        let mut axis: String = " ".repeat(prefix_width);
+        let mut position: usize = 0;
+        for (column, label) in labels {
+            axis += &" ".repeat(column - position);
+            position = column + label.len();
+            axis += &label;
+        }
+        chart += &axis;
+        chart.push('\n');
+        chart
+    }
+
+    /// Print the chart of a timeline.
+    pub fn print(&self, event_list: &[Event]) {
+        print!("{}", self.render(event_list));
+    }
+}
+
+/// Rows of an SVG chart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rows {
+    /// One row per core, with the idle time and the context switches of the core.
+    Cores,
+    /// One row per process (or task), and a last row with the context switches and the time no core runs anything.
+    Processes,
+}
+
+/// A deadline marker of an SVG chart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deadline {
+    /// Name of the process or task (`T1`); in the `Processes` layout the marker is drawn on its row.
+    pub name: String,
+    pub time: f64,
+    pub missed: bool,
+}
+
+/// Fill colors given to the processes, in turn.
+pub(crate) const PALETTE: [&str; 10] = [
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7", "#9c755f", "#bab0ac",
+];
+
+/// Escape the characters that are special in XML (and HTML) text and attribute values.
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&apos;")
+}
+
+/// A step of 1, 2 or 5 times a power of ten giving about `count` ticks over `length`.
+fn tick_step(length: f64, count: f64) -> f64 {
+    let raw: f64 = length / count.max(1.0);
+    let magnitude: f64 = 10f64.powf(raw.log10().floor());
+    [1.0, 2.0, 5.0, 10.0].iter().map(|factor| factor * magnitude).find(|&step| step >= raw).unwrap_or(10.0 * magnitude)
+}
+
+/// Gantt chart of an event timeline as an SVG image, for slides and reports: colored slices (one color per process),
+/// hatched idle time and context switches, a time axis and optional deadline markers.
+#[derive(Debug, Clone)]
+pub struct SvgGanttChart {
+    /// Width of the image in pixels.
+    pub width: usize,
+    /// Height of a row in pixels.
+    pub row_height: usize,
+    pub rows: Rows,
+    pub deadlines: Vec<Deadline>,
+}
+
+impl Default for SvgGanttChart {
+    fn default() -> SvgGanttChart {
+        SvgGanttChart::new()
+    }
+}
+
+// Common methods
+impl SvgGanttChart {
+    pub fn new() -> SvgGanttChart {
+        SvgGanttChart { width: 800, row_height: 28, rows: Rows::Cores, deadlines: Vec::new() }
+    }
+
+    pub fn set_width(&mut self, width: usize) -> &mut SvgGanttChart {
+        self.width = width;
+        self
+    }
+
+    pub fn set_row_height(&mut self, row_height: usize) -> &mut SvgGanttChart {
+        self.row_height = row_height;
+        self
+    }
+
+    pub fn set_rows(&mut self, rows: Rows) -> &mut SvgGanttChart {
+        self.rows = rows;
+        self
+    }
+
+    pub fn add_deadline(&mut self, name: &str, time: f64, missed: bool) -> &mut SvgGanttChart {
+        self.deadlines.push(Deadline { name: name.to_string(), time, missed });
+        self
+    }
+
+    /// Mark the deadline of every job of the tasks up to `horizon`, the missed ones in red.
+    pub fn add_task_deadlines(&mut self, tasks: &[Task], deadline_misses: &[DeadlineMiss], horizon: f64) -> &mut SvgGanttChart {
+        for task in tasks.iter() {
+            let mut release_time: f64 = 0.0;
+            while release_time + task.deadline <= horizon + EPSILON {
+                let time: f64 = release_time + task.deadline;
+                let missed: bool = deadline_misses
+                    .iter()
+                    .any(|miss| miss.task_id == task.id && (miss.deadline - time).abs() < EPSILON);
+                self.add_deadline(&format!("T{}", task.id), time, missed);
+                release_time += task.period;
+            }
+        }
+        self
+    }
+
+    /// Labelled rows of segments of the chart.
+    fn rows<'a>(&self, event_list: &'a [Event], finish_time: f64) -> Vec<(String, Vec<Segment<'a>>)> {
+        match self.rows {
+            Rows::Cores => {
+                let mut cores: Vec<usize> = event_list.iter().map(|event| event.core).collect();
+                cores.sort_unstable();
+                cores.dedup();
+                cores
+                    .iter()
+                    .map(|&core| {
+                        let events: Vec<&Event> = event_list.iter().filter(|event| event.core == core).collect();
+                        (format!("Core {}", core), segments(&events, finish_time))
+                    })
+                    .collect()
+            }
+            Rows::Processes => {
+                let processes: Vec<&Event> = event_list.iter().filter(|event| event.kind == EventKind::Process).collect();
+                let mut names: Vec<(u32, &str)> = processes.iter().map(|event| (color_key(event), row_name(&event.name))).collect();
+                names.sort_unstable();
+                names.dedup();
+                let mut rows: Vec<(String, Vec<Segment>)> = names
+                    .iter()
+                    .map(|&(_, name)| {
+                        let segments: Vec<Segment> = processes
+                            .iter()
+                            .filter(|event| row_name(&event.name) == name)
+                            .map(|event| Segment { event: Some(event), start_time: event.start_time, finish_time: event.finish_time })
+                            .collect();
+                        (name.to_string(), segments)
+                    })
+                    .collect();
+
+                // The context switches, and the gaps of the whole timeline as idle time.
+                let all_events: Vec<&Event> = event_list.iter().collect();
+                let dispatcher: Vec<Segment> = segments(&all_events, finish_time)
+                    .into_iter()
+                    .filter(|segment| segment.event.is_none_or(|event| event.kind == EventKind::ContextSwitch))
+                    .collect();
+                if !dispatcher.is_empty() {
+                    rows.push((String::from("CPU"), dispatcher));
+                }
+                rows
+            }
+        }
+    }
+}
+
+// Visualization
+impl SvgGanttChart {
+    pub fn render(&self, event_list: &[Event]) -> String {
+        let finish_time: f64 = event_list
+            .iter()
+            .map(|event| event.finish_time)
+            .chain(self.deadlines.iter().map(|deadline| deadline.time))
+            .fold(0.0, f64::max);
+        let rows: Vec<(String, Vec<Segment>)> = if finish_time > 0.0 { self.rows(event_list, finish_time) } else { Vec::new() };
+
+        let row_height: f64 = self.row_height as f64;
+        let gap: f64 = 6.0;
+        let top: f64 = 16.0;
+        let left: f64 = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0) as f64 * 7.5 + 16.0;
+        let right: f64 = 24.0;
+        let plot_width: f64 = (self.width as f64 - left - right).max(1.0);
+        let plot_height: f64 = rows.len() as f64 * (row_height + gap);
+        let height: f64 = top + plot_height + 36.0;
+        let x = |time: f64| -> f64 { left + if finish_time > 0.0 { time / finish_time * plot_width } else { 0.0 } };
+        let row_top = |row: usize| -> f64 { top + row as f64 * (row_height + gap) };
+
+        let mut svg: String = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{:.0}\" font-family=\"sans-serif\" font-size=\"12\">\n",
+            self.width, height
+        );
+        svg += "<defs>\n";
+        svg += "<pattern id=\"idle\" width=\"6\" height=\"6\" patternUnits=\"userSpaceOnUse\" patternTransform=\"rotate(45)\">\
+                <rect width=\"6\" height=\"6\" fill=\"white\"/><line x1=\"0\" y1=\"0\" x2=\"0\" y2=\"6\" stroke=\"#bbbbbb\" stroke-width=\"2\"/></pattern>\n";
+        svg += "<pattern id=\"context-switch\" width=\"6\" height=\"6\" patternUnits=\"userSpaceOnUse\">\
+                <rect width=\"6\" height=\"6\" fill=\"#dddddd\"/><path d=\"M0,0 L6,6 M6,0 L0,6\" stroke=\"#555555\" stroke-width=\"1\"/></pattern>\n";
+        svg += "</defs>\n";
+
+        // Time axis and grid.
+        let axis_y: f64 = top + plot_height;
+        svg += &format!("<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"black\"/>\n", left, axis_y, left + plot_width, axis_y);
+        if finish_time > 0.0 {
+            let step: f64 = tick_step(finish_time, plot_width / 60.0);
+            let mut tick: f64 = 0.0;
+            while tick <= finish_time + EPSILON {
+                svg += &format!(
+                    "<line x1=\"{x:.1}\" y1=\"{t:.1}\" x2=\"{x:.1}\" y2=\"{b:.1}\" stroke=\"#e0e0e0\"/>\n",
+                    x = x(tick), t = top, b = axis_y + 4.0
+                );
+                svg += &format!(
+                    "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>\n",
+                    x(tick), axis_y + 18.0, format_time(tick)
+                );
+                tick += step;
+            }
+        }
+
+        // Rows.
+        for (row, (label, segments)) in rows.iter().enumerate() {
+            let y: f64 = row_top(row);
+            svg += &format!(
+                "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>\n",
+                left - 8.0, y + row_height / 2.0 + 4.0, escape(label)
+            );
+            for segment in segments.iter() {
+                let (fill, name): (String, String) = match segment.event {
+                    None => (String::from("url(#idle)"), String::from("idle")),
+                    Some(event) if event.kind == EventKind::ContextSwitch => (String::from("url(#context-switch)"), event.name.clone()),
+                    Some(event) => (PALETTE[color_key(event) as usize % PALETTE.len()].to_string(), event.name.clone()),
+                };
+                let width: f64 = x(segment.finish_time) - x(segment.start_time);
+                svg += &format!(
+                    "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\" stroke=\"#333333\" stroke-width=\"0.5\">\
+                     <title>{} [{:.2}, {:.2}]</title></rect>\n",
+                    x(segment.start_time), y, width, row_height, fill, escape(&name), segment.start_time, segment.finish_time
+                );
+                let is_process: bool = segment.event.is_some_and(|event| event.kind == EventKind::Process);
+                if is_process && width >= name.len() as f64 * 7.5 + 4.0 {
+                    svg += &format!(
+                        "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" fill=\"white\">{}</text>\n",
+                        x(segment.start_time) + width / 2.0, y + row_height / 2.0 + 4.0, escape(&name)
+                    );
+                }
+            }
+        }
+
+        // Deadlines: on the row of their task if it has one, across the chart otherwise.
+        for deadline in self.deadlines.iter() {
+            let row: Option<usize> = match self.rows {
+                Rows::Processes => rows.iter().position(|(label, _)| *label == deadline.name),
+                Rows::Cores => None,
+            };
+            let (y1, y2): (f64, f64) = match row {
+                Some(row) => (row_top(row) - 3.0, row_top(row) + row_height + 3.0),
+                None => (top - 6.0, axis_y),
+            };
+            let color: &str = if deadline.missed { "#d62728" } else { "#333333" };
+            let status: &str = if deadline.missed { " (missed)" } else { "" };
+            svg += &format!(
+                "<g stroke=\"{c}\" fill=\"{c}\"><title>{n} deadline {t:.2}{s}</title>\
+                 <line x1=\"{x:.1}\" y1=\"{y1:.1}\" x2=\"{x:.1}\" y2=\"{y2:.1}\" stroke-width=\"1.5\" stroke-dasharray=\"4 2\"/>\
+                 <polygon points=\"{l:.1},{y0:.1} {r:.1},{y0:.1} {x:.1},{y1:.1}\"/></g>\n",
+                c = color, n = escape(&deadline.name), t = deadline.time, s = status,
+                x = x(deadline.time), y1 = y1, y2 = y2, l = x(deadline.time) - 4.0, r = x(deadline.time) + 4.0, y0 = y1 - 6.0
+            );
+        }
+
+        svg += "</svg>\n";
+        svg
+    }
+}
+
+// Test idle time, preemption, several cores, scaling and the ASCII style, then the SVG charts.
+#[test]
+fn test() {
+    use crate::cpu_schedulers::{Process, ContextSwitch};
+    use crate::schedule::{Algorithm, ScheduleResult};
+
+    let processes: Vec<Process> = vec![Process::new(1, 0.0, 3.0), Process::new(2, 5.0, 4.0)];
+    let result: ScheduleResult = Algorithm::Fcfs.run(&processes, ContextSwitch::None);
+    let mut gantt_chart: GanttChart = GanttChart::new();
+    gantt_chart.set_width(38);
+    assert_eq!(
+        gantt_chart.render(&result.event_list),
+        "┌───────────┬────────┬───────────────┐\n\
+         │    P1     │░░░░░░░░│      P2       │\n\
+         └───────────┴────────┴───────────────┘\n\
+         0           3        5               9\n"
+    );
+
+    // Round robin with context switches, squeezed into a narrow ASCII chart.
+    let processes: Vec<Process> = vec![Process::new(1, 0.0, 5.0), Process::new(2, 0.0, 3.0)];
+    let result: ScheduleResult = Algorithm::Rr { time_quantum: 2.0 }.run(&processes, ContextSwitch::PerSwitch(0.5));
+    gantt_chart.set_width(30).set_unicode(false);
+    let chart: String = gantt_chart.render(&result.event_list);
+    let lines: Vec<&str> = chart.lines().collect();
+    assert_eq!(lines[1].matches('|').count(), result.event_list.len() + 1);
+    assert!(lines[1].contains('~'));
+    assert!(lines[3].starts_with('0') && lines[3].ends_with("10.5"));
+    assert!(lines.iter().all(|line| line.chars().count() <= 30), "{}", chart);
+
+    // Many short slices after a long one still fit in the width.
+    let slice = |name: &str, start_time: f64, finish_time: f64| -> Event {
+        Event { name: name.to_string(), kind: EventKind::Process, core: 0, start_time, finish_time }
+    };
+    let short_slices: Vec<Event> = vec![slice("P1", 0.0, 100.0), slice("P2", 100.0, 100.01), slice("P3", 100.01, 100.02), slice("P4", 100.02, 100.03)];
+    for width in [80, 12, 8] {
+        let chart: String = gantt_chart.set_width(width).render(&short_slices);
+        assert!(chart.lines().all(|line| line.chars().count() <= width), "{}", chart);
+    }
+    let chart: String = gantt_chart.set_width(80).render(&short_slices);
+    assert_eq!(chart.lines().nth(1).unwrap().matches('|').count(), 5);
+
+    // Several cores are labelled and share the time axis.
+    let mut event_list: Vec<Event> = result.event_list.clone();
+    event_list.push(Event { name: String::from("P3"), kind: EventKind::Process, core: 1, start_time: 1.0, finish_time: 4.0 });
+    gantt_chart.set_width(60).set_unicode(true).set_colors(true);
+    let chart: String = gantt_chart.render(&event_list);
+    let lines: Vec<&str> = chart.lines().collect();
+    assert_eq!(lines.len(), 7);
+    assert!(lines[1].starts_with("Core 0 │") && lines[4].starts_with("Core 1 │░"));
+    assert!(lines[4].contains("\x1b[") && lines[4].ends_with("░│"));
+    assert_eq!(lines[0].chars().count(), lines[3].chars().count());
+
+    // SVG: one row per core with hatched context switches, or one row per process.
+    let mut svg_chart: SvgGanttChart = SvgGanttChart::new();
+    let svg: String = svg_chart.render(&event_list);
+    assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+    assert_eq!(svg.matches("<title>CS [").count(), result.event_list.iter().filter(|event| event.kind == EventKind::ContextSwitch).count());
+    assert!(svg.contains(">Core 1</text>") && svg.contains("<title>idle [0.00, 1.00]</title>"));
+    let svg: String = svg_chart.set_rows(Rows::Processes).render(&event_list);
+    assert!(svg.contains(">P1</text>") && svg.contains(">P3</text>") && svg.contains(">CPU</text>"));
+    assert!(!svg.contains(">Core 0</text>"));
+
+    // Deadline markers of real-time tasks, the missed ones in red.
+    let tasks: Vec<Task> = vec![Task::new(1, 2.0, 4.0), Task::new(2, 3.0, 5.0)];
+    let deadline_misses: Vec<DeadlineMiss> = vec![DeadlineMiss { task_id: 2, job: 1, deadline: 10.0, remaining_time: 1.0 }];
+    let svg: String = SvgGanttChart::new().add_task_deadlines(&tasks, &deadline_misses, 10.0).render(&[]);
+    assert_eq!(svg.matches("<title>T1 deadline").count(), 2);
+    assert!(svg.contains("<title>T2 deadline 10.00 (missed)</title>"));
+    assert_eq!(svg.matches("(missed)").count(), 1);
+
+    assert_eq!(escape("<a & \"b\" 'c'>"), "&lt;a &amp; &quot;b&quot; &apos;c&apos;&gt;");
+}
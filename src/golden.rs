@@ -0,0 +1,239 @@
+use std::fs;
+use std::path::Path;
+
+use crate::cpu_schedulers::{Process, ContextSwitch};
+use crate::csv;
+use crate::schedule::{Algorithm, ScheduleResult};
+
+/// A scheduling exercise with its known answer, read from a text file:
+///
+/// ```text
+/// # Silberschatz, FCFS
+/// algorithm: fcfs
+/// context_switch: none
+///
+/// processes:
+/// # pid arrival burst [priority]
+/// 1 0 24
+/// 2 0 3
+///
+/// timeline:
+/// # name start finish
+/// P1 0 24
+/// P2 24 27
+///
+/// average_waiting_time: 12.00
+/// average_turn_around_time: 25.50
+/// ```
+///
+/// Lines starting with `#` are comments; `context_switch` is optional (`none` by default).
+#[derive(Debug, Clone)]
+pub struct Exercise {
+    pub name: String,
+    pub algorithm: Algorithm,
+    pub context_switch: ContextSwitch,
+    pub processes: Vec<Process>,
+    /// (name, start time, finish time) of every slice, context switches included, in time order.
+    pub timeline: Vec<(String, f64, f64)>,
+    pub average_waiting_time: f64,
+    pub average_turn_around_time: f64,
+}
+
+/// Tolerance of the averages, which are written with two decimal places.
+const AVERAGE_TOLERANCE: f64 = 0.005 + 1e-9;
+
+fn parse_time(word: &str, line_number: usize) -> Result<f64, String> {
+    csv::parse_time(word, "time").map_err(|error| format!("line {}: {}", line_number, error))
+}
+
+impl Exercise {
+    pub fn parse(name: &str, text: &str) -> Result<Exercise, String> {
+        let mut algorithm: Option<Algorithm> = None;
+        let mut context_switch: ContextSwitch = ContextSwitch::None;
+        let mut processes: Vec<Process> = Vec::new();
+        let mut timeline: Vec<(String, f64, f64)> = Vec::new();
+        let mut average_waiting_time: Option<f64> = None;
+        let mut average_turn_around_time: Option<f64> = None;
+
+        let mut section: &str = "";
+        for (index, line) in text.lines().enumerate() {
+            let line_number: usize = index + 1;
+            let line: &str = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once(':') {
+                let value: &str = value.trim();
+                let key: &str = key.trim();
+                match key {
+                    "algorithm" => algorithm = Some(value.parse().map_err(|error| format!("line {}: {}", line_number, error))?),
+                    "context_switch" => context_switch = value.parse().map_err(|error| format!("line {}: {}", line_number, error))?,
+                    "average_waiting_time" => average_waiting_time = Some(parse_time(value, line_number)?),
+                    "average_turn_around_time" => average_turn_around_time = Some(parse_time(value, line_number)?),
+                    "processes" | "timeline" if value.is_empty() => section = if key == "processes" { "processes" } else { "timeline" },
+                    other => return Err(format!("line {}: unknown key `{}`", line_number, other)),
+                }
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match (section, words.len()) {
+                ("processes", 3 | 4) => {
+                    let pid: u32 = words[0].parse().map_err(|_| format!("line {}: invalid pid `{}`", line_number, words[0]))?;
+                    let arrival_time: f64 = parse_time(words[1], line_number)?;
+                    let burst_time: f64 = parse_time(words[2], line_number)?;
+                    let priority: u32 = match words.get(3) {
+                        Some(word) => word.parse().map_err(|_| format!("line {}: invalid priority `{}`", line_number, word))?,
+                        None => 0,
+                    };
+                    processes.push(Process::new_with_priority(pid, arrival_time, burst_time, priority));
+                }
+                ("timeline", 3) => {
+                    timeline.push((words[0].to_string(), parse_time(words[1], line_number)?, parse_time(words[2], line_number)?));
+                }
+                _ => return Err(format!("line {}: unexpected `{}`", line_number, line)),
+            }
+        }
+
+        Ok(Exercise {
+            name: name.to_string(),
+            algorithm: algorithm.ok_or("missing `algorithm`")?,
+            context_switch,
+            processes,
+            timeline,
+            average_waiting_time: average_waiting_time.ok_or("missing `average_waiting_time`")?,
+            average_turn_around_time: average_turn_around_time.ok_or("missing `average_turn_around_time`")?,
+        })
+    }
+
+    /// Every `.txt` file of a directory, sorted by name.
+    pub fn load_directory(directory: &Path) -> Result<Vec<Exercise>, String> {
+        let mut paths: Vec<_> = fs::read_dir(directory)
+            .map_err(|error| format!("{}: {}", directory.display(), error))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|extension| extension == "txt"))
+            .collect();
+        paths.sort();
+        paths
+            .iter()
+            .map(|path| {
+                let text: String = fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+                let name: String = path.file_stem().unwrap().to_string_lossy().to_string();
+                Exercise::parse(&name, &text).map_err(|error| format!("{}: {}", path.display(), error))
+            })
+            .collect()
+    }
+
+    /// Run the exercise and compare the result with the answer; the error is a readable diff.
+    pub fn check(&self) -> Result<(), String> {
+        let result: ScheduleResult = self.algorithm.run(&self.processes, self.context_switch);
+        let timeline: Vec<(String, f64, f64)> = actual_timeline(&result);
+        let average = |time: fn(&Process) -> f64| -> f64 {
+            result.finished_processes.iter().map(time).sum::<f64>() / result.finished_processes.len() as f64
+        };
+        let average_waiting_time: f64 = average(|process| process.waiting_time);
+        let average_turn_around_time: f64 = average(|process| process.turn_around_time);
+
+        let mut report: Vec<String> = Vec::new();
+        let same_timeline: bool = timeline.len() == self.timeline.len()
+            && timeline.iter().zip(self.timeline.iter()).all(|(actual, expected)| same_slice(actual, expected));
+        if !same_timeline {
+            report.push(String::from("timeline (- expected, + actual):"));
+            report.extend(diff(&self.timeline, &timeline));
+        }
+        if (average_waiting_time - self.average_waiting_time).abs() > AVERAGE_TOLERANCE {
+            report.push(format!("average waiting time: expected {:.2}, actual {:.2}", self.average_waiting_time, average_waiting_time));
+        }
+        if (average_turn_around_time - self.average_turn_around_time).abs() > AVERAGE_TOLERANCE {
+            report.push(format!(
+                "average turn around time: expected {:.2}, actual {:.2}",
+                self.average_turn_around_time, average_turn_around_time
+            ));
+        }
+
+        if report.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("{} ({}):\n{}", self.name, result.algorithm, report.join("\n")))
+        }
+    }
+}
+
+/// The timeline of a result in time order.
+fn actual_timeline(result: &ScheduleResult) -> Vec<(String, f64, f64)> {
+    let mut timeline: Vec<(String, f64, f64)> = result
+        .event_list
+        .iter()
+        .map(|event| (event.name.clone(), event.start_time, event.finish_time))
+        .collect();
+    timeline.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    timeline
+}
+
+fn same_slice(a: &(String, f64, f64), b: &(String, f64, f64)) -> bool {
+    a.0 == b.0 && (a.1 - b.1).abs() < 1e-6 && (a.2 - b.2).abs() < 1e-6
+}
+
+/// Line diff of two timelines (longest common subsequence), with unchanged lines kept for context.
+fn diff(expected: &[(String, f64, f64)], actual: &[(String, f64, f64)]) -> Vec<String> {
+    let line = |marker: char, slice: &(String, f64, f64)| format!("{} {:<6}{:>8.2}{:>8.2}", marker, slice.0, slice.1, slice.2);
+
+    // lengths[i][j] = length of the longest common subsequence of expected[i..] and actual[j..].
+    let mut lengths: Vec<Vec<usize>> = vec![vec![0; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lengths[i][j] = if same_slice(&expected[i], &actual[j]) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && same_slice(&expected[i], &actual[j]) {
+            lines.push(line(' ', &expected[i]));
+            i += 1;
+            j += 1;
+        } else if j < actual.len() && (i == expected.len() || lengths[i][j + 1] >= lengths[i + 1][j]) {
+            lines.push(line('+', &actual[j]));
+            j += 1;
+        } else {
+            lines.push(line('-', &expected[i]));
+            i += 1;
+        }
+    }
+    lines
+}
+
+// Test every exercise of the corpus, and the diff of a wrong answer.
+#[test]
+fn test() {
+    use crate::validator::run_and_validate;
+
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("data").join("exercises");
+    let exercises: Vec<Exercise> = Exercise::load_directory(&directory).unwrap();
+    assert!(exercises.len() >= 8);
+    let failures: Vec<String> = exercises.iter().filter_map(|exercise| exercise.check().err()).collect();
+    assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+    for exercise in exercises.iter() {
+        run_and_validate(&exercise.algorithm, &exercise.processes, exercise.context_switch);
+    }
+
+    let mut wrong: Exercise = exercises[0].clone();
+    wrong.timeline[1].0 = String::from("P9");
+    wrong.average_waiting_time += 1.0;
+    let report: String = wrong.check().unwrap_err();
+    assert!(report.contains("- P9"));
+    assert!(report.contains("+ P2"));
+    assert!(report.contains("average waiting time: expected"));
+
+    // Spaces around the keys are ignored, the section ones included.
+    let text: &str = "algorithm : fcfs\nprocesses :\n1 0 2\ntimeline :\nP1 0 2\naverage_waiting_time : 0\naverage_turn_around_time : 2\n";
+    let exercise: Exercise = Exercise::parse("spaces", text).unwrap();
+    assert_eq!(exercise.processes.len(), 1);
+    assert_eq!(exercise.timeline.len(), 1);
+    let error: String = Exercise::parse("negative", &text.replace("1 0 2", "1 -1 2")).unwrap_err();
+    assert_eq!(error, "line 3: time `-1` must be non negative with at most two decimal places");
+}
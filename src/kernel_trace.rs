@@ -0,0 +1,428 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::comparison::Comparison;
+use crate::cpu_schedulers::{Process, Burst, Event, EventKind, ContextSwitch, ProcessState};
+use crate::schedule::{Algorithm, ScheduleResult};
+
+/// A run of a task on a CPU, as read from a trace (times in seconds, as in the trace).
+#[derive(Debug, Clone)]
+struct Run {
+    pid: u32,
+    cpu: usize,
+    start_time: f64,
+    finish_time: f64,
+    /// Time the task slept (was blocked) before this run.
+    sleep_time: f64,
+    /// Time the task became ready for this run (its wakeup, or the start if unknown).
+    ready_time: f64,
+}
+
+/// A scheduling trace recorded on a real machine, from `perf sched timehist` or ftrace `sched_switch` output.
+///
+/// Times are in milliseconds from the first event of the trace, rounded to hundredths like everywhere else.
+/// Every task becomes a process (its pid is the kernel tid): it arrives when it is first seen ready,
+/// and its runs are CPU bursts separated by the times it slept, which are read as I/O bursts on device 0.
+#[derive(Debug, Clone)]
+pub struct KernelTrace {
+    /// Command name of every pid.
+    pub names: Vec<(u32, String)>,
+    /// The recorded timeline, one `P<pid>` slice per run.
+    pub event_list: Vec<Event>,
+    /// The tasks, with their CPU bursts and sleeps.
+    pub processes: Vec<Process>,
+    pub number_of_cpus: usize,
+}
+
+/// Value of a `key=value` field of an ftrace line.
+fn field<'a>(fields: &'a str, key: &str) -> Option<&'a str> {
+    let start: usize = fields.find(&format!("{}=", key))? + key.len() + 1;
+    fields[start..].split_whitespace().next()
+}
+
+/// A task of a `trace-cmd report` line, `<comm>:<pid> [<prio>] <rest>`: its command name, pid and the rest.
+fn compact_task(text: &str) -> Option<(&str, u32, &str)> {
+    let (task, rest) = text.trim().split_once(" [")?;
+    let (comm, pid) = task.rsplit_once(':')?;
+    let (_, rest) = rest.split_once(']')?;
+    Some((comm, pid.parse().ok()?, rest.trim()))
+}
+
+// Common methods
+impl KernelTrace {
+    /// Read a trace, whichever of the two formats it is.
+    pub fn parse(text: &str) -> Result<KernelTrace, String> {
+        if text.contains("sched_switch:") {
+            KernelTrace::parse_ftrace(text)
+        } else {
+            KernelTrace::parse_perf_timehist(text)
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<KernelTrace, String> {
+        let text: String = fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+        KernelTrace::parse(&text).map_err(|error| format!("{}: {}", path.display(), error))
+    }
+
+    /// Read ftrace output (`trace` or `trace-cmd report`) with `sched_switch` and, optionally,
+    /// `sched_wakeup` / `sched_wakeup_new` events; the other lines are ignored.
+    ///
+    /// ```text
+    ///   bash-1234  [001] d..2.  5000.000100: sched_switch: prev_comm=bash prev_pid=1234 prev_prio=120 prev_state=S ==> next_comm=gcc next_pid=1240 next_prio=120
+    ///   bash-1234  [001]  5000.000100: sched_switch:  bash:1234 [120] S ==> gcc:1240 [120]
+    /// ```
+    pub fn parse_ftrace(text: &str) -> Result<KernelTrace, String> {
+        let mut names: HashMap<u32, String> = HashMap::new();
+        let mut runs: Vec<Run> = Vec::new();
+        // Per CPU: the running task and when it started.
+        let mut running: HashMap<usize, (u32, f64)> = HashMap::new();
+        // Per task: when it blocked, and when it was woken up.
+        let mut blocked_since: HashMap<u32, f64> = HashMap::new();
+        let mut woken_at: HashMap<u32, f64> = HashMap::new();
+        let mut first_seen: HashMap<u32, f64> = HashMap::new();
+        let mut last_time: f64 = 0.0;
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number: usize = index + 1;
+            let line: &str = line.trim();
+            if line.starts_with('#') {
+                continue;
+            }
+            let Some((prefix, event)) = line.split_once(": sched_") else {
+                continue;
+            };
+            let error = |message: &str| format!("line {}: {}", line_number, message);
+
+            // "<comm>-<pid> [cpu] <flags> <timestamp>"
+            let cpu: usize = prefix
+                .split_once('[')
+                .and_then(|(_, rest)| rest.split_once(']'))
+                .and_then(|(cpu, _)| cpu.trim().parse().ok())
+                .ok_or_else(|| error("missing CPU number"))?;
+            let time: f64 = prefix
+                .split_whitespace()
+                .last()
+                .and_then(|time| time.parse().ok())
+                .ok_or_else(|| error("missing timestamp"))?;
+            last_time = last_time.max(time);
+            let (name, fields) = event.split_once(':').ok_or_else(|| error("missing event fields"))?;
+            let parse_pid = |key: &str| -> Result<u32, String> {
+                field(fields, key).and_then(|pid| pid.parse().ok()).ok_or_else(|| error(&format!("missing {}", key)))
+            };
+
+            match name {
+                "wakeup" | "wakeup_new" | "waking" => {
+                    let (pid, comm): (u32, Option<&str>) = if fields.contains("pid=") {
+                        (parse_pid("pid")?, field(fields, "comm"))
+                    } else {
+                        let (comm, pid, _) = compact_task(fields).ok_or_else(|| error("missing pid"))?;
+                        (pid, Some(comm))
+                    };
+                    if let Some(comm) = comm {
+                        names.insert(pid, comm.to_string());
+                    }
+                    woken_at.entry(pid).or_insert(time);
+                    first_seen.entry(pid).or_insert(time);
+                }
+                "switch" => {
+                    let (previous, next) = fields.split_once("==>").ok_or_else(|| error("sched_switch without `==>`"))?;
+                    let (previous_comm, previous_pid, previous_state, next_comm, next_pid) = if previous.contains("prev_pid=") {
+                        let next_pid: u32 = field(next, "next_pid")
+                            .and_then(|pid| pid.parse().ok())
+                            .ok_or_else(|| error("missing next_pid"))?;
+                        let previous_state: &str = field(previous, "prev_state").unwrap_or("R");
+                        (field(previous, "prev_comm"), parse_pid("prev_pid")?, previous_state, field(next, "next_comm"), next_pid)
+                    } else {
+                        let (previous_comm, previous_pid, previous_state) = compact_task(previous).ok_or_else(|| error("missing prev_pid"))?;
+                        let (next_comm, next_pid, _) = compact_task(next).ok_or_else(|| error("missing next_pid"))?;
+                        let previous_state: &str = if previous_state.is_empty() { "R" } else { previous_state };
+                        (Some(previous_comm), previous_pid, previous_state, Some(next_comm), next_pid)
+                    };
+
+                    // The previous task stops: preempted if still runnable, blocked otherwise.
+                    if let Some((pid, start_time)) = running.remove(&cpu) {
+                        if pid == previous_pid && pid != 0 {
+                            let (sleep_time, ready_time) = KernelTrace::readiness(pid, start_time, &mut blocked_since, &mut woken_at);
+                            runs.push(Run { pid, cpu, start_time, finish_time: time, sleep_time, ready_time });
+                        }
+                    }
+                    if previous_pid != 0 && !previous_state.starts_with('R') {
+                        blocked_since.insert(previous_pid, time);
+                        woken_at.remove(&previous_pid);
+                    }
+
+                    if next_pid != 0 {
+                        if let Some(comm) = next_comm {
+                            names.insert(next_pid, comm.to_string());
+                        }
+                        running.insert(cpu, (next_pid, time));
+                        first_seen.entry(next_pid).or_insert(time);
+                    }
+                    if previous_pid != 0 {
+                        if let Some(comm) = previous_comm {
+                            names.entry(previous_pid).or_insert_with(|| comm.to_string());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // The tasks still running at the end of the trace run until then.
+        let mut still_running: Vec<(usize, (u32, f64))> = running.into_iter().collect();
+        still_running.sort_unstable_by_key(|&(cpu, _)| cpu);
+        for (cpu, (pid, start_time)) in still_running {
+            let (sleep_time, ready_time) = KernelTrace::readiness(pid, start_time, &mut blocked_since, &mut woken_at);
+            runs.push(Run { pid, cpu, start_time, finish_time: last_time, sleep_time, ready_time });
+        }
+        if runs.is_empty() {
+            return Err(String::from("no sched_switch event"));
+        }
+        Ok(KernelTrace::from_runs(runs, names, first_seen))
+    }
+
+    /// Sleep time and ready time of a task starting to run at `start_time`.
+    fn readiness(pid: u32, start_time: f64, blocked_since: &mut HashMap<u32, f64>, woken_at: &mut HashMap<u32, f64>) -> (f64, f64) {
+        let woken: Option<f64> = woken_at.remove(&pid).filter(|&time| time <= start_time);
+        match blocked_since.remove(&pid) {
+            Some(blocked) => {
+                let ready_time: f64 = woken.unwrap_or(start_time).max(blocked);
+                (ready_time - blocked, ready_time)
+            }
+            None => (0.0, woken.unwrap_or(start_time)),
+        }
+    }
+
+    /// Read the output of `perf sched timehist` (one line per context switch, written when the task is switched out).
+    ///
+    /// ```text
+    ///            time    cpu  task name                       wait time  sch delay   run time
+    ///                         [tid/pid]                          (msec)     (msec)     (msec)
+    /// --------------- ------  ------------------------------  ---------  ---------  ---------
+    ///   79371.874569 [0011]  gcc[31949]                          0.014      0.000      1.148
+    /// ```
+    pub fn parse_perf_timehist(text: &str) -> Result<KernelTrace, String> {
+        let mut names: HashMap<u32, String> = HashMap::new();
+        let mut runs: Vec<Run> = Vec::new();
+        let mut first_seen: HashMap<u32, f64> = HashMap::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number: usize = index + 1;
+            let words: Vec<&str> = line.split_whitespace().collect();
+            // Skip the header, the separator and the empty lines.
+            let Some(time) = words.first().and_then(|word| word.parse::<f64>().ok()) else {
+                continue;
+            };
+            let error = |message: &str| format!("line {}: {}", line_number, message);
+            if words.len() < 6 {
+                return Err(error("expected time, cpu, task, wait time, sch delay and run time"));
+            }
+            let cpu: usize = words[1]
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse()
+                .map_err(|_| error(&format!("invalid CPU `{}`", words[1])))?;
+            // The task name may contain spaces: it is everything between the CPU and the last three columns.
+            let task: String = words[2..words.len() - 3].join(" ");
+            if task.starts_with("<idle>") {
+                continue;
+            }
+            let (comm, ids) = task
+                .rsplit_once('[')
+                .and_then(|(comm, ids)| Some((comm, ids.strip_suffix(']')?)))
+                .ok_or_else(|| error(&format!("invalid task `{}`", task)))?;
+            let pid: u32 = ids.split('/').next().and_then(|tid| tid.parse().ok()).ok_or_else(|| error(&format!("invalid tid `{}`", ids)))?;
+            let milliseconds = |word: &str| -> Result<f64, String> {
+                word.parse::<f64>().map(|time| time / 1000.0).map_err(|_| error(&format!("invalid time `{}`", word)))
+            };
+            let wait_time: f64 = milliseconds(words[words.len() - 3])?;
+            let scheduling_delay: f64 = milliseconds(words[words.len() - 2])?;
+            let run_time: f64 = milliseconds(words[words.len() - 1])?;
+
+            // Wait time is the time since the task was last switched out, the scheduling delay the part of it spent ready.
+            let start_time: f64 = time - run_time;
+            let ready_time: f64 = start_time - scheduling_delay;
+            names.insert(pid, comm.to_string());
+            first_seen.entry(pid).or_insert(ready_time);
+            runs.push(Run { pid, cpu, start_time, finish_time: time, sleep_time: (wait_time - scheduling_delay).max(0.0), ready_time });
+        }
+        if runs.is_empty() {
+            return Err(String::from("no task switch"));
+        }
+        Ok(KernelTrace::from_runs(runs, names, first_seen))
+    }
+
+    /// Build the timeline and the processes from the runs of the tasks.
+    fn from_runs(mut runs: Vec<Run>, names: HashMap<u32, String>, first_seen: HashMap<u32, f64>) -> KernelTrace {
+        runs.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+        let origin: f64 = first_seen.values().copied().chain(runs.iter().map(|run| run.start_time)).fold(f64::INFINITY, f64::min);
+        // Milliseconds since the start of the trace, rounded to hundredths.
+        let time = |seconds: f64| -> f64 { ((seconds - origin) * 100_000.0).round() / 100.0 };
+
+        let mut event_list: Vec<Event> = Vec::new();
+        let mut bursts: HashMap<u32, Vec<Burst>> = HashMap::new();
+        for run in runs.iter() {
+            let (start_time, finish_time) = (time(run.start_time), time(run.finish_time));
+            if finish_time <= start_time {
+                continue;
+            }
+            event_list.push(Event { name: format!("P{}", run.pid), kind: EventKind::Process, core: run.cpu, start_time, finish_time });
+
+            // A run after a sleep starts a new CPU burst, a run after a preemption continues the current one.
+            let bursts: &mut Vec<Burst> = bursts.entry(run.pid).or_default();
+            let sleep_time: f64 = (run.sleep_time * 100_000.0).round() / 100.0;
+            match bursts.last_mut() {
+                Some(Burst::Cpu(cpu_time)) if sleep_time <= 0.0 => *cpu_time = ((*cpu_time + finish_time - start_time) * 100.0).round() / 100.0,
+                Some(_) => bursts.extend([Burst::Io { device: 0, time: sleep_time }, Burst::Cpu(finish_time - start_time)]),
+                None => bursts.push(Burst::Cpu(finish_time - start_time)),
+            }
+        }
+
+        let mut pids: Vec<u32> = bursts.keys().copied().collect();
+        pids.sort_unstable();
+        let processes: Vec<Process> = pids
+            .iter()
+            .map(|&pid| {
+                let first_run: &Run = runs.iter().find(|run| run.pid == pid).unwrap();
+                let arrival_time: f64 = time(first_seen.get(&pid).copied().unwrap_or(first_run.ready_time).min(first_run.start_time));
+                let bursts: Vec<Burst> = bursts[&pid].clone();
+                if bursts.len() == 1 {
+                    Process::new(pid, arrival_time, bursts[0].time())
+                } else {
+                    Process::new_with_bursts(pid, arrival_time, bursts)
+                }
+            })
+            .collect();
+
+        let mut names: Vec<(u32, String)> = names.into_iter().filter(|(pid, _)| pids.contains(pid)).collect();
+        names.sort_unstable();
+        let number_of_cpus: usize = event_list.iter().map(|event| event.core + 1).max().unwrap_or(0);
+        KernelTrace { names, event_list, processes, number_of_cpus }
+    }
+
+    /// Keep only the runs on one CPU, e.g. to compare with a single core scheduler.
+    pub fn only_cpu(&self, cpu: usize) -> KernelTrace {
+        let event_list: Vec<Event> = self.event_list.iter().filter(|event| event.core == cpu).cloned().collect();
+        let pids: Vec<u32> = event_list.iter().filter_map(|event| event.pid()).collect();
+        let processes: Vec<Process> = self.processes.iter().filter(|process| pids.contains(&process.pid)).cloned().collect();
+        let names: Vec<(u32, String)> = self.names.iter().filter(|(pid, _)| pids.contains(pid)).cloned().collect();
+        KernelTrace { names, event_list, processes, number_of_cpus: 1 }
+    }
+
+    /// The workload as the CPU schedulers see it: one CPU burst per task, with its total CPU time.
+    pub fn workload(&self) -> Vec<Process> {
+        self.processes
+            .iter()
+            .map(|process| Process::new(process.pid, process.arrival_time, process.burst_time))
+            .collect()
+    }
+
+    /// The recorded schedule as a result, to compare with simulated ones.
+    /// The replayed workload has no I/O, so the times the tasks slept are left out of their waiting and turn around times.
+    pub fn recorded_result(&self) -> ScheduleResult {
+        let finished_processes: Vec<Process> = self
+            .workload()
+            .into_iter()
+            .zip(self.processes.iter())
+            .map(|(mut process, task)| {
+                let events: Vec<&Event> = self.event_list.iter().filter(|event| event.pid() == Some(process.pid)).collect();
+                process.start_time = events.iter().map(|event| event.start_time).fold(f64::INFINITY, f64::min);
+                process.finish_time = events.iter().map(|event| event.finish_time).fold(0.0, f64::max);
+                process.turn_around_time = process.finish_time - process.arrival_time - task.io_time();
+                process.waiting_time = process.turn_around_time - process.burst_time;
+                process.remaining_time = 0.0;
+                process.state = ProcessState::Terminated;
+                process
+            })
+            .collect();
+        let mut result: ScheduleResult =
+            ScheduleResult::new(String::from("Recorded"), finished_processes, self.event_list.clone(), self.number_of_cpus.max(1));
+        result.metrics.exclude_io_time(&self.processes);
+        result
+    }
+
+    /// Replay the workload through the algorithms and compare with the recorded schedule (the first result).
+    pub fn compare(&self, algorithms: &[Algorithm], context_switch: ContextSwitch) -> Comparison {
+        let mut comparison: Comparison = Comparison::new(self.workload(), algorithms, context_switch);
+        comparison.results.insert(0, self.recorded_result());
+        comparison
+    }
+}
+
+// Test both formats on the same small trace, and the comparison with the simulated schedulers.
+#[test]
+fn test() {
+    use crate::metrics::Metric;
+
+    let ftrace: &str = "\
+# tracer: nop
+#
+#           TASK-PID     CPU#  |||||  TIMESTAMP  FUNCTION
+          <idle>-0       [000] dN.2.  100.000000: sched_wakeup: comm=make pid=10 prio=120 target_cpu=000
+          <idle>-0       [000] d..2.  100.000000: sched_switch: prev_comm=swapper/0 prev_pid=0 prev_prio=120 prev_state=R ==> next_comm=make next_pid=10 next_prio=120
+            make-10      [000] d..2.  100.001000: sched_wakeup_new: comm=cc1 pid=11 prio=120 target_cpu=000
+            make-10      [000] d..2.  100.002000: sched_switch: prev_comm=make prev_pid=10 prev_prio=120 prev_state=S ==> next_comm=cc1 next_pid=11 next_prio=120
+             cc1-11      [000] d..2.  100.003000: sched_wakeup: comm=make pid=10 prio=120 target_cpu=000
+             cc1-11      [000] d..2.  100.005000: sched_switch: prev_comm=cc1 prev_pid=11 prev_prio=120 prev_state=R+ ==> next_comm=make next_pid=10 next_prio=120
+            make-10      [000] d..2.  100.006000: sched_switch: prev_comm=make prev_pid=10 prev_prio=120 prev_state=R ==> next_comm=cc1 next_pid=11 next_prio=120
+             cc1-11      [000] d..2.  100.008000: sched_switch: prev_comm=cc1 prev_pid=11 prev_prio=120 prev_state=X ==> next_comm=make next_pid=10 next_prio=120
+            make-10      [000] d..2.  100.009000: sched_switch: prev_comm=make prev_pid=10 prev_prio=120 prev_state=X ==> next_comm=swapper/0 next_pid=0 next_prio=120
+";
+    let trace: KernelTrace = KernelTrace::parse(ftrace).unwrap();
+    assert_eq!(trace.names, vec![(10, String::from("make")), (11, String::from("cc1"))]);
+    let timeline: Vec<(&str, f64, f64)> = trace.event_list.iter().map(|event| (event.name.as_str(), event.start_time, event.finish_time)).collect();
+    assert_eq!(timeline, vec![("P10", 0.0, 2.0), ("P11", 2.0, 5.0), ("P10", 5.0, 6.0), ("P11", 6.0, 8.0), ("P10", 8.0, 9.0)]);
+    // make sleeps from 2 to 3 (then waits for the CPU), cc1 is preempted once.
+    assert_eq!(trace.processes[0].bursts, vec![Burst::Cpu(2.0), Burst::Io { device: 0, time: 1.0 }, Burst::Cpu(2.0)]);
+    assert_eq!((trace.processes[1].arrival_time, trace.processes[1].burst_time, trace.processes[1].bursts.len()), (1.0, 5.0, 0));
+
+    // The same events in the compact format of trace-cmd report.
+    let report: &str = "\
+cpus=1
+          <idle>-0     [000]   100.000000: sched_wakeup:         make:10 [120] CPU:000
+          <idle>-0     [000]   100.000000: sched_switch:         swapper/0:0 [120] R ==> make:10 [120]
+            make-10    [000]   100.001000: sched_wakeup_new:     cc1:11 [120] CPU:000
+            make-10    [000]   100.002000: sched_switch:         make:10 [120] S ==> cc1:11 [120]
+             cc1-11    [000]   100.003000: sched_wakeup:         make:10 [120] success=1 CPU:000
+             cc1-11    [000]   100.005000: sched_switch:         cc1:11 [120] R+ ==> make:10 [120]
+            make-10    [000]   100.006000: sched_switch:         make:10 [120] R ==> cc1:11 [120]
+             cc1-11    [000]   100.008000: sched_switch:         cc1:11 [120] X ==> make:10 [120]
+            make-10    [000]   100.009000: sched_switch:         make:10 [120] X ==> swapper/0:0 [120]
+";
+    let trace_cmd: KernelTrace = KernelTrace::parse(report).unwrap();
+    assert_eq!(trace_cmd.names, trace.names);
+    assert_eq!(format!("{:?}", trace_cmd.event_list), format!("{:?}", trace.event_list));
+    assert_eq!(format!("{:?}", trace_cmd.processes), format!("{:?}", trace.processes));
+
+    // The same schedule seen by perf sched timehist.
+    let timehist: &str = "\
+           time    cpu  task name                       wait time  sch delay   run time
+                        [tid/pid]                          (msec)     (msec)     (msec)
+--------------- ------  ------------------------------  ---------  ---------  ---------
+     100.002000 [0000]  make[10]                            0.000      0.000      2.000
+     100.005000 [0000]  cc1[11]                             0.000      1.000      3.000
+     100.006000 [0000]  make[10]                            3.000      2.000      1.000
+     100.008000 [0000]  cc1[11/10]                          1.000      1.000      2.000
+     100.009000 [0000]  make[10]                            2.000      2.000      1.000
+     100.010000 [0000]  <idle>                              0.000      0.000      1.000
+";
+    let perf: KernelTrace = KernelTrace::parse(timehist).unwrap();
+    let perf_timeline: Vec<(&str, f64, f64)> = perf.event_list.iter().map(|event| (event.name.as_str(), event.start_time, event.finish_time)).collect();
+    assert_eq!(perf_timeline, timeline);
+    assert_eq!(perf.processes[0].bursts, trace.processes[0].bursts);
+    assert_eq!(perf.workload()[1].arrival_time, 1.0);
+
+    // Replay through the simulated schedulers.
+    let comparison: Comparison = trace.compare(&[Algorithm::Fcfs, Algorithm::Srtf], ContextSwitch::None);
+    assert_eq!(comparison.results[0].algorithm, "Recorded");
+    // make slept for 1, which is not waiting: it waited 4 and cc1 waited 2.
+    let recorded: &ScheduleResult = &comparison.results[0];
+    assert_eq!(recorded.finished_processes.iter().map(|process| process.waiting_time).collect::<Vec<f64>>(), vec![4.0, 2.0]);
+    assert_eq!(recorded.metrics.waiting_time.mean, 3.0);
+    assert_eq!(recorded.metrics.turn_around_time.mean, 7.5);
+    assert_eq!(comparison.results[2].metrics.turn_around_time.mean, 6.0);
+    // The kernel shared the CPU between both tasks, which SRTF does not.
+    assert_eq!(comparison.ranking(Metric::AverageTurnAroundTime)[2].algorithm, "Recorded");
+    assert_eq!(KernelTrace::parse("100.0 [x] a[1] 0 0 1\n").unwrap_err(), "line 1: invalid CPU `[x]`");
+}
@@ -1 +1,19 @@
 pub mod cpu_schedulers;
+pub mod metrics;
+pub mod gantt;
+pub mod schedule;
+pub mod comparison;
+pub mod sweep;
+pub mod workload;
+pub mod experiment;
+pub mod validator;
+pub mod golden;
+pub mod csv;
+pub mod trace;
+pub mod report;
+pub mod kernel_trace;
+pub mod cli;
+pub mod tui;
+pub mod simulation;
+#[cfg(feature = "serde")]
+pub mod serialization;
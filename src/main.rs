@@ -1,6 +1,17 @@
+use std::process::ExitCode;
 
-fn main() {
-    let mut v: Vec<u32> = vec![7, 6, 5, 4, 3, 2, 1];
-    v[2..5].sort_unstable();
-    println!("{:?}", v);
+use rust_os_int2214_library::cli;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match cli::run(&args) {
+        Ok(output) => {
+            print!("{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::from(error.exit_code() as u8)
+        }
+    }
 }
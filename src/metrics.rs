@@ -0,0 +1,409 @@
+use crate::cpu_schedulers::{Process, Event, EventKind};
+
+/// Bound used by the bounded slowdown, so that very short processes do not dominate it.
+pub const SLOWDOWN_BOUND: f64 = 1.0;
+
+/// Metrics of one process.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProcessMetrics {
+    pub pid: u32,
+    pub arrival_time: f64,
+    pub burst_time: f64,
+    pub first_run_time: f64,
+    pub finish_time: f64,
+    /// First run - arrival.
+    pub response_time: f64,
+    /// Turn around time - time spent running.
+    /// The threads of a process run side by side, so only its longest running thread counts.
+    pub waiting_time: f64,
+    /// Finish - arrival.
+    pub turn_around_time: f64,
+    /// Turn around time / burst time (of the longest thread for a multi-threaded process).
+    pub normalized_turn_around_time: f64,
+    /// Bounded slowdown: max(1, turn around time / max(burst time, `SLOWDOWN_BOUND`)),
+    /// with the burst time of the longest thread for a multi-threaded process.
+    pub slowdown: f64,
+}
+
+/// Summary of a set of values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Distribution {
+    pub mean: f64,
+    pub standard_deviation: f64,
+    pub min: f64,
+    pub max: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+}
+
+impl Distribution {
+    pub fn new(values: &[f64]) -> Distribution {
+        if values.is_empty() {
+            return Distribution { mean: 0.0, standard_deviation: 0.0, min: 0.0, max: 0.0, p50: 0.0, p90: 0.0, p99: 0.0 };
+        }
+        let mut sorted: Vec<f64> = values.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let mean: f64 = sorted.iter().sum::<f64>() / sorted.len() as f64;
+        let variance: f64 = sorted.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / sorted.len() as f64;
+        Distribution {
+            mean,
+            standard_deviation: variance.sqrt(),
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            p50: percentile(&sorted, 50.0),
+            p90: percentile(&sorted, 90.0),
+            p99: percentile(&sorted, 99.0),
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted values.
+pub fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank: usize = ((percent / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Jain's fairness index: (Σx)² / (n·Σx²). 1 when all values are equal, 1/n when one value takes everything.
+pub fn jain_fairness_index(values: &[f64]) -> f64 {
+    let sum: f64 = values.iter().sum();
+    let sum_of_squares: f64 = values.iter().map(|value| value * value).sum();
+    if sum_of_squares == 0.0 { 1.0 } else { sum * sum / (values.len() as f64 * sum_of_squares) }
+}
+
+/// Metrics of a schedule, derived from the event timeline.
+/// The processes are only used for their pid, arrival time, burst time and threads.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metrics {
+    pub processes: Vec<ProcessMetrics>,
+    pub number_of_cores: usize,
+    pub start_time: f64,
+    pub finish_time: f64,
+    pub busy_time: f64,
+    pub context_switch_time: f64,
+    pub idle_time: f64,
+    /// Busy time / (elapsed time · number of cores).
+    pub cpu_utilization: f64,
+    /// Finished processes per time unit.
+    pub throughput: f64,
+    /// Jain's fairness index of the progress rates (burst time / turn around time).
+    pub fairness_index: f64,
+    pub response_time: Distribution,
+    pub waiting_time: Distribution,
+    pub turn_around_time: Distribution,
+    pub normalized_turn_around_time: Distribution,
+    pub slowdown: Distribution,
+}
+
+impl Metrics {
+    pub fn new(processes: &[Process], event_list: &[Event], number_of_cores: usize) -> Metrics {
+        // Per-process metrics.
+        let mut process_metrics: Vec<ProcessMetrics> = Vec::new();
+        for process in processes.iter() {
+            let events: Vec<&Event> = event_list.iter().filter(|event| event.pid() == Some(process.pid)).collect();
+            if events.is_empty() {
+                continue;
+            }
+            let first_run_time: f64 = events.iter().map(|event| event.start_time).fold(f64::INFINITY, f64::min);
+            let finish_time: f64 = events.iter().map(|event| event.finish_time).fold(f64::NEG_INFINITY, f64::max);
+            // Time spent running by each thread (`P3.T0`, `P3.T1`...), or by the process if it has no threads.
+            let mut running_times: Vec<(&str, f64)> = Vec::new();
+            for event in events.iter() {
+                match running_times.iter_mut().find(|(name, _)| *name == event.name) {
+                    Some((_, running_time)) => *running_time += event.duration(),
+                    None => running_times.push((&event.name, event.duration())),
+                }
+            }
+            let running_time: f64 = running_times.iter().map(|&(_, running_time)| running_time).fold(0.0, f64::max);
+            let burst_time: f64 = if process.threads.is_empty() {
+                process.burst_time
+            } else {
+                process.threads.iter().map(|thread| thread.burst_time).fold(0.0, f64::max)
+            };
+            let turn_around_time: f64 = finish_time - process.arrival_time;
+            process_metrics.push(ProcessMetrics {
+                pid: process.pid,
+                arrival_time: process.arrival_time,
+                burst_time: process.burst_time,
+                first_run_time,
+                finish_time,
+                response_time: first_run_time - process.arrival_time,
+                waiting_time: turn_around_time - running_time,
+                turn_around_time,
+                normalized_turn_around_time: if burst_time > 0.0 { turn_around_time / burst_time } else { 1.0 },
+                slowdown: (turn_around_time / burst_time.max(SLOWDOWN_BOUND)).max(1.0),
+            });
+        }
+
+        // System metrics.
+        let start_time: f64 = process_metrics.iter().map(|process| process.arrival_time).fold(f64::INFINITY, f64::min);
+        let finish_time: f64 = event_list.iter().map(|event| event.finish_time).fold(f64::NEG_INFINITY, f64::max);
+        let (start_time, finish_time) = if process_metrics.is_empty() { (0.0, 0.0) } else { (start_time, finish_time) };
+        let elapsed_time: f64 = (finish_time - start_time) * number_of_cores as f64;
+        let busy_time: f64 = event_list
+            .iter()
+            .filter(|event| event.kind == EventKind::Process)
+            .map(|event| event.duration())
+            .fold(0.0, |total, time| total + time);
+        let context_switch_time: f64 = event_list
+            .iter()
+            .filter(|event| event.kind == EventKind::ContextSwitch)
+            .map(|event| event.duration())
+            .fold(0.0, |total, time| total + time);
+
+        let mut metrics: Metrics = Metrics {
+            number_of_cores,
+            start_time,
+            finish_time,
+            busy_time,
+            context_switch_time,
+            idle_time: (elapsed_time - busy_time - context_switch_time).max(0.0),
+            cpu_utilization: if elapsed_time > 0.0 { busy_time / elapsed_time } else { 0.0 },
+            throughput: if finish_time > start_time { process_metrics.len() as f64 / (finish_time - start_time) } else { 0.0 },
+            fairness_index: 1.0,
+            response_time: Distribution::new(&[]),
+            waiting_time: Distribution::new(&[]),
+            turn_around_time: Distribution::new(&[]),
+            normalized_turn_around_time: Distribution::new(&[]),
+            slowdown: Distribution::new(&[]),
+            processes: process_metrics,
+        };
+        metrics.summarize();
+        metrics
+    }
+
+    /// Leave the I/O time of the processes out of their waiting and turn around times,
+    /// e.g. to compare a schedule with I/O to the schedules of the same workload without it.
+    pub fn exclude_io_time(&mut self, processes: &[Process]) {
+        for process_metrics in self.processes.iter_mut() {
+            let Some(process) = processes.iter().find(|process| process.pid == process_metrics.pid) else {
+                continue;
+            };
+            let io_time: f64 = process.io_time();
+            process_metrics.waiting_time -= io_time;
+            process_metrics.turn_around_time -= io_time;
+            let burst_time: f64 = process_metrics.turn_around_time - process_metrics.waiting_time;
+            process_metrics.normalized_turn_around_time =
+                if burst_time > 0.0 { process_metrics.turn_around_time / burst_time } else { 1.0 };
+            process_metrics.slowdown = (process_metrics.turn_around_time / burst_time.max(SLOWDOWN_BOUND)).max(1.0);
+        }
+        self.summarize();
+    }
+
+    /// Compute the fairness index and the distributions from the per-process metrics.
+    fn summarize(&mut self) {
+        let values = |metric: fn(&ProcessMetrics) -> f64| -> Vec<f64> { self.processes.iter().map(metric).collect() };
+        let progress_rates: Vec<f64> = values(|process| {
+            if process.turn_around_time > 0.0 { process.burst_time / process.turn_around_time } else { 1.0 }
+        });
+        self.fairness_index = jain_fairness_index(&progress_rates);
+        self.response_time = Distribution::new(&values(|process| process.response_time));
+        self.waiting_time = Distribution::new(&values(|process| process.waiting_time));
+        self.turn_around_time = Distribution::new(&values(|process| process.turn_around_time));
+        self.normalized_turn_around_time = Distribution::new(&values(|process| process.normalized_turn_around_time));
+        self.slowdown = Distribution::new(&values(|process| process.slowdown));
+    }
+
+    /// Number of processes finishing in each window of `window` time units, starting at the first arrival.
+    /// The window must be at least one hundredth, the resolution of the schedulers.
+    pub fn throughput_over_time(&self, window: f64) -> Vec<(f64, usize)> {
+        if !window.is_finite() || window < 0.01 {
+            panic!("Throughput window must be a finite number of at least 0.01.");
+        }
+        // At least one window, then as many as needed to reach the finish time.
+        let number_of_windows: usize = (((self.finish_time - self.start_time) / window - 1e-9).ceil() as usize).max(1);
+        (0..number_of_windows)
+            .map(|index| {
+                let window_start: f64 = self.start_time + index as f64 * window;
+                let window_finish: f64 = window_start + window;
+                let count: usize = self
+                    .processes
+                    .iter()
+                    .filter(|process| process.finish_time > window_start && process.finish_time <= window_finish)
+                    .count();
+                (window_start, count)
+            })
+            .collect()
+    }
+
+    pub fn print(&self) {
+        println!("Name\t\tResponse Time\tWaiting Time\tTurn Around Time\tNormalized TAT\tSlowdown");
+        for process in self.processes.iter() {
+            println!(
+                "P{}\t\t{:.2}\t\t{:.2}\t\t{:.2}\t\t\t{:.2}\t\t{:.2}",
+                process.pid,
+                process.response_time,
+                process.waiting_time,
+                process.turn_around_time,
+                process.normalized_turn_around_time,
+                process.slowdown
+            );
+        }
+
+        println!("\nMetric\t\t\tMean\tStd Dev\tMin\tP50\tP90\tP99\tMax");
+        let distributions: [(&str, &Distribution); 5] = [
+            ("Response time\t", &self.response_time),
+            ("Waiting time\t", &self.waiting_time),
+            ("Turn around time", &self.turn_around_time),
+            ("Normalized TAT\t", &self.normalized_turn_around_time),
+            ("Slowdown\t", &self.slowdown),
+        ];
+        for (name, distribution) in distributions {
+            println!(
+                "{}\t{:.2}\t{:.2}\t{:.2}\t{:.2}\t{:.2}\t{:.2}\t{:.2}",
+                name,
+                distribution.mean,
+                distribution.standard_deviation,
+                distribution.min,
+                distribution.p50,
+                distribution.p90,
+                distribution.p99,
+                distribution.max
+            );
+        }
+
+        println!("\nCPU utilization: {:.2}%", self.cpu_utilization * 100.0);
+        println!("Busy time: {:.2}, idle time: {:.2}, context switch time: {:.2}", self.busy_time, self.idle_time, self.context_switch_time);
+        println!("Throughput: {:.3} processes per time unit", self.throughput);
+        println!("Jain's fairness index: {:.3}", self.fairness_index);
+    }
+}
+
+/// A single value picked from `Metrics`, used to compare and rank schedules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Metric {
+    AverageWaitingTime,
+    AverageTurnAroundTime,
+    AverageResponseTime,
+    AverageNormalizedTurnAroundTime,
+    P90TurnAroundTime,
+    MaxSlowdown,
+    CpuUtilization,
+    Throughput,
+    FairnessIndex,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 9] = [
+        Metric::AverageWaitingTime,
+        Metric::AverageTurnAroundTime,
+        Metric::AverageResponseTime,
+        Metric::AverageNormalizedTurnAroundTime,
+        Metric::P90TurnAroundTime,
+        Metric::MaxSlowdown,
+        Metric::CpuUtilization,
+        Metric::Throughput,
+        Metric::FairnessIndex,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Metric::AverageWaitingTime => "Average waiting time",
+            Metric::AverageTurnAroundTime => "Average turn around time",
+            Metric::AverageResponseTime => "Average response time",
+            Metric::AverageNormalizedTurnAroundTime => "Average normalized turn around time",
+            Metric::P90TurnAroundTime => "P90 turn around time",
+            Metric::MaxSlowdown => "Max slowdown",
+            Metric::CpuUtilization => "CPU utilization",
+            Metric::Throughput => "Throughput",
+            Metric::FairnessIndex => "Jain's fairness index",
+        }
+    }
+
+    /// Identifier, used for CSV headers.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Metric::AverageWaitingTime => "average_waiting_time",
+            Metric::AverageTurnAroundTime => "average_turn_around_time",
+            Metric::AverageResponseTime => "average_response_time",
+            Metric::AverageNormalizedTurnAroundTime => "average_normalized_turn_around_time",
+            Metric::P90TurnAroundTime => "p90_turn_around_time",
+            Metric::MaxSlowdown => "max_slowdown",
+            Metric::CpuUtilization => "cpu_utilization",
+            Metric::Throughput => "throughput",
+            Metric::FairnessIndex => "fairness_index",
+        }
+    }
+
+    /// Short name, used for column headers.
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            Metric::AverageWaitingTime => "WT",
+            Metric::AverageTurnAroundTime => "TAT",
+            Metric::AverageResponseTime => "RT",
+            Metric::AverageNormalizedTurnAroundTime => "NTAT",
+            Metric::P90TurnAroundTime => "P90 TAT",
+            Metric::MaxSlowdown => "Slowdown",
+            Metric::CpuUtilization => "Util",
+            Metric::Throughput => "Thr",
+            Metric::FairnessIndex => "Jain",
+        }
+    }
+
+    pub fn value(&self, metrics: &Metrics) -> f64 {
+        match self {
+            Metric::AverageWaitingTime => metrics.waiting_time.mean,
+            Metric::AverageTurnAroundTime => metrics.turn_around_time.mean,
+            Metric::AverageResponseTime => metrics.response_time.mean,
+            Metric::AverageNormalizedTurnAroundTime => metrics.normalized_turn_around_time.mean,
+            Metric::P90TurnAroundTime => metrics.turn_around_time.p90,
+            Metric::MaxSlowdown => metrics.slowdown.max,
+            Metric::CpuUtilization => metrics.cpu_utilization,
+            Metric::Throughput => metrics.throughput,
+            Metric::FairnessIndex => metrics.fairness_index,
+        }
+    }
+
+    /// Whether a higher value means a better schedule.
+    pub fn higher_is_better(&self) -> bool {
+        matches!(self, Metric::CpuUtilization | Metric::Throughput | Metric::FairnessIndex)
+    }
+}
+
+// Test the metrics of a small FCFS schedule with idle time.
+#[test]
+fn test() {
+    use crate::cpu_schedulers::nonpreemptive::NonpreemptiveScheduler;
+    use crate::cpu_schedulers::gang::GangScheduler;
+
+    let mut scheduler: NonpreemptiveScheduler = NonpreemptiveScheduler::new(vec![
+        Process::new(1, 0.0, 4.0),
+        Process::new(2, 1.0, 2.0),
+        Process::new(3, 8.0, 2.0),
+    ]);
+    scheduler.quiet = true;
+    scheduler.fcfs();
+
+    let metrics: Metrics = Metrics::new(&scheduler.finished_processes, &scheduler.event_list, 1);
+    let response_times: Vec<f64> = metrics.processes.iter().map(|process| process.response_time).collect();
+    assert_eq!(response_times, vec![0.0, 3.0, 0.0]);
+    assert_eq!(metrics.idle_time, 2.0);
+    assert_eq!(metrics.cpu_utilization, 0.8);
+    assert_eq!(metrics.waiting_time.p50, 0.0);
+    assert_eq!(metrics.waiting_time.p99, 3.0);
+    assert_eq!(metrics.throughput_over_time(5.0), vec![(0.0, 1), (5.0, 2)]);
+    for window in [0.0, 1e-17, 0.001, f64::NAN, f64::INFINITY] {
+        assert!(std::panic::catch_unwind(|| metrics.throughput_over_time(window)).is_err(), "{}", window);
+    }
+    assert_eq!(metrics.throughput_over_time(0.01).len(), 1000);
+    assert_eq!(jain_fairness_index(&[1.0, 1.0, 1.0]), 1.0);
+
+    // The two threads of P1 run side by side: they do not add up to a negative waiting time.
+    let mut scheduler: GangScheduler = GangScheduler::new(vec![
+        Process::new_with_threads(1, 0.0, vec![2.0, 2.0]),
+        Process::new(2, 0.0, 1.0),
+    ], 2);
+    scheduler.quiet = true;
+    scheduler.gang(1.0);
+    let metrics: Metrics = Metrics::new(&scheduler.finished_processes, &scheduler.event_list, 2);
+    let waiting_times: Vec<(u32, f64)> = metrics.processes.iter().map(|process| (process.pid, process.waiting_time)).collect();
+    assert_eq!(waiting_times, vec![(2, 1.0), (1, 1.0)]);
+    assert_eq!(metrics.processes[1].normalized_turn_around_time, 1.5);
+}
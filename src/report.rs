@@ -0,0 +1,300 @@
+use crate::cpu_schedulers::{Event, EventKind, number_of_context_switches};
+use crate::gantt::{GanttChart, SvgGanttChart, PALETTE, color_key, escape, format_time};
+use crate::schedule::ScheduleResult;
+
+/// Headers of the process table.
+const HEADERS: [&str; 9] = ["Process", "Arrival", "Burst", "Priority", "Start", "Finish", "Turn around", "Waiting", "Response"];
+
+/// Width of the TikZ Gantt chart, in centimeters.
+const LATEX_WIDTH: f64 = 14.0;
+
+/// Cells of the process table, one row per process.
+fn rows(result: &ScheduleResult) -> Vec<Vec<String>> {
+    result
+        .metrics
+        .processes
+        .iter()
+        .map(|process| {
+            let priority: u32 = result
+                .finished_processes
+                .iter()
+                .find(|finished_process| finished_process.pid == process.pid)
+                .map_or(0, |finished_process| finished_process.priority);
+            vec![
+                format!("P{}", process.pid),
+                format!("{:.2}", process.arrival_time),
+                format!("{:.2}", process.burst_time),
+                priority.to_string(),
+                format!("{:.2}", process.first_run_time),
+                format!("{:.2}", process.finish_time),
+                format!("{:.2}", process.turn_around_time),
+                format!("{:.2}", process.waiting_time),
+                format!("{:.2}", process.response_time),
+            ]
+        })
+        .collect()
+}
+
+/// Cells of the average row (empty for the columns that are not averaged).
+fn averages(result: &ScheduleResult) -> Vec<String> {
+    let mut cells: Vec<String> = vec![String::new(); HEADERS.len()];
+    cells[0] = String::from("Average");
+    cells[6] = format!("{:.2}", result.metrics.turn_around_time.mean);
+    cells[7] = format!("{:.2}", result.metrics.waiting_time.mean);
+    cells[8] = format!("{:.2}", result.metrics.response_time.mean);
+    cells
+}
+
+/// One sentence with the CPU utilization, throughput and context switches.
+fn summary(result: &ScheduleResult) -> String {
+    let mut summary: String = format!(
+        "CPU utilization: {:.2}%, throughput: {:.2} processes per time unit",
+        result.metrics.cpu_utilization * 100.0,
+        result.metrics.throughput
+    );
+    let context_switches: usize = number_of_context_switches(&result.event_list);
+    if context_switches > 0 {
+        summary += &format!(
+            ", context switches: {} ({:.2} time units)",
+            context_switches, result.metrics.context_switch_time
+        );
+    }
+    summary + "."
+}
+
+/// A Markdown page: the process table, the averages, a summary and a text Gantt chart.
+pub fn write_markdown(result: &ScheduleResult) -> String {
+    let line = |cells: &[String]| format!("| {} |\n", cells.join(" | "));
+    let mut markdown: String = format!("## {}\n\n", result.algorithm);
+    markdown += &line(&HEADERS.map(String::from));
+    // The process column is left aligned, the numbers right aligned.
+    let separator: String = (0..HEADERS.len()).map(|column| if column == 0 { "---|" } else { "---:|" }).collect();
+    markdown += &format!("|{}\n", separator);
+    for row in rows(result) {
+        markdown += &line(&row);
+    }
+    let averages: Vec<String> = averages(result)
+        .into_iter()
+        .map(|cell| if cell.is_empty() { cell } else { format!("**{}**", cell) })
+        .collect();
+    markdown += &line(&averages);
+    markdown += &format!("\n{}\n\n", summary(result));
+    markdown += &format!("```text\n{}```\n", GanttChart::new().set_width(80).render(&result.event_list));
+    markdown
+}
+
+/// Plain text for a terminal: the process table with aligned columns, a summary and a text Gantt chart.
+pub fn write_text(result: &ScheduleResult, width: usize) -> String {
+    let mut table: Vec<Vec<String>> = vec![HEADERS.map(String::from).to_vec()];
+    table.extend(rows(result));
+    table.push(averages(result));
+    let widths: Vec<usize> = (0..HEADERS.len())
+        .map(|column| table.iter().map(|row| row[column].len()).max().unwrap_or(0))
+        .collect();
+    let mut text: String = format!("{}\n\n", result.algorithm);
+    for row in table.iter() {
+        // The process column is left aligned, the numbers right aligned.
+        let cells: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(column, cell)| if column == 0 { format!("{:<w$}", cell, w = widths[column]) } else { format!("{:>w$}", cell, w = widths[column]) })
+            .collect();
+        text += &format!("{}\n", cells.join("  ").trim_end());
+    }
+    text += &format!("\n{}\n\n", summary(result));
+    text += &GanttChart::new().set_width(width).render(&result.event_list);
+    text
+}
+
+/// A self-contained HTML page: the process table, a summary and an SVG Gantt chart.
+pub fn write_html(result: &ScheduleResult) -> String {
+    let title: String = escape(&result.algorithm);
+    let mut html: String = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html += &format!("<title>{}</title>\n", title);
+    html += "<style>\n\
+             body { font-family: sans-serif; margin: 2em; }\n\
+             table { border-collapse: collapse; margin-bottom: 1em; }\n\
+             th, td { border: 1px solid #cccccc; padding: 4px 10px; text-align: right; }\n\
+             th:first-child, td:first-child { text-align: left; }\n\
+             th { background: #f0f0f0; }\n\
+             tr.average td { font-weight: bold; }\n\
+             </style>\n</head>\n<body>\n";
+    html += &format!("<h1>{}</h1>\n<table>\n<thead>\n<tr>", title);
+    for header in HEADERS.iter() {
+        html += &format!("<th>{}</th>", header);
+    }
+    html += "</tr>\n</thead>\n<tbody>\n";
+    for row in rows(result) {
+        html += &format!("<tr>{}</tr>\n", row.iter().map(|cell| format!("<td>{}</td>", escape(cell))).collect::<String>());
+    }
+    html += &format!(
+        "<tr class=\"average\">{}</tr>\n</tbody>\n</table>\n",
+        averages(result).iter().map(|cell| format!("<td>{}</td>", cell)).collect::<String>()
+    );
+    html += &format!("<p>{}</p>\n", summary(result));
+    html += &SvgGanttChart::new().render(&result.event_list);
+    html += "</body>\n</html>\n";
+    html
+}
+
+/// Escape the characters that are special in LaTeX.
+fn escape_latex(text: &str) -> String {
+    let mut escaped: String = String::new();
+    for character in text.chars() {
+        match character {
+            '\\' => escaped += "\\textbackslash{}",
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            '~' => escaped += "\\textasciitilde{}",
+            '^' => escaped += "\\textasciicircum{}",
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// A LaTeX fragment: the process table as a `tabular` and the Gantt chart as a TikZ picture.
+/// The document needs `\usepackage{tikz}` and `\usetikzlibrary{patterns}`.
+pub fn write_latex(result: &ScheduleResult) -> String {
+    let line = |cells: &[String]| format!("{} \\\\\n", cells.join(" & "));
+    let mut latex: String = format!("% {}\n\\begin{{tabular}}{{l{}}}\n\\hline\n", escape_latex(&result.algorithm), "r".repeat(HEADERS.len() - 1));
+    latex += &line(&HEADERS.map(String::from));
+    latex += "\\hline\n";
+    for row in rows(result) {
+        latex += &line(&row);
+    }
+    latex += "\\hline\n";
+    let averages: Vec<String> = averages(result)
+        .into_iter()
+        .map(|cell| if cell.is_empty() { cell } else { format!("\\textbf{{{}}}", cell) })
+        .collect();
+    latex += &line(&averages);
+    latex += "\\hline\n\\end{tabular}\n\n";
+    latex += &format!("{}\n\n", escape_latex(&summary(result)));
+    latex += &tikz_gantt_chart(&result.event_list);
+    latex
+}
+
+/// Gantt chart as a TikZ picture: one row per core, idle time hatched, context switches in gray.
+fn tikz_gantt_chart(event_list: &[Event]) -> String {
+    let finish_time: f64 = event_list.iter().map(|event| event.finish_time).fold(0.0, f64::max);
+    if finish_time <= 0.0 {
+        return String::new();
+    }
+    let scale: f64 = LATEX_WIDTH / finish_time;
+    let mut cores: Vec<usize> = event_list.iter().map(|event| event.core).collect();
+    cores.sort_unstable();
+    cores.dedup();
+
+    let mut tikz: String = String::new();
+    // One color per process, from the palette of the SVG charts.
+    let mut colors: Vec<usize> = event_list
+        .iter()
+        .filter(|event| event.kind == EventKind::Process)
+        .map(|event| color_key(event) as usize % PALETTE.len())
+        .collect();
+    colors.sort_unstable();
+    colors.dedup();
+    for color in colors.iter() {
+        tikz += &format!("\\definecolor{{gantt{}}}{{HTML}}{{{}}}\n", color, PALETTE[*color].trim_start_matches('#').to_uppercase());
+    }
+    tikz += &format!("\\begin{{tikzpicture}}[x={:.4}cm, y=1cm]\n", scale);
+
+    let mut boundaries: Vec<f64> = vec![0.0];
+    for (row, core) in cores.iter().enumerate() {
+        let y: f64 = 0.0 - row as f64;
+        if cores.len() > 1 {
+            tikz += &format!("\\node[anchor=east] at (0, {:.2}) {{Core {}}};\n", y + 0.3, core);
+        }
+        let mut events: Vec<&Event> = event_list.iter().filter(|event| event.core == *core).collect();
+        events.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+        let mut time: f64 = 0.0;
+        for event in events {
+            if event.start_time > time + 1e-6 {
+                tikz += &format!(
+                    "\\draw[pattern=north east lines, pattern color=gray] ({}, {:.2}) rectangle ({}, {:.2});\n",
+                    format_time(time), y, format_time(event.start_time), y + 0.6
+                );
+            }
+            let style: String = match event.kind {
+                EventKind::Process => format!("fill=gantt{}", color_key(event) as usize % PALETTE.len()),
+                EventKind::ContextSwitch => String::from("fill=gray!40"),
+            };
+            tikz += &format!(
+                "\\draw[{}] ({}, {:.2}) rectangle ({}, {:.2});\n",
+                style, format_time(event.start_time), y, format_time(event.finish_time), y + 0.6
+            );
+            // The name if it fits (about 0.25 cm per character).
+            let width: f64 = event.duration() * scale;
+            if event.kind == EventKind::Process && width >= event.name.len() as f64 * 0.25 {
+                tikz += &format!(
+                    "\\node at ({}, {:.2}) {{\\small {}}};\n",
+                    format_time((event.start_time + event.finish_time) / 2.0), y + 0.3, escape_latex(&event.name)
+                );
+            }
+            boundaries.extend([event.start_time, event.finish_time]);
+            time = time.max(event.finish_time);
+        }
+    }
+
+    // Time axis with a label under every boundary that does not overlap the previous one.
+    let bottom: f64 = 1.0 - cores.len() as f64;
+    boundaries.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    boundaries.dedup_by(|a, b| (*a - *b).abs() < 1e-6);
+    let mut last_label: Option<f64> = None;
+    for boundary in boundaries {
+        if last_label.is_none_or(|last| (boundary - last) * scale >= 0.5) {
+            tikz += &format!("\\node[below] at ({}, {:.2}) {{\\scriptsize {}}};\n", format_time(boundary), bottom, format_time(boundary));
+            last_label = Some(boundary);
+        }
+    }
+    tikz += "\\end{tikzpicture}\n";
+    tikz
+}
+
+// Test the three formats on a schedule with idle time and context switches.
+#[test]
+fn test() {
+    use crate::cpu_schedulers::{Process, ContextSwitch};
+    use crate::schedule::Algorithm;
+
+    let processes: Vec<Process> = vec![
+        Process::new_with_priority(1, 0.0, 3.0, 2),
+        Process::new_with_priority(2, 1.0, 2.0, 1),
+        Process::new(3, 8.0, 1.0),
+    ];
+    let result: ScheduleResult = Algorithm::Fcfs.run(&processes, ContextSwitch::Fixed(0.5));
+
+    let markdown: String = write_markdown(&result);
+    let lines: Vec<&str> = markdown.lines().collect();
+    assert_eq!(lines[0], "## FCFS");
+    assert_eq!(lines[2], "| Process | Arrival | Burst | Priority | Start | Finish | Turn around | Waiting | Response |");
+    assert_eq!(lines[3], "|---|---:|---:|---:|---:|---:|---:|---:|---:|");
+    assert_eq!(lines[5], "| P2 | 1.00 | 2.00 | 1 | 4.00 | 6.00 | 5.00 | 3.00 | 3.00 |");
+    assert_eq!(lines[7], "| **Average** |  |  |  |  |  | **3.33** | **1.33** | **1.33** |");
+    assert!(lines[9].starts_with("CPU utilization: ") && lines[9].contains("context switches: 3 (1.50 time units)."));
+    assert!(markdown.contains("```text\n┌") && markdown.contains("░"));
+
+    let text: String = write_text(&result, 60);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[2], "Process  Arrival  Burst  Priority  Start  Finish  Turn around  Waiting  Response");
+    assert_eq!(lines[4], "P2          1.00   2.00         1   4.00    6.00         5.00     3.00      3.00");
+    assert_eq!(lines[6], "Average                                                  3.33     1.33      1.33");
+    assert!(text.contains("context switches: 3") && text.contains("┌"));
+
+    let html: String = write_html(&result);
+    assert!(html.starts_with("<!DOCTYPE html>") && html.ends_with("</html>\n"));
+    assert!(html.contains("<tr><td>P2</td><td>1.00</td>") && html.contains("<tr class=\"average\"><td>Average</td>"));
+    assert!(html.contains("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+
+    let latex: String = write_latex(&result);
+    assert!(latex.contains("\\begin{tabular}{lrrrrrrrr}"));
+    assert!(latex.contains("P2 & 1.00 & 2.00 & 1 & 4.00 & 6.00 & 5.00 & 3.00 & 3.00 \\\\\n"));
+    assert!(latex.contains("\\textbf{Average} &  &  &  &  &  & \\textbf{3.33}"));
+    assert!(latex.contains("\\draw[pattern=north east lines, pattern color=gray] (6, 0.00) rectangle (8, 0.60);"));
+    assert!(latex.contains("\\draw[fill=gray!40] (8, 0.00) rectangle (8.5, 0.60);"));
+    assert!(latex.contains("\\definecolor{gantt1}{HTML}{F28E2B}") && latex.ends_with("\\end{tikzpicture}\n"));
+    assert_eq!(escape_latex("a_b & 50%"), "a\\_b \\& 50\\%");
+}
@@ -0,0 +1,257 @@
+use std::str::FromStr;
+
+use crate::cpu_schedulers::{Process, Event, Burst, ContextSwitch, more_than_two_decimal_places};
+use crate::cpu_schedulers::nonpreemptive::NonpreemptiveScheduler;
+use crate::cpu_schedulers::preemptive::PreemptiveScheduler;
+use crate::cpu_schedulers::io::{IoScheduler, CpuPolicy};
+use crate::cpu_schedulers::prediction::ExponentialAverage;
+use crate::metrics::Metrics;
+
+/// A single-CPU scheduling algorithm with its parameters.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "name", rename_all = "snake_case"))]
+pub enum Algorithm {
+    Fcfs,
+    Sjf,
+    /// Nonpreemptive priority scheduling.
+    Priority,
+    /// Nonpreemptive priority scheduling where waiting processes gain `aging_rate` priority levels per time unit.
+    PriorityWithAging { aging_rate: f64 },
+    Rr { time_quantum: f64 },
+    Srtf,
+    /// Preemptive priority scheduling.
+    PreemptivePriority,
+    /// SJF on the bursts predicted by exponential averaging of the burst history of each process.
+    PredictiveSjf { alpha: f64, initial_guess: f64 },
+    /// SRTF on the remaining times predicted by exponential averaging of the burst history of each process.
+    PredictiveSrtf { alpha: f64, initial_guess: f64 },
+    /// Multilevel Feedback Queue, run by the `IoScheduler` (context switches are not modelled there).
+    Mlfq { time_quanta: Vec<f64> },
+}
+
+impl Algorithm {
+    /// Every algorithm, with the given RR time quantum, MLFQ time quanta, aging rate
+    /// and exponential average (α and initial guess) of the predictive algorithms.
+    pub fn all(time_quantum: f64, time_quanta: Vec<f64>, aging_rate: f64, alpha: f64, initial_guess: f64) -> Vec<Algorithm> {
+        vec![
+            Algorithm::Fcfs,
+            Algorithm::Sjf,
+            Algorithm::Priority,
+            Algorithm::PriorityWithAging { aging_rate },
+            Algorithm::Rr { time_quantum },
+            Algorithm::Srtf,
+            Algorithm::PreemptivePriority,
+            Algorithm::PredictiveSjf { alpha, initial_guess },
+            Algorithm::PredictiveSrtf { alpha, initial_guess },
+            Algorithm::Mlfq { time_quanta },
+        ]
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Algorithm::Fcfs => String::from("FCFS"),
+            Algorithm::Sjf => String::from("SJF"),
+            Algorithm::Priority => String::from("PS"),
+            Algorithm::PriorityWithAging { aging_rate } => format!("PS (aging={:.2})", aging_rate),
+            Algorithm::Rr { time_quantum } => format!("RR (q={:.2})", time_quantum),
+            Algorithm::Srtf => String::from("SRTF"),
+            Algorithm::PreemptivePriority => String::from("PPS"),
+            Algorithm::PredictiveSjf { alpha, initial_guess } => format!("Predictive SJF (alpha={:.2}, guess={:.2})", alpha, initial_guess),
+            Algorithm::PredictiveSrtf { alpha, initial_guess } => format!("Predictive SRTF (alpha={:.2}, guess={:.2})", alpha, initial_guess),
+            Algorithm::Mlfq { time_quanta } => format!("MLFQ ({} levels)", time_quanta.len()),
+        }
+    }
+
+    /// Check the parameters the schedulers would panic on.
+    pub fn check(&self) -> Result<(), String> {
+        let valid_time_quantum = |time_quantum: f64| time_quantum > 0.0 && !more_than_two_decimal_places(time_quantum);
+        match self {
+            Algorithm::Rr { time_quantum } if !valid_time_quantum(*time_quantum) => Err(format!("Invalid time quantum {}.", time_quantum)),
+            Algorithm::Mlfq { time_quanta } if time_quanta.is_empty() || !time_quanta.iter().all(|time_quantum| valid_time_quantum(*time_quantum)) => {
+                Err(String::from("Invalid MLFQ time quanta."))
+            }
+            Algorithm::PriorityWithAging { aging_rate } if !aging_rate.is_finite() || *aging_rate < 0.0 => Err(format!("Invalid aging rate {}.", aging_rate)),
+            Algorithm::PredictiveSjf { alpha, initial_guess } | Algorithm::PredictiveSrtf { alpha, initial_guess }
+                if !(0.0..=1.0).contains(alpha) || !initial_guess.is_finite() || *initial_guess < 0.0 =>
+            {
+                Err(format!("Invalid exponential average (alpha={}, initial guess={}).", alpha, initial_guess))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that the algorithm can run with the context switch: MLFQ, run by the `IoScheduler`, does not model context switches.
+    pub fn check_context_switch(&self, context_switch: ContextSwitch) -> Result<(), String> {
+        let costs_something: bool = context_switch.cost(true) > 0.0 || context_switch.cost(false) > 0.0;
+        match self {
+            Algorithm::Mlfq { .. } if costs_something => Err(String::from("MLFQ does not model context switches.")),
+            _ => Ok(()),
+        }
+    }
+
+    /// The processes as the algorithm runs them: MLFQ keeps their I/O bursts,
+    /// the other algorithms get a single CPU burst of their total CPU time.
+    pub fn workload(&self, processes: &[Process]) -> Vec<Process> {
+        match self {
+            Algorithm::Mlfq { .. } => processes.to_vec(),
+            _ => processes
+                .iter()
+                .map(|process| {
+                    let mut process: Process = process.clone();
+                    process.bursts.clear();
+                    process
+                })
+                .collect(),
+        }
+    }
+
+    /// Run the algorithm on a copy of the processes, without printing.
+    /// Only MLFQ runs the I/O bursts of the processes (on as many devices as they use); the other algorithms
+    /// see a process with I/O as a single CPU burst of its total CPU time, its I/O bursts are dropped.
+    /// The metrics of MLFQ leave the I/O time out of the waiting and turn around times, so that every algorithm
+    /// is ranked on the same numbers.
+    pub fn run(&self, processes: &[Process], context_switch: ContextSwitch) -> ScheduleResult {
+        if let Err(error) = self.check_context_switch(context_switch) {
+            panic!("{}", error);
+        }
+        let processes: Vec<Process> = self.workload(processes);
+        let (finished_processes, event_list): (Vec<Process>, Vec<Event>) = match self {
+            Algorithm::Fcfs | Algorithm::Sjf | Algorithm::Priority | Algorithm::PriorityWithAging { .. } | Algorithm::PredictiveSjf { .. } => {
+                let mut scheduler: NonpreemptiveScheduler = NonpreemptiveScheduler::new(processes.clone());
+                scheduler.set_context_switch(context_switch);
+                scheduler.quiet = true;
+                match self {
+                    Algorithm::Fcfs => scheduler.fcfs(),
+                    Algorithm::Sjf => scheduler.sjf(),
+                    Algorithm::PriorityWithAging { aging_rate } => scheduler.ps_with_aging(*aging_rate),
+                    Algorithm::PredictiveSjf { alpha, initial_guess } => {
+                        scheduler.predictive_sjf(&ExponentialAverage::new(*alpha, *initial_guess));
+                    }
+                    _ => scheduler.ps(),
+                }
+                (scheduler.finished_processes, scheduler.event_list)
+            }
+            Algorithm::Rr { .. } | Algorithm::Srtf | Algorithm::PreemptivePriority | Algorithm::PredictiveSrtf { .. } => {
+                let mut scheduler: PreemptiveScheduler = PreemptiveScheduler::new(processes.clone());
+                scheduler.set_context_switch(context_switch);
+                scheduler.quiet = true;
+                match self {
+                    Algorithm::Rr { time_quantum } => scheduler.rr(*time_quantum),
+                    Algorithm::Srtf => scheduler.srtf(),
+                    Algorithm::PredictiveSrtf { alpha, initial_guess } => {
+                        scheduler.predictive_srtf(&ExponentialAverage::new(*alpha, *initial_guess));
+                    }
+                    _ => scheduler.ps(),
+                }
+                (scheduler.finished_processes, scheduler.event_list)
+            }
+            Algorithm::Mlfq { time_quanta } => {
+                let number_of_devices: usize = processes
+                    .iter()
+                    .flat_map(|process| process.bursts.iter())
+                    .filter_map(|burst| match burst {
+                        Burst::Io { device, .. } => Some(device + 1),
+                        Burst::Cpu(_) => None,
+                    })
+                    .max()
+                    .unwrap_or(0);
+                let devices: Vec<String> = (0..number_of_devices).map(|device| format!("Device {}", device)).collect();
+                let mut scheduler: IoScheduler = IoScheduler::new(processes.clone(), devices);
+                scheduler.quiet = true;
+                scheduler.run(CpuPolicy::Mlfq { time_quanta: time_quanta.clone() });
+                (scheduler.finished_processes, scheduler.event_list)
+            }
+        };
+        let mut result: ScheduleResult = ScheduleResult::new(self.name(), finished_processes, event_list, 1);
+        if let Algorithm::Mlfq { .. } = self {
+            result.metrics.exclude_io_time(&processes);
+        }
+        result
+    }
+}
+
+/// Parse an algorithm and its parameters: `fcfs`, `sjf`, `ps`, `ps_aging <rate>`, `rr <quantum>`, `srtf`, `pps`,
+/// `psjf <alpha> <initial guess>`, `psrtf <alpha> <initial guess>`, `mlfq <quantum> <quantum> ...`.
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Algorithm, String> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let parameters: Vec<f64> = words
+            .iter()
+            .skip(1)
+            .map(|word| word.parse::<f64>().map_err(|_| format!("Invalid parameter `{}` in `{}`.", word, text)))
+            .collect::<Result<Vec<f64>, String>>()?;
+        let expect = |count: usize| -> Result<(), String> {
+            if parameters.len() == count { Ok(()) } else { Err(format!("`{}` expects {} parameter(s).", words[0], count)) }
+        };
+        match words.first().map(|word| word.to_lowercase()).as_deref() {
+            Some("fcfs") => expect(0).map(|_| Algorithm::Fcfs),
+            Some("sjf") => expect(0).map(|_| Algorithm::Sjf),
+            Some("ps") => expect(0).map(|_| Algorithm::Priority),
+            Some("ps_aging") => expect(1).map(|_| Algorithm::PriorityWithAging { aging_rate: parameters[0] }),
+            Some("rr") => expect(1).map(|_| Algorithm::Rr { time_quantum: parameters[0] }),
+            Some("srtf") => expect(0).map(|_| Algorithm::Srtf),
+            Some("pps") => expect(0).map(|_| Algorithm::PreemptivePriority),
+            Some("psjf") => expect(2).map(|_| Algorithm::PredictiveSjf { alpha: parameters[0], initial_guess: parameters[1] }),
+            Some("psrtf") => expect(2).map(|_| Algorithm::PredictiveSrtf { alpha: parameters[0], initial_guess: parameters[1] }),
+            Some("mlfq") if !parameters.is_empty() => Ok(Algorithm::Mlfq { time_quanta: parameters }),
+            Some("mlfq") => Err(String::from("`mlfq` expects at least one time quantum.")),
+            Some(name) => Err(format!("Unknown algorithm `{}`.", name)),
+            None => Err(String::from("Missing algorithm.")),
+        }
+    }
+}
+
+/// The outcome of one scheduler run: the finished processes, the timeline and the metrics derived from it.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScheduleResult {
+    pub algorithm: String,
+    pub finished_processes: Vec<Process>,
+    pub event_list: Vec<Event>,
+    pub number_of_cores: usize,
+    pub metrics: Metrics,
+}
+
+impl ScheduleResult {
+    pub fn new(algorithm: String, mut finished_processes: Vec<Process>, event_list: Vec<Event>, number_of_cores: usize) -> ScheduleResult {
+        finished_processes.sort_by_key(|process| process.pid);
+        let metrics: Metrics = Metrics::new(&finished_processes, &event_list, number_of_cores);
+        ScheduleResult { algorithm, finished_processes, event_list, number_of_cores, metrics }
+    }
+}
+
+// Test a workload with I/O: MLFQ runs it on the devices it uses, the other algorithms only see its CPU time.
+#[test]
+fn test() {
+    let processes: Vec<Process> = vec![
+        Process::new_with_bursts(1, 0.0, vec![Burst::Cpu(1.0), Burst::Io { device: 1, time: 2.0 }, Burst::Cpu(1.0)]),
+        Process::new(2, 0.0, 2.0),
+    ];
+    let result: ScheduleResult = Algorithm::Mlfq { time_quanta: vec![4.0] }.run(&processes, ContextSwitch::None);
+    let finish_times: Vec<(u32, f64)> = result.finished_processes.iter().map(|process| (process.pid, process.finish_time)).collect();
+    assert_eq!(finish_times, vec![(1, 4.0), (2, 3.0)]);
+    // The I/O time of P1 is left out of its metrics, as for the other algorithms.
+    assert_eq!(result.metrics.processes[0].turn_around_time, 2.0);
+    assert_eq!(result.metrics.processes[0].waiting_time, result.finished_processes[0].waiting_time);
+
+    let result: ScheduleResult = Algorithm::Fcfs.run(&processes, ContextSwitch::None);
+    let finish_times: Vec<(u32, f64)> = result.finished_processes.iter().map(|process| (process.pid, process.finish_time)).collect();
+    assert_eq!(finish_times, vec![(1, 2.0), (2, 4.0)]);
+    assert!(result.finished_processes[0].bursts.is_empty());
+
+    let mlfq: Algorithm = Algorithm::Mlfq { time_quanta: vec![4.0] };
+    assert!(mlfq.check_context_switch(ContextSwitch::PerSwitch(0.0)).is_ok());
+    assert!(mlfq.check_context_switch(ContextSwitch::Fixed(0.5)).is_err());
+    assert!(Algorithm::Fcfs.check_context_switch(ContextSwitch::Fixed(0.5)).is_ok());
+
+    assert!(Algorithm::Mlfq { time_quanta: vec![] }.check().is_err());
+    for time_quantum in [0.0, 0.125, f64::NAN, f64::INFINITY] {
+        assert!(Algorithm::Rr { time_quantum }.check().is_err());
+    }
+    for aging_rate in [-1.0, f64::NAN, f64::INFINITY] {
+        assert!(Algorithm::PriorityWithAging { aging_rate }.check().is_err());
+    }
+}
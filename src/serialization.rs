@@ -0,0 +1,421 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cpu_schedulers::{Process, Burst, ContextSwitch, more_than_two_decimal_places};
+use crate::schedule::{Algorithm, ScheduleResult};
+
+/// Version of the file schema written by this crate.
+/// Bump it when the schema changes, and teach `migrate` to upgrade the files of the previous version.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Affinity masks are written as hex strings (`"0xff"`), since TOML integers stop at `i64::MAX`.
+/// Plain integers are accepted too.
+pub(crate) mod mask {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Mask {
+        Integer(u64),
+        Text(String),
+    }
+
+    pub fn serialize<S: Serializer>(mask: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:#x}", mask))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        match Mask::deserialize(deserializer)? {
+            Mask::Integer(mask) => Ok(mask),
+            Mask::Text(text) => {
+                let parsed = match text.get(..2) {
+                    Some("0x" | "0X") => u64::from_str_radix(&text[2..], 16),
+                    Some("0b" | "0B") => u64::from_str_radix(&text[2..], 2),
+                    _ => text.parse(),
+                };
+                parsed.map_err(|_| serde::de::Error::custom(format!("invalid affinity mask `{}`", text)))
+            }
+        }
+    }
+}
+
+mod optional_mask {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    struct Mask(#[serde(with = "super::mask")] u64);
+
+    pub fn serialize<S: Serializer>(mask: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error> {
+        match mask {
+            Some(mask) => super::mask::serialize(mask, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+        Ok(Option::<Mask>::deserialize(deserializer)?.map(|Mask(mask)| mask))
+    }
+}
+
+/// A process as written in a workload file: only the inputs, the optional ones can be left out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessSpec {
+    pub pid: u32,
+    pub arrival_time: f64,
+    /// Ignored when `bursts` or `threads` are given (the burst time is then their total CPU time).
+    #[serde(default)]
+    pub burst_time: f64,
+    #[serde(default)]
+    pub priority: u32,
+    /// Alternating CPU and I/O bursts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bursts: Vec<Burst>,
+    /// Burst time of every thread.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub threads: Vec<f64>,
+    /// Previous CPU bursts, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub burst_history: Vec<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "optional_mask")]
+    pub hard_affinity: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "optional_mask")]
+    pub soft_affinity: Option<u64>,
+}
+
+impl From<&Process> for ProcessSpec {
+    fn from(process: &Process) -> ProcessSpec {
+        ProcessSpec {
+            pid: process.pid,
+            arrival_time: process.arrival_time,
+            burst_time: process.burst_time,
+            priority: process.priority,
+            bursts: process.bursts.clone(),
+            threads: process.threads.iter().map(|thread| thread.burst_time).collect(),
+            burst_history: process.burst_history.clone(),
+            hard_affinity: Some(process.hard_affinity).filter(|&mask| mask != u64::MAX),
+            soft_affinity: Some(process.soft_affinity).filter(|&mask| mask != 0),
+        }
+    }
+}
+
+impl ProcessSpec {
+    /// Build the process, with an error instead of the panics of the `Process` constructors.
+    pub fn to_process(&self) -> Result<Process, String> {
+        let valid = |time: f64| time >= 0.0 && time.is_finite() && !more_than_two_decimal_places(time);
+        let times: Vec<f64> = [self.arrival_time, self.burst_time]
+            .into_iter()
+            .chain(self.bursts.iter().map(|burst| burst.time()))
+            .chain(self.threads.iter().copied())
+            .collect();
+        if !times.into_iter().all(valid) {
+            return Err(format!("P{}: times must be non negative with at most two decimal places", self.pid));
+        }
+        let mut process: Process = if !self.bursts.is_empty() {
+            if !Burst::is_valid_sequence(&self.bursts) {
+                return Err(format!("P{}: a burst sequence must alternate CPU and I/O bursts, starting and ending with a CPU burst", self.pid));
+            }
+            Process::new_with_bursts(self.pid, self.arrival_time, self.bursts.clone())
+        } else if !self.threads.is_empty() {
+            Process::new_with_threads(self.pid, self.arrival_time, self.threads.clone())
+        } else {
+            Process::new(self.pid, self.arrival_time, self.burst_time)
+        };
+        process.priority = self.priority;
+        if !self.burst_history.iter().all(|&burst_time| burst_time.is_finite() && burst_time >= 0.0) {
+            return Err(format!("P{}: the burst history must only contain non negative times", self.pid));
+        }
+        process.set_burst_history(self.burst_history.clone());
+        if self.hard_affinity.is_some() || self.soft_affinity.is_some() {
+            let hard_affinity: u64 = self.hard_affinity.unwrap_or(u64::MAX);
+            if hard_affinity == 0 {
+                return Err(format!("P{}: the hard affinity must allow at least one core", self.pid));
+            }
+            process.set_affinity(hard_affinity, self.soft_affinity.unwrap_or(0));
+        }
+        Ok(process)
+    }
+}
+
+/// A list of processes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Workload {
+    pub processes: Vec<ProcessSpec>,
+}
+
+impl Workload {
+    pub fn new(processes: &[Process]) -> Workload {
+        Workload { processes: processes.iter().map(ProcessSpec::from).collect() }
+    }
+
+    /// Build the processes, rejecting duplicate pids as the schedulers key their queues by pid.
+    pub fn to_processes(&self) -> Result<Vec<Process>, String> {
+        let mut processes: Vec<Process> = Vec::with_capacity(self.processes.len());
+        for spec in self.processes.iter() {
+            if processes.iter().any(|process| process.pid == spec.pid) {
+                return Err(format!("P{}: duplicate pid", spec.pid));
+            }
+            processes.push(spec.to_process()?);
+        }
+        Ok(processes)
+    }
+}
+
+/// A scheduler configuration: the algorithm with its parameters and the context switch cost.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    pub algorithm: Algorithm,
+    #[serde(default = "no_context_switch")]
+    pub context_switch: ContextSwitch,
+}
+
+fn no_context_switch() -> ContextSwitch {
+    ContextSwitch::None
+}
+
+impl Config {
+    /// Check the parameters and the context switch cost the schedulers would panic on.
+    pub fn check(&self) -> Result<(), String> {
+        self.algorithm.check()?;
+        self.context_switch.validate()?;
+        self.algorithm.check_context_switch(self.context_switch)
+    }
+
+    pub fn run(&self, processes: &[Process]) -> ScheduleResult {
+        self.algorithm.run(processes, self.context_switch)
+    }
+}
+
+/// A workload, the configurations to run on it and (once run) their results.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentFile {
+    pub workload: Workload,
+    pub configs: Vec<Config>,
+    #[serde(default)]
+    pub results: Vec<ScheduleResult>,
+}
+
+impl ExperimentFile {
+    /// Run every configuration on the workload, replacing the previous results.
+    pub fn run(&mut self) -> Result<(), String> {
+        let processes: Vec<Process> = self.workload.to_processes()?;
+        for config in self.configs.iter() {
+            config.check().map_err(|error| format!("{}: {}", config.algorithm.name(), error))?;
+        }
+        self.results = self.configs.iter().map(|config| config.run(&processes)).collect();
+        Ok(())
+    }
+}
+
+/// Everything that can be stored in a file. The `kind` field tells which one a file holds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Document {
+    Workload(Workload),
+    Config(Config),
+    Result(Box<ScheduleResult>),
+    Experiment(ExperimentFile),
+}
+
+/// A document with the schema version in front, as written to files.
+#[derive(Serialize)]
+struct Versioned<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    document: &'a Document,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl Format {
+    /// The format of a file, from its extension.
+    pub fn from_path(path: &Path) -> Result<Format, String> {
+        match path.extension().and_then(|extension| extension.to_str()).map(|extension| extension.to_lowercase()).as_deref() {
+            Some("json") => Ok(Format::Json),
+            Some("toml") => Ok(Format::Toml),
+            Some("yaml" | "yml") => Ok(Format::Yaml),
+            _ => Err(format!("{}: unknown format (expected .json, .toml, .yaml or .yml)", path.display())),
+        }
+    }
+}
+
+/// Upgrade a document of an older schema version to the current one.
+/// A document without `schema_version` is taken as version 1, the first version.
+fn migrate(mut value: serde_json::Value) -> Result<serde_json::Value, String> {
+    let object = value.as_object_mut().ok_or("a document must be a table / object")?;
+    let version: u64 = match object.remove("schema_version") {
+        None => 1,
+        Some(version) => version.as_u64().ok_or("`schema_version` must be a positive integer")?,
+    };
+    match version {
+        // Version 1 is the current version; future versions add their upgrade steps here, oldest first.
+        1 => Ok(value),
+        _ if version > SCHEMA_VERSION as u64 => Err(format!(
+            "schema version {} is newer than the supported version {}",
+            version, SCHEMA_VERSION
+        )),
+        _ => Err(format!("unknown schema version {}", version)),
+    }
+}
+
+/// Convert a YAML value, with a tagged value `!name value` read as the map `{name: value}`.
+fn yaml_to_json(value: serde_yaml::Value) -> Result<serde_json::Value, String> {
+    use serde_yaml::Value;
+    Ok(match value {
+        Value::Null => serde_json::Value::Null,
+        Value::Bool(boolean) => serde_json::Value::Bool(boolean),
+        Value::Number(number) => serde_json::to_value(&number).map_err(|error| error.to_string())?,
+        Value::String(string) => serde_json::Value::String(string),
+        Value::Sequence(sequence) => serde_json::Value::Array(sequence.into_iter().map(yaml_to_json).collect::<Result<_, _>>()?),
+        Value::Mapping(mapping) => {
+            let mut object: serde_json::Map<String, serde_json::Value> = serde_json::Map::new();
+            for (key, value) in mapping {
+                let key: String = match key {
+                    Value::String(key) => key,
+                    Value::Number(key) => key.to_string(),
+                    Value::Bool(key) => key.to_string(),
+                    other => return Err(format!("unsupported map key {:?}", other)),
+                };
+                object.insert(key, yaml_to_json(value)?);
+            }
+            serde_json::Value::Object(object)
+        }
+        Value::Tagged(tagged) => {
+            let name: String = tagged.tag.to_string().trim_start_matches('!').to_string();
+            serde_json::Value::Object(serde_json::Map::from_iter([(name, yaml_to_json(tagged.value)?)]))
+        }
+    })
+}
+
+impl Document {
+    pub fn to_string(&self, format: Format) -> Result<String, String> {
+        let versioned: Versioned = Versioned { schema_version: SCHEMA_VERSION, document: self };
+        match format {
+            Format::Json => serde_json::to_string_pretty(&versioned).map_err(|error| error.to_string()),
+            Format::Toml => toml::to_string_pretty(&versioned).map_err(|error| error.to_string()),
+            // Through a JSON value, so that enums are written as maps like in the other formats instead of YAML tags.
+            Format::Yaml => {
+                let value: serde_json::Value = serde_json::to_value(&versioned).map_err(|error| error.to_string())?;
+                serde_yaml::to_string(&value).map_err(|error| error.to_string())
+            }
+        }
+    }
+
+    pub fn from_str(text: &str, format: Format) -> Result<Document, String> {
+        let value: serde_json::Value = match format {
+            Format::Json => serde_json::from_str(text).map_err(|error| error.to_string())?,
+            Format::Toml => toml::from_str(text).map_err(|error| error.to_string())?,
+            Format::Yaml => yaml_to_json(serde_yaml::from_str(text).map_err(|error| error.to_string())?)?,
+        };
+        serde_json::from_value(migrate(value)?).map_err(|error| error.to_string())
+    }
+
+    /// Write the document in the format given by the extension of the path.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text: String = self.to_string(Format::from_path(path)?)?;
+        fs::write(path, text).map_err(|error| format!("{}: {}", path.display(), error))
+    }
+
+    /// Read a document in the format given by the extension of the path.
+    pub fn load(path: &Path) -> Result<Document, String> {
+        let text: String = fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+        Document::from_str(&text, Format::from_path(path)?).map_err(|error| format!("{}: {}", path.display(), error))
+    }
+}
+
+// Test a round trip of an experiment in every format, and the schema version checks.
+#[test]
+fn test() {
+    let workload: Vec<Process> = vec![
+        Process::new_with_priority(1, 0.0, 8.0, 2),
+        Process::new(2, 1.0, 4.0),
+        Process::new(3, 2.0, 9.0),
+    ];
+    let mut experiment: ExperimentFile = ExperimentFile {
+        workload: Workload::new(&workload),
+        configs: vec![
+            Config { algorithm: Algorithm::Rr { time_quantum: 2.0 }, context_switch: ContextSwitch::PerSwitch(0.5) },
+            Config { algorithm: Algorithm::Mlfq { time_quanta: vec![1.0, 2.0] }, context_switch: ContextSwitch::None },
+        ],
+        results: Vec::new(),
+    };
+    experiment.run().unwrap();
+
+    for format in [Format::Json, Format::Toml, Format::Yaml] {
+        let text: String = Document::Experiment(experiment.clone()).to_string(format).unwrap();
+        let again = match Document::from_str(&text, format).unwrap() {
+            Document::Experiment(again) => again,
+            other => panic!("Wrong kind: {:?}", other),
+        };
+        assert_eq!(again.workload, experiment.workload, "{:?}", format);
+        assert_eq!(again.configs, experiment.configs, "{:?}", format);
+        assert_eq!(format!("{:?}", again.results), format!("{:?}", experiment.results), "{:?}", format);
+    }
+
+    // Bursts and affinities.
+    let mut process: Process = Process::new_with_bursts(4, 1.0, vec![Burst::Cpu(1.0), Burst::Io { device: 0, time: 2.0 }, Burst::Cpu(1.5)]);
+    process.set_affinity(0b11, 0b01);
+    let workload: Workload = Workload::new(&[process]);
+    let text: String = Document::Workload(workload.clone()).to_string(Format::Yaml).unwrap();
+    match Document::from_str(&text, Format::Yaml).unwrap() {
+        Document::Workload(again) => {
+            assert_eq!(again, workload);
+            let process: Process = again.to_processes().unwrap().remove(0);
+            assert_eq!((process.burst_time, process.io_time(), process.hard_affinity, process.soft_affinity), (2.5, 2.0, 0b11, 0b01));
+        }
+        other => panic!("Wrong kind: {:?}", other),
+    }
+    let mut spec: ProcessSpec = workload.processes[0].clone();
+    spec.bursts.insert(1, Burst::Io { device: 1, time: 1.0 });
+    assert_eq!(
+        spec.to_process().unwrap_err(),
+        "P4: a burst sequence must alternate CPU and I/O bursts, starting and ending with a CPU burst"
+    );
+
+    let mut spec: ProcessSpec = workload.processes[0].clone();
+    spec.burst_history = vec![1.0, f64::NAN];
+    assert_eq!(spec.to_process().unwrap_err(), "P4: the burst history must only contain non negative times");
+
+    let twice: Workload = Workload { processes: vec![workload.processes[0].clone(), workload.processes[0].clone()] };
+    assert_eq!(twice.to_processes().unwrap_err(), "P4: duplicate pid");
+
+    // Configurations the schedulers would panic on are rejected before running.
+    for config in [
+        Config { algorithm: Algorithm::Rr { time_quantum: 0.0 }, context_switch: ContextSwitch::None },
+        Config { algorithm: Algorithm::Mlfq { time_quanta: vec![] }, context_switch: ContextSwitch::None },
+        Config { algorithm: Algorithm::Mlfq { time_quanta: vec![1.0] }, context_switch: ContextSwitch::Fixed(0.5) },
+        Config { algorithm: Algorithm::Fcfs, context_switch: ContextSwitch::Fixed(-1.0) },
+    ] {
+        let mut invalid: ExperimentFile = ExperimentFile { configs: vec![config.clone()], ..experiment.clone() };
+        assert!(invalid.run().is_err(), "{:?}", config);
+    }
+
+    // A hand written workload without version nor optional fields.
+    let text: &str = "kind = \"workload\"\n[[processes]]\npid = 1\narrival_time = 0.0\nburst_time = 3.0\nhard_affinity = 6\n";
+    match Document::from_str(text, Format::Toml).unwrap() {
+        Document::Workload(workload) => {
+            let process: Process = workload.to_processes().unwrap().remove(0);
+            assert_eq!((process.burst_time, process.hard_affinity), (3.0, 6));
+        }
+        other => panic!("Wrong kind: {:?}", other),
+    }
+    let text: &str = "{\"schema_version\": 99, \"kind\": \"config\", \"algorithm\": {\"name\": \"fcfs\"}}";
+    assert_eq!(
+        Document::from_str(text, Format::Json).unwrap_err(),
+        "schema version 99 is newer than the supported version 1"
+    );
+    let text: &str = "schema_version: 1\nkind: config\nalgorithm:\n  name: rr\n  time_quantum: 4\ncontext_switch:\n  fixed: 0.5\n";
+    match Document::from_str(text, Format::Yaml).unwrap() {
+        Document::Config(config) => {
+            assert_eq!(config.algorithm, Algorithm::Rr { time_quantum: 4.0 });
+            assert_eq!(config.context_switch, ContextSwitch::Fixed(0.5));
+        }
+        other => panic!("Wrong kind: {:?}", other),
+    }
+}
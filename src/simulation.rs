@@ -0,0 +1,404 @@
+use std::collections::VecDeque;
+
+use crate::cpu_schedulers::{Process, ProcessState, Event, ContextSwitch, to_ticks};
+use crate::cpu_schedulers::nonpreemptive::{burst_key, priority_key, predicted_burst_key, shortest_first_order, aged_priority_order};
+use crate::cpu_schedulers::preemptive::{PreemptiveScheduler, remaining_time_key, predicted_remaining_time_key};
+use crate::cpu_schedulers::prediction::ExponentialAverage;
+use crate::schedule::{Algorithm, ScheduleResult};
+
+/// One thing that happens in a simulation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SimulationEvent {
+    /// The process enters the ready queue.
+    Arrival { time: f64, pid: u32 },
+    /// The process gets the CPU; `time` is when it starts running, after the context switch if there is one.
+    Dispatch { time: f64, pid: u32 },
+    /// The process goes back to the ready queue.
+    Preemption { time: f64, pid: u32 },
+    Completion { time: f64, pid: u32 },
+    /// Nothing is ready: the CPU is idle from `time` until `until`.
+    Idle { time: f64, until: f64 },
+}
+
+impl SimulationEvent {
+    pub fn time(&self) -> f64 {
+        match *self {
+            SimulationEvent::Arrival { time, .. }
+            | SimulationEvent::Dispatch { time, .. }
+            | SimulationEvent::Preemption { time, .. }
+            | SimulationEvent::Completion { time, .. }
+            | SimulationEvent::Idle { time, .. } => time,
+        }
+    }
+}
+
+/// A single-CPU scheduling algorithm run one event at a time.
+///
+/// The simulation uses the dispatcher of `PreemptiveScheduler` (admission, context switches, runs and state histories),
+/// so running it to the end gives the same timeline as `Algorithm::run`. Between two events the ready queue
+/// and the running process can be inspected, and processes arriving from the current time on can be added.
+/// MLFQ, run by the `IoScheduler`, is not supported.
+pub struct Simulation {
+    algorithm: Algorithm,
+    /// Holds the processes that have not arrived yet (in arrival order), the timeline and the finished processes.
+    scheduler: PreemptiveScheduler,
+    ready_queue: VecDeque<Process>,
+    running: Option<Process>,
+    /// Whether the running process has run since its dispatch (its next run extends the same slice).
+    continuing: bool,
+    /// End of the RR time slice of the running process.
+    slice_end: u64,
+    /// The clock, in hundredths.
+    current_time: u64,
+    /// Events that happened at the current time and are not returned yet.
+    events: VecDeque<SimulationEvent>,
+}
+
+// Common methods
+impl Simulation {
+    pub fn new(algorithm: Algorithm, processes: Vec<Process>, context_switch: ContextSwitch) -> Simulation {
+        if let Err(error) = algorithm.check() {
+            panic!("{}", error);
+        }
+        if let Algorithm::Mlfq { .. } = algorithm {
+            panic!("MLFQ is not supported by the step-wise simulation.");
+        }
+        let mut scheduler: PreemptiveScheduler = PreemptiveScheduler::new(processes);
+        scheduler.set_context_switch(context_switch);
+        scheduler.sort_by_arrival_time();
+        let current_time: u64 = scheduler.processes.first().map_or(0, |process| to_ticks(process.arrival_time));
+        Simulation {
+            algorithm,
+            scheduler,
+            ready_queue: VecDeque::new(),
+            running: None,
+            continuing: false,
+            slice_end: 0,
+            current_time,
+            events: VecDeque::new(),
+        }
+    }
+
+    /// The clock of the simulation.
+    pub fn now(&self) -> f64 {
+        self.current_time as f64 / 100.0
+    }
+
+    pub fn running(&self) -> Option<&Process> {
+        self.running.as_ref()
+    }
+
+    /// The ready processes, in the order they entered the ready queue.
+    pub fn ready_queue(&self) -> impl Iterator<Item = &Process> {
+        self.ready_queue.iter()
+    }
+
+    /// The processes that have not arrived yet, in arrival order.
+    pub fn future_arrivals(&self) -> &[Process] {
+        &self.scheduler.processes
+    }
+
+    pub fn finished_processes(&self) -> &[Process] {
+        &self.scheduler.finished_processes
+    }
+
+    /// The timeline so far.
+    pub fn event_list(&self) -> &[Event] {
+        &self.scheduler.event_list
+    }
+
+    /// Whether every process has finished (adding a process starts the simulation again).
+    pub fn is_finished(&self) -> bool {
+        self.events.is_empty() && self.running.is_none() && self.ready_queue.is_empty() && self.scheduler.processes.is_empty()
+    }
+
+    /// Add a process arriving now or later. A process added at the current time is queued behind the processes
+    /// already ready.
+    pub fn add_process(&mut self, process: Process) {
+        if to_ticks(process.arrival_time) < self.current_time {
+            panic!("P{} arrives at {:.2}, before the current time {:.2}.", process.pid, process.arrival_time, self.now());
+        }
+        let known: bool = self
+            .scheduler
+            .processes
+            .iter()
+            .chain(self.ready_queue.iter())
+            .chain(self.running.iter())
+            .chain(self.scheduler.finished_processes.iter())
+            .any(|other| other.pid == process.pid);
+        if known {
+            panic!("P{} is already in the simulation.", process.pid);
+        }
+        let position: usize = self.scheduler.processes.partition_point(|other| to_ticks(other.arrival_time) <= to_ticks(process.arrival_time));
+        self.scheduler.processes.insert(position, process);
+    }
+
+    /// The next event, or `None` when every process has finished.
+    pub fn next_event(&mut self) -> Option<SimulationEvent> {
+        while self.events.is_empty() {
+            if !self.advance(None) {
+                return None;
+            }
+        }
+        self.events.pop_front()
+    }
+
+    /// The events until the clock reaches `time` (the events at `time` itself are left for the next call).
+    pub fn run_until(&mut self, time: f64) -> Vec<SimulationEvent> {
+        let limit: u64 = to_ticks(time);
+        let mut events: Vec<SimulationEvent> = Vec::new();
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                events.push(event);
+            } else if self.current_time >= limit || !self.advance(Some(limit)) {
+                return events;
+            }
+        }
+    }
+
+    /// The result so far (the whole schedule once the simulation is finished).
+    pub fn result(&self) -> ScheduleResult {
+        ScheduleResult::new(self.algorithm.name(), self.scheduler.finished_processes.clone(), self.scheduler.event_list.clone(), 1)
+    }
+}
+
+// Algorithms
+impl Simulation {
+    /// Ordering key of the preemptive algorithms (a ready process with a smaller key preempts the running one).
+    fn preemption_key(&self, process: &Process) -> Option<u64> {
+        match self.algorithm {
+            Algorithm::Srtf => Some(remaining_time_key(process)),
+            Algorithm::PreemptivePriority => Some(priority_key(process)),
+            Algorithm::PredictiveSrtf { alpha, initial_guess } => {
+                Some(predicted_remaining_time_key(&ExponentialAverage::new(alpha, initial_guess), process))
+            }
+            _ => None,
+        }
+    }
+
+    /// Position in the ready queue of the next process to dispatch, with the selection keys of the schedulers.
+    fn pick(&self) -> Option<usize> {
+        let queue: &VecDeque<Process> = &self.ready_queue;
+        if queue.is_empty() {
+            return None;
+        }
+        // Nonpreemptive ties: earliest arrival, then smallest pid; preemptive ties: longest in the ready queue.
+        let shortest_first = |key: &dyn Fn(&Process) -> u64| {
+            (0..queue.len()).min_by_key(|&position| shortest_first_order(key(&queue[position]), &queue[position]))
+        };
+        match self.algorithm {
+            Algorithm::Fcfs | Algorithm::Rr { .. } => Some(0),
+            Algorithm::Sjf => shortest_first(&burst_key),
+            Algorithm::Priority => shortest_first(&priority_key),
+            Algorithm::PredictiveSjf { alpha, initial_guess } => {
+                let estimator: ExponentialAverage = ExponentialAverage::new(alpha, initial_guess);
+                shortest_first(&|process: &Process| predicted_burst_key(&estimator, process))
+            }
+            Algorithm::PriorityWithAging { aging_rate } => {
+                (0..queue.len()).min_by(|&a, &b| aged_priority_order(&queue[a], &queue[b], aging_rate, self.now()))
+            }
+            _ => (0..queue.len()).min_by_key(|&position| self.preemption_key(&queue[position]).unwrap()),
+        }
+    }
+
+    /// Move the arrived processes to the ready queue, with an event for each.
+    fn admit(&mut self) {
+        let queued: usize = self.ready_queue.len();
+        self.scheduler.admit(self.current_time, &mut self.ready_queue);
+        for process in self.ready_queue.iter().skip(queued) {
+            self.events.push_back(SimulationEvent::Arrival { time: process.arrival_time, pid: process.pid });
+        }
+    }
+
+    /// Simulate until something happens, without going past `limit`. Returns `false` if nothing is left to do.
+    fn advance(&mut self, limit: Option<u64>) -> bool {
+        // Arrivals (with a nonpreemptive algorithm or RR, the processes arriving during a context switch
+        // are seen once the switched-in process has run).
+        if self.running.is_none() || self.continuing {
+            self.admit();
+            if !self.events.is_empty() {
+                return true;
+            }
+        }
+
+        let Some(mut process) = self.running.take() else {
+            // Dispatch the next process, or wait for the next arrival.
+            match self.pick() {
+                Some(position) => {
+                    let mut process: Process = self.ready_queue.remove(position).unwrap();
+                    self.current_time = self.scheduler.switch_to(&process, self.current_time);
+
+                    // A process that arrived during the context switch with a smaller key preempts the process before it runs.
+                    if let Some(key) = self.preemption_key(&process) {
+                        self.admit();
+                        if self.ready_queue.iter().any(|ready| self.preemption_key(ready).unwrap() < key) {
+                            self.ready_queue.insert(position, process);
+                            return true;
+                        }
+                    }
+                    self.scheduler.dispatch(&mut process, self.current_time);
+                    if let Algorithm::Rr { time_quantum } = self.algorithm {
+                        self.slice_end = self.current_time + to_ticks(time_quantum);
+                    }
+                    self.events.push_back(SimulationEvent::Dispatch { time: self.now(), pid: process.pid });
+                    self.running = Some(process);
+                    self.continuing = false;
+                }
+                None => {
+                    let Some(next_arrival) = self.scheduler.processes.first().map(|process| to_ticks(process.arrival_time)) else {
+                        return false;
+                    };
+                    let until: u64 = limit.map_or(next_arrival, |limit| limit.min(next_arrival));
+                    self.events.push_back(SimulationEvent::Idle { time: self.now(), until: until as f64 / 100.0 });
+                    self.current_time = until;
+                }
+            }
+            return true;
+        };
+
+        // A ready process with a smaller key preempts the running one.
+        if let (Some(key), Some(position)) = (self.preemption_key(&process), self.pick()) {
+            if self.preemption_key(&self.ready_queue[position]).unwrap() < key {
+                process.transition(ProcessState::Ready, self.now());
+                self.events.push_back(SimulationEvent::Preemption { time: self.now(), pid: process.pid });
+                self.ready_queue.push_back(process);
+                return true;
+            }
+        }
+
+        // Run until the process finishes, its time slice ends, the next process arrives or the limit.
+        let mut next_time: u64 = self.current_time + to_ticks(process.remaining_time);
+        if let Algorithm::Rr { .. } = self.algorithm {
+            next_time = next_time.min(self.slice_end);
+        }
+        let next_arrival: Option<u64> = self
+            .scheduler
+            .processes
+            .iter()
+            .map(|next_process| to_ticks(next_process.arrival_time))
+            .find(|&arrival_time| arrival_time > self.current_time);
+        for stop in next_arrival.into_iter().chain(limit) {
+            next_time = next_time.min(stop);
+        }
+        self.scheduler.run(&mut process, self.current_time, next_time, self.continuing);
+        self.current_time = next_time;
+        self.continuing = true;
+
+        if to_ticks(process.remaining_time) == 0 {
+            self.events.push_back(SimulationEvent::Completion { time: self.now(), pid: process.pid });
+            self.scheduler.finish(process, self.current_time);
+        } else if matches!(self.algorithm, Algorithm::Rr { .. }) && self.current_time == self.slice_end {
+            // The processes that arrived during the slice are queued before the preempted one.
+            self.admit();
+            process.transition(ProcessState::Ready, self.now());
+            self.events.push_back(SimulationEvent::Preemption { time: self.now(), pid: process.pid });
+            self.ready_queue.push_back(process);
+        } else {
+            self.running = Some(process);
+        }
+        true
+    }
+}
+
+impl Iterator for Simulation {
+    type Item = SimulationEvent;
+
+    fn next(&mut self) -> Option<SimulationEvent> {
+        self.next_event()
+    }
+}
+
+// Test that a simulation run to the end gives the schedule of `Algorithm::run`, the events, pausing and injected processes.
+#[test]
+fn test() {
+    use SimulationEvent::*;
+
+    let mut processes: Vec<Process> = vec![
+        Process::new_with_priority(1, 0.0, 5.0, 3),
+        Process::new_with_priority(2, 1.0, 3.0, 1),
+        Process::new_with_priority(3, 1.0, 1.0, 2),
+        Process::new_with_priority(4, 2.5, 2.0, 1),
+        Process::new_with_priority(5, 20.0, 1.5, 0),
+    ];
+    processes[0].set_burst_history(vec![1.0]);
+    processes[1].set_burst_history(vec![6.0, 4.0]);
+    let algorithms: Vec<Algorithm> = vec![
+        Algorithm::Fcfs,
+        Algorithm::Sjf,
+        Algorithm::Priority,
+        Algorithm::PriorityWithAging { aging_rate: 0.5 },
+        Algorithm::Rr { time_quantum: 2.0 },
+        Algorithm::Srtf,
+        Algorithm::PreemptivePriority,
+        Algorithm::PredictiveSjf { alpha: 0.5, initial_guess: 2.0 },
+        Algorithm::PredictiveSrtf { alpha: 0.5, initial_guess: 2.0 },
+    ];
+    for algorithm in algorithms {
+        for context_switch in [ContextSwitch::None, ContextSwitch::PerSwitch(0.5), ContextSwitch::Fixed(0.25)] {
+            let expected: ScheduleResult = algorithm.run(&processes, context_switch);
+            let mut simulation: Simulation = Simulation::new(algorithm.clone(), processes.clone(), context_switch);
+            let events: Vec<SimulationEvent> = simulation.by_ref().collect();
+            assert!(simulation.is_finished());
+            assert!(events.windows(2).all(|pair| pair[0].time() <= pair[1].time() || matches!(pair[1], Arrival { .. })));
+            let result: ScheduleResult = simulation.result();
+            let timeline = |result: &ScheduleResult| -> Vec<(String, f64, f64)> {
+                result.event_list.iter().map(|event| (event.name.clone(), event.start_time, event.finish_time)).collect()
+            };
+            assert_eq!(timeline(&result), timeline(&expected), "{} with {:?}", algorithm.name(), context_switch);
+            let times = |result: &ScheduleResult| -> Vec<(u32, f64, f64)> {
+                result.finished_processes.iter().map(|process| (process.pid, process.finish_time, process.waiting_time)).collect()
+            };
+            assert_eq!(times(&result), times(&expected), "{} with {:?}", algorithm.name(), context_switch);
+        }
+    }
+
+    // The events of RR, with the idle gap before P5.
+    let simulation: Simulation = Simulation::new(Algorithm::Rr { time_quantum: 2.0 }, processes.clone(), ContextSwitch::None);
+    let events: Vec<SimulationEvent> = simulation.take(9).collect();
+    assert_eq!(
+        events,
+        vec![
+            Arrival { time: 0.0, pid: 1 },
+            Dispatch { time: 0.0, pid: 1 },
+            Arrival { time: 1.0, pid: 2 },
+            Arrival { time: 1.0, pid: 3 },
+            Preemption { time: 2.0, pid: 1 },
+            Dispatch { time: 2.0, pid: 2 },
+            Arrival { time: 2.5, pid: 4 },
+            Preemption { time: 4.0, pid: 2 },
+            Dispatch { time: 4.0, pid: 3 },
+        ]
+    );
+    let mut simulation: Simulation = Simulation::new(Algorithm::Rr { time_quantum: 2.0 }, processes.clone(), ContextSwitch::None);
+    assert!(simulation.by_ref().any(|event| event == Idle { time: 11.0, until: 20.0 }));
+
+    // Pause SRTF at 3 and look inside, then add a short process arriving at 4: it preempts P4.
+    let mut simulation: Simulation = Simulation::new(Algorithm::Srtf, processes.clone(), ContextSwitch::None);
+    let events: Vec<SimulationEvent> = simulation.run_until(3.0);
+    assert_eq!(events[events.len() - 3..], [Arrival { time: 2.5, pid: 4 }, Preemption { time: 2.5, pid: 2 }, Dispatch { time: 2.5, pid: 4 }]);
+    assert_eq!(simulation.now(), 3.0);
+    assert_eq!(simulation.running().map(|process| (process.pid, process.remaining_time)), Some((4, 1.5)));
+    let ready: Vec<u32> = simulation.ready_queue().map(|process| process.pid).collect();
+    assert_eq!(ready, vec![1, 2]);
+    simulation.add_process(Process::new(6, 4.0, 0.25));
+    let events: Vec<SimulationEvent> = simulation.by_ref().take(5).collect();
+    assert_eq!(
+        events,
+        vec![
+            Arrival { time: 4.0, pid: 6 },
+            Preemption { time: 4.0, pid: 4 },
+            Dispatch { time: 4.0, pid: 6 },
+            Completion { time: 4.25, pid: 6 },
+            Dispatch { time: 4.25, pid: 4 },
+        ]
+    );
+
+    // A simulation can start empty and be fed online.
+    let mut simulation: Simulation = Simulation::new(Algorithm::Fcfs, Vec::new(), ContextSwitch::None);
+    assert_eq!(simulation.next_event(), None);
+    simulation.add_process(Process::new(1, 2.0, 1.0));
+    assert_eq!(simulation.next_event(), Some(Idle { time: 0.0, until: 2.0 }));
+    assert_eq!(simulation.by_ref().last(), Some(Completion { time: 3.0, pid: 1 }));
+    simulation.add_process(Process::new(2, 3.0, 1.0));
+    assert_eq!(simulation.last(), Some(Completion { time: 4.0, pid: 2 }));
+}
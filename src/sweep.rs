@@ -0,0 +1,256 @@
+use crate::cpu_schedulers::{Process, ContextSwitch};
+use crate::gantt::escape;
+use crate::metrics::Metric;
+use crate::schedule::{Algorithm, ScheduleResult};
+
+/// One run of a sweep.
+#[derive(Debug, Clone)]
+pub struct SweepPoint {
+    pub value: f64,
+    pub result: ScheduleResult,
+}
+
+/// One workload run once per value of a tuning knob.
+#[derive(Debug, Clone)]
+pub struct Sweep {
+    /// Name of the swept parameter, used as the x axis label and the first CSV column.
+    pub parameter: String,
+    pub points: Vec<SweepPoint>,
+}
+
+/// Most values in a range.
+pub const MAX_RANGE_LENGTH: usize = 10_000;
+
+/// Most levels in an MLFQ levels sweep.
+pub const MAX_MLFQ_LEVELS: usize = 64;
+
+/// Check the bounds and the step of a range, and that it has at most `MAX_RANGE_LENGTH` values.
+pub fn check_range(start: f64, stop: f64, step: f64) -> Result<(), String> {
+    if !start.is_finite() || !stop.is_finite() || !step.is_finite() || step <= 0.0 || stop < start {
+        return Err(format!("Invalid range {}:{}:{}.", start, stop, step));
+    }
+    if (stop - start) / step >= MAX_RANGE_LENGTH as f64 {
+        return Err(format!("A range has at most {} values.", MAX_RANGE_LENGTH));
+    }
+    Ok(())
+}
+
+/// Values from `start` to `stop` (inclusive) every `step`, rounded to two decimal places.
+pub fn range(start: f64, stop: f64, step: f64) -> Vec<f64> {
+    if let Err(error) = check_range(start, stop, step) {
+        panic!("{}", error);
+    }
+    let number_of_values: usize = ((stop - start) / step + 1e-9).floor() as usize + 1;
+    (0..number_of_values)
+        .map(|i| ((start + i as f64 * step) * 100.0).round() / 100.0)
+        .collect()
+}
+
+impl Sweep {
+    /// Run `configure(value)` on a copy of the processes for every value.
+    pub fn new(parameter: &str, processes: &[Process], values: &[f64], configure: impl Fn(f64) -> (Algorithm, ContextSwitch)) -> Sweep {
+        if values.is_empty() {
+            panic!("Nothing to sweep!");
+        }
+        let points: Vec<SweepPoint> = values
+            .iter()
+            .map(|&value| {
+                let (algorithm, context_switch) = configure(value);
+                SweepPoint { value, result: algorithm.run(processes, context_switch) }
+            })
+            .collect();
+        Sweep { parameter: parameter.to_string(), points }
+    }
+
+    /// RR with every time quantum.
+    pub fn time_quantum(processes: &[Process], time_quanta: &[f64], context_switch: ContextSwitch) -> Sweep {
+        Sweep::new("time_quantum", processes, time_quanta, |time_quantum| (Algorithm::Rr { time_quantum }, context_switch))
+    }
+
+    /// MLFQ with 1, 2, ... `max_levels` levels; the quantum doubles on every level, starting at `base_quantum`.
+    pub fn mlfq_levels(processes: &[Process], max_levels: usize, base_quantum: f64) -> Sweep {
+        if max_levels > MAX_MLFQ_LEVELS {
+            panic!("An MLFQ levels sweep has at most {} levels.", MAX_MLFQ_LEVELS);
+        }
+        let levels: Vec<f64> = (1..=max_levels).map(|level| level as f64).collect();
+        Sweep::new("mlfq_levels", processes, &levels, |levels| {
+            let time_quanta: Vec<f64> = (0..levels as usize).map(|level| base_quantum * 2f64.powi(level as i32)).collect();
+            (Algorithm::Mlfq { time_quanta }, ContextSwitch::None)
+        })
+    }
+
+    /// Nonpreemptive priority scheduling with every aging rate.
+    pub fn aging_rate(processes: &[Process], aging_rates: &[f64], context_switch: ContextSwitch) -> Sweep {
+        Sweep::new("aging_rate", processes, aging_rates, |aging_rate| (Algorithm::PriorityWithAging { aging_rate }, context_switch))
+    }
+
+    /// The given algorithm with every context switch cost (`ContextSwitch::PerSwitch`).
+    pub fn context_switch_cost(processes: &[Process], algorithm: Algorithm, costs: &[f64]) -> Sweep {
+        Sweep::new("context_switch_cost", processes, costs, |cost| (algorithm.clone(), ContextSwitch::PerSwitch(cost)))
+    }
+
+    /// (parameter value, metric value) for every point.
+    pub fn series(&self, metric: Metric) -> Vec<(f64, f64)> {
+        self.points
+            .iter()
+            .map(|point| (point.value, metric.value(&point.result.metrics)))
+            .collect()
+    }
+
+    /// One row per point, one column per metric in `Metric::ALL`.
+    pub fn to_csv(&self) -> String {
+        let mut csv: String = self.parameter.clone();
+        for metric in Metric::ALL.iter() {
+            csv += &format!(",{}", metric.id());
+        }
+        csv += "\n";
+        for point in self.points.iter() {
+            csv += &format!("{}", point.value);
+            for metric in Metric::ALL.iter() {
+                csv += &format!(",{:.4}", metric.value(&point.result.metrics));
+            }
+            csv += "\n";
+        }
+        csv
+    }
+}
+
+// Visualization
+impl Sweep {
+    /// Scatter the series on a `width` x `height` character grid, with the y range on the left and the x range below.
+    pub fn ascii_chart(&self, metric: Metric, width: usize, height: usize) -> String {
+        if width < 2 || height < 2 {
+            panic!("Chart is too small!");
+        }
+        let series: Vec<(f64, f64)> = self.series(metric);
+        let (x_min, x_max, y_min, y_max) = bounds(&series);
+
+        let mut grid: Vec<Vec<char>> = vec![vec![' '; width]; height];
+        for &(x, y) in series.iter() {
+            let column: usize = scale(x, x_min, x_max, (width - 1) as f64).round() as usize;
+            let row: usize = height - 1 - scale(y, y_min, y_max, (height - 1) as f64).round() as usize;
+            grid[row][column] = '*';
+        }
+
+        let mut chart: String = format!("{} vs {}\n", metric.name(), self.parameter);
+        for (row, cells) in grid.iter().enumerate() {
+            let label: String = match row {
+                0 => format!("{:>8.2}", y_max),
+                _ if row == height - 1 => format!("{:>8.2}", y_min),
+                _ => " ".repeat(8),
+            };
+            chart += &format!("{} |{}\n", label, cells.iter().collect::<String>());
+        }
+        chart += &format!("{} +{}\n", " ".repeat(8), "-".repeat(width));
+        let x_min_label: String = format!("{:.2}", x_min);
+        chart += &format!("{}  {}{:>w$}\n", " ".repeat(8), x_min_label, format!("{:.2}", x_max), w = width.saturating_sub(x_min_label.len()));
+        chart
+    }
+
+    /// Line chart of the series as a standalone SVG document.
+    pub fn svg_chart(&self, metric: Metric, width: usize, height: usize) -> String {
+        let series: Vec<(f64, f64)> = self.series(metric);
+        let (x_min, x_max, y_min, y_max) = bounds(&series);
+        let margin: f64 = 50.0;
+        let plot_width: f64 = width as f64 - 2.0 * margin;
+        let plot_height: f64 = height as f64 - 2.0 * margin;
+        let point = |(x, y): (f64, f64)| -> (f64, f64) {
+            (margin + scale(x, x_min, x_max, plot_width), margin + plot_height - scale(y, y_min, y_max, plot_height))
+        };
+
+        let mut svg: String = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"monospace\" font-size=\"12\">\n",
+            width, height
+        );
+        svg += &format!("<text x=\"{}\" y=\"20\" text-anchor=\"middle\">{} vs {}</text>\n", width / 2, escape(metric.name()), escape(&self.parameter));
+        // Axes and their ranges.
+        svg += &format!(
+            "<polyline points=\"{m:.1},{m:.1} {m:.1},{b:.1} {r:.1},{b:.1}\" fill=\"none\" stroke=\"black\"/>\n",
+            m = margin, b = margin + plot_height, r = margin + plot_width
+        );
+        svg += &format!("<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{:.2}</text>\n", margin - 5.0, margin + 4.0, y_max);
+        svg += &format!("<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{:.2}</text>\n", margin - 5.0, margin + plot_height, y_min);
+        svg += &format!("<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{:.2}</text>\n", margin, margin + plot_height + 16.0, x_min);
+        svg += &format!("<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{:.2}</text>\n", margin + plot_width, margin + plot_height + 16.0, x_max);
+        // The series.
+        let points: Vec<(f64, f64)> = series.iter().map(|&value| point(value)).collect();
+        let path: Vec<String> = points.iter().map(|(x, y)| format!("{:.1},{:.1}", x, y)).collect();
+        svg += &format!("<polyline points=\"{}\" fill=\"none\" stroke=\"steelblue\" stroke-width=\"2\"/>\n", path.join(" "));
+        for (x, y) in points.iter() {
+            svg += &format!("<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"steelblue\"/>\n", x, y);
+        }
+        svg += "</svg>\n";
+        svg
+    }
+
+    /// The series as a two column table followed by its ASCII chart.
+    pub fn to_text(&self, metric: Metric) -> String {
+        let mut text: String = format!("{}\t{}\n", self.parameter, metric.name());
+        for (value, metric_value) in self.series(metric) {
+            text += &format!("{:.2}\t\t{:.2}\n", value, metric_value);
+        }
+        text + &self.ascii_chart(metric, 40, 10)
+    }
+
+    pub fn print(&self, metric: Metric) {
+        print!("{}", self.to_text(metric));
+    }
+}
+
+/// (x min, x max, y min, y max) of a series.
+fn bounds(series: &[(f64, f64)]) -> (f64, f64, f64, f64) {
+    series.iter().fold(
+        (f64::INFINITY, f64::NEG_INFINITY, f64::INFINITY, f64::NEG_INFINITY),
+        |(x_min, x_max, y_min, y_max), &(x, y)| (x_min.min(x), x_max.max(x), y_min.min(y), y_max.max(y)),
+    )
+}
+
+/// Map `value` from [min, max] to [0, length]; a flat range maps to the middle.
+fn scale(value: f64, min: f64, max: f64, length: f64) -> f64 {
+    if max > min { (value - min) / (max - min) * length } else { length / 2.0 }
+}
+
+// Test the turn around time vs quantum curve and the other knobs.
+#[test]
+fn test() {
+    let processes: Vec<Process> = vec![
+        Process::new(1, 0.0, 24.0),
+        Process::new(2, 0.0, 3.0),
+        Process::new(3, 0.0, 3.0),
+    ];
+    assert_eq!(range(1.0, 2.0, 0.25), vec![1.0, 1.25, 1.5, 1.75, 2.0]);
+    assert!(check_range(0.0, 9_999.0, 1.0).is_ok());
+    assert!(check_range(0.0, 10_000.0, 1.0).is_err());
+    assert!(check_range(0.0, f64::INFINITY, 1.0).is_err());
+
+    // With a quantum of 4, RR gives the textbook 5.67 average waiting time; with a huge quantum it becomes FCFS (17).
+    let sweep: Sweep = Sweep::time_quantum(&processes, &[1.0, 4.0, 24.0], ContextSwitch::None);
+    let series: Vec<(f64, f64)> = sweep.series(Metric::AverageWaitingTime);
+    assert!((series[1].1 - 17.0 / 3.0).abs() < 1e-9);
+    assert_eq!(series[2].1, 17.0);
+
+    let csv: String = sweep.to_csv();
+    assert!(csv.starts_with("time_quantum,average_waiting_time,"));
+    assert_eq!(csv.lines().count(), 4);
+    assert!(sweep.svg_chart(Metric::AverageWaitingTime, 400, 300).contains("<polyline"));
+    let sweep: Sweep = Sweep::new("q<\"8\"", &processes, &[4.0], |time_quantum| (Algorithm::Rr { time_quantum }, ContextSwitch::None));
+    assert!(sweep.svg_chart(Metric::AverageWaitingTime, 400, 300).contains("vs q&lt;&quot;8&quot;</text>"));
+
+    // The context switch cost lowers the CPU utilization.
+    let sweep: Sweep = Sweep::context_switch_cost(&processes, Algorithm::Rr { time_quantum: 4.0 }, &[0.0, 0.5]);
+    let series: Vec<(f64, f64)> = sweep.series(Metric::CpuUtilization);
+    assert!(series[0].1 > series[1].1);
+
+    // Aging lets the low priority process run before the later high priority ones.
+    let processes: Vec<Process> = vec![
+        Process::new_with_priority(1, 0.0, 2.0, 1),
+        Process::new_with_priority(2, 0.0, 2.0, 5),
+        Process::new_with_priority(3, 1.0, 2.0, 1),
+        Process::new_with_priority(4, 3.0, 2.0, 1),
+    ];
+    let sweep: Sweep = Sweep::aging_rate(&processes, &[0.0, 2.0], ContextSwitch::None);
+    let finish_time = |point: &SweepPoint| point.result.finished_processes[1].finish_time;
+    assert_eq!(finish_time(&sweep.points[0]), 8.0);
+    assert_eq!(finish_time(&sweep.points[1]), 6.0);
+    assert_eq!(Sweep::mlfq_levels(&processes, 3, 1.0).points.len(), 3);
+}
@@ -0,0 +1,187 @@
+use crate::cpu_schedulers::{Process, ProcessState, Event, EventKind};
+
+/// Trace process holding one track per simulated process (or thread).
+const PROCESSES_TRACK: u32 = 1;
+/// Trace process holding one track per core.
+const CORES_TRACK: u32 = 2;
+
+/// Timestamp of a simulated time, in microseconds: one time unit is shown as one millisecond.
+fn timestamp(time: f64) -> i64 {
+    (time * 1000.0).round() as i64
+}
+
+/// Quote a JSON string.
+fn quote(text: &str) -> String {
+    let mut quoted: String = String::from("\"");
+    for character in text.chars() {
+        match character {
+            '"' => quoted += "\\\"",
+            '\\' => quoted += "\\\\",
+            '\n' => quoted += "\\n",
+            character if (character as u32) < 0x20 => quoted += &format!("\\u{:04x}", character as u32),
+            character => quoted.push(character),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Name of the process of an event, without the thread (`P3.T1` gives `P3`).
+fn process_name(name: &str) -> &str {
+    name.split('.').next().unwrap_or(name)
+}
+
+/// Write a timeline in the Chrome Trace Event format, to open in Perfetto (ui.perfetto.dev) or chrome://tracing.
+///
+/// - "Processes" has one track per process (per thread for threaded processes) with a slice for every run,
+///   and instant events when the process arrives, is preempted, blocks on I/O and terminates.
+/// - "Cores" has one track per core with the runs and the context switches.
+/// - The "Ready queue" counter is the number of ready processes over time, from the state histories.
+///
+/// One time unit of the simulation is shown as one millisecond.
+pub fn write_chrome_trace(finished_processes: &[Process], event_list: &[Event]) -> String {
+    let mut trace_events: Vec<String> = Vec::new();
+    let metadata = |pid: u32, tid: Option<u32>, kind: &str, name: &str| -> String {
+        let tid: String = tid.map(|tid| format!(",\"tid\":{}", tid)).unwrap_or_default();
+        format!("{{\"ph\":\"M\",\"pid\":{}{},\"name\":\"{}\",\"args\":{{\"name\":{}}}}}", pid, tid, kind, quote(name))
+    };
+    trace_events.push(metadata(PROCESSES_TRACK, None, "process_name", "Processes"));
+    trace_events.push(metadata(CORES_TRACK, None, "process_name", "Cores"));
+
+    // One track per process or thread, in pid order (processes that never ran still get one for their instant events).
+    let mut tracks: Vec<(Option<u32>, String)> = event_list
+        .iter()
+        .filter(|event| event.kind == EventKind::Process)
+        .map(|event| (event.pid(), event.name.clone()))
+        .collect();
+    for process in finished_processes.iter() {
+        if !tracks.iter().any(|(pid, _)| *pid == Some(process.pid)) {
+            tracks.push((Some(process.pid), format!("P{}", process.pid)));
+        }
+    }
+    tracks.sort_unstable();
+    tracks.dedup();
+    let tracks: Vec<String> = tracks.into_iter().map(|(_, name)| name).collect();
+    let track = |name: &str| -> u32 {
+        // The track of the event, or the first track of its process for the process-wide instant events.
+        let index: usize = tracks
+            .iter()
+            .position(|track| track == name)
+            .or_else(|| tracks.iter().position(|track| process_name(track) == process_name(name)))
+            .unwrap();
+        index as u32 + 1
+    };
+    for (index, name) in tracks.iter().enumerate() {
+        trace_events.push(metadata(PROCESSES_TRACK, Some(index as u32 + 1), "thread_name", name));
+    }
+    let mut cores: Vec<usize> = event_list.iter().map(|event| event.core).collect();
+    cores.sort_unstable();
+    cores.dedup();
+    for core in cores {
+        trace_events.push(metadata(CORES_TRACK, Some(core as u32), "thread_name", &format!("Core {}", core)));
+    }
+
+    // Slices.
+    for event in event_list.iter() {
+        let slice = |pid: u32, tid: u32, category: &str| -> String {
+            format!(
+                "{{\"ph\":\"X\",\"pid\":{},\"tid\":{},\"ts\":{},\"dur\":{},\"name\":{},\"cat\":\"{}\",\"args\":{{\"core\":{}}}}}",
+                pid,
+                tid,
+                timestamp(event.start_time),
+                timestamp(event.finish_time) - timestamp(event.start_time),
+                quote(&event.name),
+                category,
+                event.core
+            )
+        };
+        match event.kind {
+            EventKind::Process => {
+                trace_events.push(slice(PROCESSES_TRACK, track(&event.name), "running"));
+                trace_events.push(slice(CORES_TRACK, event.core as u32, "running"));
+            }
+            EventKind::ContextSwitch => trace_events.push(slice(CORES_TRACK, event.core as u32, "context_switch")),
+        }
+    }
+
+    // Instant events from the state histories.
+    for process in finished_processes.iter() {
+        let tid: u32 = track(&format!("P{}", process.pid));
+        let instant = |name: &str, time: f64| -> String {
+            format!(
+                "{{\"ph\":\"i\",\"s\":\"t\",\"pid\":{},\"tid\":{},\"ts\":{},\"name\":\"{}\",\"cat\":\"state\"}}",
+                PROCESSES_TRACK, tid, timestamp(time), name
+            )
+        };
+        trace_events.push(instant("arrival", process.arrival_time));
+        for transition in process.state_history.iter() {
+            match (transition.from, transition.to) {
+                (ProcessState::Running, ProcessState::Ready) => trace_events.push(instant("preempted", transition.time)),
+                (ProcessState::Running, ProcessState::Blocked) => trace_events.push(instant("blocked", transition.time)),
+                (_, ProcessState::Terminated) => trace_events.push(instant("terminated", transition.time)),
+                _ => {}
+            }
+        }
+    }
+
+    // Ready queue length, after all the transitions of each instant.
+    let mut changes: Vec<(i64, i64)> = finished_processes
+        .iter()
+        .flat_map(|process| process.state_history.iter())
+        .map(|transition| {
+            let entered: i64 = (transition.to == ProcessState::Ready) as i64;
+            let left: i64 = (transition.from == ProcessState::Ready) as i64;
+            (timestamp(transition.time), entered - left)
+        })
+        .filter(|&(_, change)| change != 0)
+        .collect();
+    changes.sort_unstable();
+    let mut ready: i64 = 0;
+    for (index, &(time, change)) in changes.iter().enumerate() {
+        ready += change;
+        if changes.get(index + 1).is_none_or(|&(next_time, _)| next_time != time) {
+            trace_events.push(format!(
+                "{{\"ph\":\"C\",\"pid\":{},\"ts\":{},\"name\":\"Ready queue\",\"args\":{{\"ready\":{}}}}}",
+                PROCESSES_TRACK, time, ready
+            ));
+        }
+    }
+
+    format!("{{\"displayTimeUnit\":\"ms\",\"traceEvents\":[\n{}\n]}}\n", trace_events.join(",\n"))
+}
+
+// Test the tracks, slices, instants and the ready queue counter of a round robin schedule.
+#[test]
+fn test() {
+    use crate::cpu_schedulers::ContextSwitch;
+    use crate::schedule::{Algorithm, ScheduleResult};
+
+    let processes: Vec<Process> = vec![Process::new(1, 0.0, 3.0), Process::new(2, 0.0, 2.0), Process::new(3, 1.0, 1.0)];
+    let result: ScheduleResult = Algorithm::Rr { time_quantum: 2.0 }.run(&processes, ContextSwitch::PerSwitch(0.5));
+    let trace: String = write_chrome_trace(&result.finished_processes, &result.event_list);
+    let lines: Vec<&str> = trace.lines().collect();
+    assert!(lines[0].starts_with("{\"displayTimeUnit\":\"ms\",\"traceEvents\":[") && lines.last() == Some(&"]}"));
+    assert!(trace.contains("{\"ph\":\"M\",\"pid\":1,\"tid\":3,\"name\":\"thread_name\",\"args\":{\"name\":\"P3\"}}"));
+    assert!(trace.contains("{\"ph\":\"X\",\"pid\":1,\"tid\":1,\"ts\":500,\"dur\":2000,\"name\":\"P1\",\"cat\":\"running\",\"args\":{\"core\":0}}"));
+    let count = |pattern: &str| trace.matches(pattern).count();
+    let context_switches: usize = result.event_list.iter().filter(|event| event.kind == EventKind::ContextSwitch).count();
+    assert_eq!(count("\"cat\":\"running\""), 2 * (result.event_list.len() - context_switches));
+    assert_eq!(count("\"cat\":\"context_switch\""), context_switches);
+    assert_eq!((count("\"arrival\""), count("\"preempted\""), count("\"terminated\"")), (3, 1, 3));
+
+    // P1 and P2 are ready at 0, P1 runs at 0.5, P3 arrives at 1...
+    let counter: Vec<&str> = lines.iter().filter(|line| line.contains("Ready queue")).copied().collect();
+    assert!(counter[0].contains("\"ts\":0,") && counter[0].contains("{\"ready\":2}"));
+    assert!(counter[1].contains("\"ts\":500,") && counter[1].contains("{\"ready\":1}"));
+    assert!(counter[2].contains("\"ts\":1000,") && counter[2].contains("{\"ready\":2}"));
+    assert!(counter.last().unwrap().contains("{\"ready\":0}"));
+
+    // Threads get their own tracks.
+    let event_list: Vec<Event> = vec![
+        Event { name: String::from("P1.T0"), kind: EventKind::Process, core: 0, start_time: 0.0, finish_time: 1.0 },
+        Event { name: String::from("P1.T1"), kind: EventKind::Process, core: 1, start_time: 0.0, finish_time: 1.0 },
+    ];
+    let trace: String = write_chrome_trace(&[], &event_list);
+    assert!(trace.contains("\"tid\":2,\"name\":\"thread_name\",\"args\":{\"name\":\"P1.T1\"}"));
+    assert!(trace.contains("\"tid\":1,\"name\":\"thread_name\",\"args\":{\"name\":\"Core 1\"}"));
+}
@@ -0,0 +1,377 @@
+use std::io::{self, BufRead, Write};
+
+use crate::cpu_schedulers::{Process, ProcessState, Event, EventKind, ContextSwitch, to_ticks};
+use crate::gantt::{GanttChart, format_time};
+use crate::schedule::{Algorithm, ScheduleResult};
+
+/// Clear the screen and move the cursor to the top left corner.
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+const HELP: &str = "[Enter] next  [p] previous  [s] start  [e] end  [g N] go to step N  [a ALGORITHM] change algorithm  [q] quit";
+
+/// A waiting process at a decision point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waiting {
+    pub pid: u32,
+    pub arrival_time: f64,
+    pub burst_time: f64,
+    pub remaining_time: f64,
+    pub priority: u32,
+}
+
+/// A moment where the dispatcher picks the next process (or finds nothing to run).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    pub time: f64,
+    /// The process given the CPU, `None` when the CPU goes idle or the schedule is over.
+    pub running: Option<u32>,
+    /// The processes left waiting, in the order they entered the ready queue.
+    pub ready_queue: Vec<Waiting>,
+    /// What happened at this time and why the dispatcher picked `running`.
+    pub reason: String,
+}
+
+/// A schedule broken down into its decision points, rebuilt from the timeline and the state histories.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub algorithm: Algorithm,
+    pub result: ScheduleResult,
+    pub decisions: Vec<Decision>,
+}
+
+impl Replay {
+    /// Run the algorithm on a copy of the processes and find its decision points.
+    pub fn new(algorithm: Algorithm, processes: &[Process], context_switch: ContextSwitch) -> Replay {
+        let result: ScheduleResult = algorithm.run(processes, context_switch);
+        let mut replay: Replay = Replay { algorithm, result, decisions: Vec::new() };
+        replay.decisions = replay.find_decisions();
+        replay
+    }
+
+    fn process(&self, pid: u32) -> &Process {
+        self.result.finished_processes.iter().find(|process| process.pid == pid).unwrap()
+    }
+
+    /// Runs of the processes (context switches left out), in time order.
+    fn runs(&self) -> Vec<&Event> {
+        self.result.event_list.iter().filter(|event| event.kind == EventKind::Process).collect()
+    }
+
+    /// State of a process just before the dispatcher decides at `time`:
+    /// every transition up to `time`, except the dispatches made at `time`.
+    fn state_at(&self, process: &Process, time: f64) -> ProcessState {
+        process
+            .state_history
+            .iter()
+            .rev()
+            .find(|transition| {
+                to_ticks(transition.time) < to_ticks(time) || (to_ticks(transition.time) == to_ticks(time) && transition.to != ProcessState::Running)
+            })
+            .map_or(ProcessState::New, |transition| transition.to)
+    }
+
+    /// CPU time left to a process at `time`.
+    fn remaining_time_at(&self, pid: u32, time: f64) -> f64 {
+        let executed_time: f64 = self
+            .runs()
+            .iter()
+            .filter(|event| event.pid() == Some(pid) && event.start_time < time)
+            .map(|event| event.finish_time.min(time) - event.start_time)
+            .sum();
+        ((self.process(pid).burst_time - executed_time) * 100.0).round() / 100.0
+    }
+
+    /// The ready processes at `time`, in the order they entered the ready queue
+    /// (arrivals before a process preempted at the same time, as the schedulers queue them).
+    fn ready_at(&self, time: f64) -> Vec<Waiting> {
+        let mut ready: Vec<(u64, bool, u32)> = self
+            .result
+            .finished_processes
+            .iter()
+            .filter(|process| self.state_at(process, time) == ProcessState::Ready)
+            .map(|process| {
+                let entered = process
+                    .state_history
+                    .iter()
+                    .rev()
+                    .find(|transition| transition.to == ProcessState::Ready && to_ticks(transition.time) <= to_ticks(time))
+                    .unwrap();
+                (to_ticks(entered.time), entered.from == ProcessState::Running, process.pid)
+            })
+            .collect();
+        ready.sort_unstable();
+        ready
+            .into_iter()
+            .map(|(_, _, pid)| {
+                let process: &Process = self.process(pid);
+                Waiting {
+                    pid,
+                    arrival_time: process.arrival_time,
+                    burst_time: process.burst_time,
+                    remaining_time: self.remaining_time_at(pid, time),
+                    priority: process.priority,
+                }
+            })
+            .collect()
+    }
+
+    /// Why `chosen` was picked among the ready processes.
+    fn choice(&self, chosen: &Waiting, ready: &[Waiting]) -> String {
+        if ready.len() == 1 {
+            return format!("P{} is the only ready process.", chosen.pid);
+        }
+        let because: String = match &self.algorithm {
+            Algorithm::Fcfs => format!("arrived first (at {})", format_time(chosen.arrival_time)),
+            Algorithm::Sjf => format!("has the shortest burst ({})", format_time(chosen.burst_time)),
+            Algorithm::Priority | Algorithm::PreemptivePriority => format!("has the highest priority ({})", chosen.priority),
+            Algorithm::PriorityWithAging { .. } => String::from("has the highest priority once aged"),
+            Algorithm::Rr { .. } => String::from("is at the head of the ready queue"),
+            Algorithm::Srtf => format!("has the shortest remaining time ({})", format_time(chosen.remaining_time)),
+            Algorithm::PredictiveSjf { .. } => String::from("has the shortest predicted burst"),
+            Algorithm::PredictiveSrtf { .. } => String::from("has the shortest predicted remaining time"),
+            Algorithm::Mlfq { .. } => String::from("is at the head of the highest non-empty queue"),
+        };
+        format!("P{} {} among {} ready processes.", chosen.pid, because, ready.len())
+    }
+
+    /// What happened at `time` before the decision: arrivals and returns from I/O first, as they may cause the rest.
+    fn happenings(&self, time: f64) -> Vec<String> {
+        let mut happenings: Vec<(usize, String)> = Vec::new();
+        for process in self.result.finished_processes.iter() {
+            for transition in process.state_history.iter().filter(|transition| to_ticks(transition.time) == to_ticks(time)) {
+                match (transition.from, transition.to) {
+                    (ProcessState::New, ProcessState::Ready) => happenings.push((0, format!("P{} arrives.", process.pid))),
+                    (ProcessState::Blocked, ProcessState::Ready) => happenings.push((0, format!("P{} is back from I/O.", process.pid))),
+                    (ProcessState::Running, ProcessState::Ready) => {
+                        let why: &str = match self.algorithm {
+                            Algorithm::Rr { .. } => " (its time quantum expired)",
+                            Algorithm::Mlfq { .. } => " (its time quantum expired or a higher queue has work)",
+                            _ => "",
+                        };
+                        happenings.push((1, format!("P{} is preempted{}.", process.pid, why)));
+                    }
+                    (ProcessState::Running, ProcessState::Blocked) => happenings.push((1, format!("P{} blocks on I/O.", process.pid))),
+                    (_, ProcessState::Terminated) => happenings.push((1, format!("P{} terminates.", process.pid))),
+                    _ => {}
+                }
+            }
+        }
+        happenings.sort_by_key(|(rank, _)| *rank);
+        happenings.into_iter().map(|(_, happening)| happening).collect()
+    }
+
+    fn find_decisions(&self) -> Vec<Decision> {
+        let mut decisions: Vec<Decision> = Vec::new();
+        let runs: Vec<&Event> = self.runs();
+        for (index, run) in runs.iter().enumerate() {
+            // The decision is made when the context switch leading to the run starts.
+            let time: f64 = self
+                .result
+                .event_list
+                .iter()
+                .find(|event| event.kind == EventKind::ContextSwitch && to_ticks(event.finish_time) == to_ticks(run.start_time))
+                .map_or(run.start_time, |event| event.start_time);
+
+            // The CPU went idle after the previous run.
+            if let Some(previous) = index.checked_sub(1).map(|previous| runs[previous]) {
+                if to_ticks(previous.finish_time) < to_ticks(time) && self.ready_at(previous.finish_time).is_empty() {
+                    let mut reason: Vec<String> = self.happenings(previous.finish_time);
+                    reason.push(format!("No process is ready: the CPU is idle until {}.", format_time(time)));
+                    decisions.push(Decision { time: previous.finish_time, running: None, ready_queue: Vec::new(), reason: reason.join(" ") });
+                }
+            }
+
+            let pid: u32 = run.pid().unwrap();
+            let ready: Vec<Waiting> = self.ready_at(time);
+            let mut reason: Vec<String> = self.happenings(time);
+            match ready.iter().find(|waiting| waiting.pid == pid) {
+                Some(chosen) => reason.push(self.choice(chosen, &ready)),
+                None => reason.push(format!("P{} keeps the CPU.", pid)),
+            }
+            let ready_queue: Vec<Waiting> = ready.into_iter().filter(|waiting| waiting.pid != pid).collect();
+            decisions.push(Decision { time, running: Some(pid), ready_queue, reason: reason.join(" ") });
+        }
+
+        let finish_time: f64 = runs.last().map_or(0.0, |run| run.finish_time);
+        let mut reason: Vec<String> = self.happenings(finish_time);
+        reason.push(String::from("Every process has terminated."));
+        decisions.push(Decision { time: finish_time, running: None, ready_queue: Vec::new(), reason: reason.join(" ") });
+        decisions
+    }
+}
+
+// Visualization
+impl Replay {
+    /// The screen of one step: clock, running process, ready queue, reason and the Gantt chart so far.
+    pub fn render(&self, step: usize, width: usize) -> String {
+        let decision: &Decision = &self.decisions[step];
+        let mut screen: String = format!(
+            "{}    step {} of {}    clock {}\n\n",
+            self.result.algorithm,
+            step + 1,
+            self.decisions.len(),
+            format_time(decision.time)
+        );
+        let running: String = decision.running.map_or(String::from("idle"), |pid| format!("P{}", pid));
+        screen += &format!("Running:     {}\n", running);
+        let ready_queue: Vec<String> = decision
+            .ready_queue
+            .iter()
+            .map(|waiting| format!("P{} (remaining {}, priority {})", waiting.pid, format_time(waiting.remaining_time), waiting.priority))
+            .collect();
+        screen += &format!("Ready queue: {}\n", if ready_queue.is_empty() { String::from("empty") } else { ready_queue.join(", ") });
+        screen += &format!("Why:         {}\n\n", decision.reason);
+
+        // The timeline up to the clock.
+        let event_list: Vec<Event> = self
+            .result
+            .event_list
+            .iter()
+            .filter(|event| to_ticks(event.start_time) < to_ticks(decision.time))
+            .map(|event| {
+                let mut event: Event = event.clone();
+                event.finish_time = event.finish_time.min(decision.time);
+                event
+            })
+            .collect();
+        if event_list.is_empty() {
+            screen += "(nothing has run yet)\n";
+        } else {
+            screen += &GanttChart::new().set_width(width).render(&event_list);
+        }
+        screen
+    }
+}
+
+/// Interactive stepping through the decisions of a schedule; the algorithm can be changed on the way.
+pub struct Tui {
+    processes: Vec<Process>,
+    context_switch: ContextSwitch,
+    pub replay: Replay,
+    pub step: usize,
+    pub width: usize,
+    /// Feedback on the last command (an error, for instance).
+    message: String,
+}
+
+impl Tui {
+    pub fn new(processes: Vec<Process>, algorithm: Algorithm, context_switch: ContextSwitch) -> Tui {
+        if processes.is_empty() {
+            panic!("Nothing to schedule!");
+        }
+        let replay: Replay = Replay::new(algorithm, &processes, context_switch);
+        Tui { processes, context_switch, replay, step: 0, width: 80, message: String::new() }
+    }
+
+    pub fn set_width(&mut self, width: usize) -> &mut Tui {
+        self.width = width;
+        self
+    }
+
+    /// Apply one command; returns `false` to quit.
+    pub fn handle(&mut self, command: &str) -> bool {
+        self.message.clear();
+        let last: usize = self.replay.decisions.len() - 1;
+        let (name, argument): (&str, &str) = command.trim().split_once(' ').unwrap_or((command.trim(), ""));
+        match name {
+            "" | "n" => self.step = (self.step + 1).min(last),
+            "p" => self.step = self.step.saturating_sub(1),
+            "s" => self.step = 0,
+            "e" => self.step = last,
+            "g" => match argument.trim().parse::<usize>() {
+                Ok(step) if (1..=last + 1).contains(&step) => self.step = step - 1,
+                _ => self.message = format!("No step `{}` (1 to {}).", argument.trim(), last + 1),
+            },
+            "a" => match argument.parse::<Algorithm>().and_then(|algorithm| {
+                algorithm.check()?;
+                algorithm.check_context_switch(self.context_switch)?;
+                Ok(algorithm)
+            }) {
+                Ok(algorithm) => {
+                    self.replay = Replay::new(algorithm, &self.processes, self.context_switch);
+                    self.step = 0;
+                }
+                Err(error) => self.message = error,
+            },
+            "q" => return false,
+            _ => self.message = format!("Unknown command `{}`.", command.trim()),
+        }
+        true
+    }
+
+    /// The current step, the last message and the key help.
+    pub fn screen(&self) -> String {
+        let mut screen: String = self.replay.render(self.step, self.width);
+        if !self.message.is_empty() {
+            screen += &format!("\n{}\n", self.message);
+        }
+        screen + &format!("\n{}\nAlgorithms: fcfs, sjf, ps, ps_aging RATE, rr QUANTUM, srtf, pps, mlfq QUANTUM...\n> ", HELP)
+    }
+
+    /// Show the screen and read commands, one per line, until `q` or the end of the input.
+    /// With `clear`, the terminal is cleared before every screen.
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write, clear: bool) -> io::Result<()> {
+        let mut lines = input.lines();
+        loop {
+            write!(output, "{}{}", if clear { CLEAR_SCREEN } else { "" }, self.screen())?;
+            output.flush()?;
+            let Some(line) = lines.next() else { break };
+            if !self.handle(&line?) {
+                break;
+            }
+        }
+        writeln!(output)
+    }
+}
+
+// Test the decision points of a round robin schedule and the commands.
+#[test]
+fn test() {
+    let processes: Vec<Process> = vec![Process::new(1, 0.0, 3.0), Process::new(2, 0.0, 2.0), Process::new(3, 1.0, 1.0), Process::new(4, 10.0, 1.0)];
+    let replay: Replay = Replay::new(Algorithm::Rr { time_quantum: 2.0 }, &processes, ContextSwitch::None);
+    let decisions: Vec<(f64, Option<u32>, Vec<u32>)> = replay
+        .decisions
+        .iter()
+        .map(|decision| (decision.time, decision.running, decision.ready_queue.iter().map(|waiting| waiting.pid).collect()))
+        .collect();
+    assert_eq!(
+        decisions,
+        vec![
+            (0.0, Some(1), vec![2]),
+            (2.0, Some(2), vec![3, 1]),
+            (4.0, Some(3), vec![1]),
+            (5.0, Some(1), vec![]),
+            (6.0, None, vec![]),
+            (10.0, Some(4), vec![]),
+            (11.0, None, vec![]),
+        ]
+    );
+    assert_eq!(replay.decisions[0].reason, "P1 arrives. P2 arrives. P1 is at the head of the ready queue among 2 ready processes.");
+    assert_eq!(replay.decisions[1].reason, "P1 is preempted (its time quantum expired). P2 is at the head of the ready queue among 3 ready processes.");
+    assert_eq!(replay.decisions[1].ready_queue[1].remaining_time, 1.0);
+    assert_eq!(replay.decisions[4].reason, "P1 terminates. No process is ready: the CPU is idle until 10.");
+
+    // SRTF names the remaining time, and the decision is made when the context switch starts.
+    let replay: Replay = Replay::new(Algorithm::Srtf, &processes, ContextSwitch::PerSwitch(0.5));
+    assert_eq!((replay.decisions[1].time, replay.decisions[1].running), (1.0, Some(3)));
+    assert_eq!(replay.decisions[1].reason, "P3 arrives. P2 is preempted. P3 has the shortest remaining time (1) among 3 ready processes.");
+    assert_eq!(replay.decisions[2].reason, "P3 terminates. P2 has the shortest remaining time (1.5) among 2 ready processes.");
+
+    let screen: String = replay.render(2, 60);
+    assert!(screen.starts_with("SRTF    step 3 of "));
+    assert!(screen.contains("┌") && !replay.render(0, 60).contains("┌"));
+
+    let mut tui: Tui = Tui::new(processes, Algorithm::Fcfs, ContextSwitch::None);
+    let mut output: Vec<u8> = Vec::new();
+    tui.run("\n\np\ng 9\na rr 2\ne\n".as_bytes(), &mut output, false).unwrap();
+    assert_eq!((tui.replay.result.algorithm.as_str(), tui.step), ("RR (q=2.00)", 6));
+    let output: String = String::from_utf8(output).unwrap();
+    assert!(output.contains("FCFS    step 2 of 6") && output.contains("No step `9` (1 to 6)."));
+    assert!(tui.handle("a rr 0") && tui.screen().contains("Invalid time quantum 0."));
+    assert!(!tui.handle("q"));
+
+    // A typo in the algorithm command shows an error instead of crashing.
+    let mut tui: Tui = Tui::new(vec![Process::new(1, 0.0, 3.0)], Algorithm::Fcfs, ContextSwitch::Fixed(0.5));
+    assert!(tui.handle("a mlfq 2") && tui.screen().contains("MLFQ does not model context switches."));
+    assert_eq!(tui.replay.result.algorithm, "FCFS");
+}
@@ -0,0 +1,283 @@
+use std::fmt;
+
+use crate::cpu_schedulers::{Process, Event, EventKind, ProcessState, ContextSwitch};
+use crate::schedule::{Algorithm, ScheduleResult};
+
+/// Tolerance used when comparing times (the schedulers work in hundredths).
+const EPSILON: f64 = 1e-6;
+
+/// A broken invariant of a schedule.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// A slice ends before it starts, or has no length.
+    EmptySlice { name: String, start_time: f64, finish_time: f64 },
+    /// A slice belongs to no process of the workload.
+    UnknownProcess { name: String },
+    RunsBeforeArrival { pid: u32, arrival_time: f64, start_time: f64 },
+    /// The total run time of a process differs from its burst time.
+    WrongRunTime { pid: u32, burst_time: f64, run_time: f64 },
+    /// Two slices overlap on one core.
+    Overlap { core: usize, first: String, second: String, time: f64 },
+    /// A nonpreemptive algorithm split a CPU burst into several slices.
+    SplitBurst { pid: u32, cpu_bursts: usize, slices: usize },
+    /// The reported waiting time is not turn around time - burst time.
+    InconsistentWaitingTime { pid: u32, waiting_time: f64, expected: f64 },
+    /// A work conserving algorithm left a core idle while a process was ready.
+    IdleWhileReady { core: usize, time: f64, pid: u32 },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::EmptySlice { name, start_time, finish_time } => {
+                write!(f, "{} has an empty slice [{:.2}, {:.2}]", name, start_time, finish_time)
+            }
+            Violation::UnknownProcess { name } => write!(f, "{} is not part of the workload", name),
+            Violation::RunsBeforeArrival { pid, arrival_time, start_time } => {
+                write!(f, "P{} runs at {:.2} but arrives at {:.2}", pid, start_time, arrival_time)
+            }
+            Violation::WrongRunTime { pid, burst_time, run_time } => {
+                write!(f, "P{} runs for {:.2} but its burst time is {:.2}", pid, run_time, burst_time)
+            }
+            Violation::Overlap { core, first, second, time } => {
+                write!(f, "{} and {} overlap on core {} at {:.2}", first, second, core, time)
+            }
+            Violation::SplitBurst { pid, cpu_bursts, slices } => {
+                write!(f, "P{} has {} CPU bursts but runs in {} slices", pid, cpu_bursts, slices)
+            }
+            Violation::InconsistentWaitingTime { pid, waiting_time, expected } => {
+                write!(f, "P{} waits {:.2} but turn around time - burst time is {:.2}", pid, waiting_time, expected)
+            }
+            Violation::IdleWhileReady { core, time, pid } => {
+                write!(f, "core {} is idle at {:.2} while P{} is ready", core, time, pid)
+            }
+        }
+    }
+}
+
+/// Checks the invariants of a timeline produced for a workload.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Validator {
+    /// A CPU burst must run in one slice.
+    pub nonpreemptive: bool,
+    /// A core must not be idle while a process is ready.
+    pub work_conserving: bool,
+}
+
+impl Validator {
+    /// Only the invariants that hold for every algorithm.
+    pub fn new() -> Validator {
+        Validator { nonpreemptive: false, work_conserving: false }
+    }
+
+    /// The invariants of the given algorithm (every algorithm in `Algorithm` is work conserving).
+    pub fn for_algorithm(algorithm: &Algorithm) -> Validator {
+        let nonpreemptive: bool = matches!(
+            algorithm,
+            Algorithm::Fcfs | Algorithm::Sjf | Algorithm::Priority | Algorithm::PriorityWithAging { .. } | Algorithm::PredictiveSjf { .. }
+        );
+        Validator { nonpreemptive, work_conserving: true }
+    }
+
+    /// Every violation found. `workload` gives the arrival and burst times,
+    /// `finished_processes` the waiting and turn around times reported by the scheduler.
+    pub fn validate(&self, workload: &[Process], event_list: &[Event], finished_processes: &[Process]) -> Vec<Violation> {
+        let mut violations: Vec<Violation> = Vec::new();
+
+        // Every slice has a length and belongs to the workload.
+        for event in event_list.iter() {
+            if event.finish_time - event.start_time < EPSILON {
+                violations.push(Violation::EmptySlice {
+                    name: event.name.clone(),
+                    start_time: event.start_time,
+                    finish_time: event.finish_time,
+                });
+            }
+            if event.kind == EventKind::Process && !workload.iter().any(|process| event.pid() == Some(process.pid)) {
+                violations.push(Violation::UnknownProcess { name: event.name.clone() });
+            }
+        }
+
+        // Per process: arrival, run time and number of slices.
+        for process in workload.iter() {
+            let slices: Vec<&Event> = event_list.iter().filter(|event| event.pid() == Some(process.pid)).collect();
+            if let Some(first) = slices.iter().find(|event| event.start_time < process.arrival_time - EPSILON) {
+                violations.push(Violation::RunsBeforeArrival {
+                    pid: process.pid,
+                    arrival_time: process.arrival_time,
+                    start_time: first.start_time,
+                });
+            }
+            let run_time: f64 = slices.iter().map(|event| event.duration()).fold(0.0, |total, time| total + time);
+            if (run_time - process.burst_time).abs() > EPSILON {
+                violations.push(Violation::WrongRunTime { pid: process.pid, burst_time: process.burst_time, run_time });
+            }
+            let cpu_bursts: usize = process.burst_sequence().len().div_ceil(2) * process.number_of_threads();
+            if self.nonpreemptive && slices.len() > cpu_bursts {
+                violations.push(Violation::SplitBurst { pid: process.pid, cpu_bursts, slices: slices.len() });
+            }
+        }
+
+        // Waiting time = turn around time - burst time (a process with I/O also spends time on the devices,
+        // the threads of a process run side by side so only its longest thread counts).
+        for process in finished_processes.iter() {
+            let longest_thread: f64 = process.threads.iter().map(|thread| thread.burst_time).fold(0.0, f64::max);
+            let expected: f64 = process.turn_around_time - if process.threads.is_empty() { process.burst_time } else { longest_thread };
+            let consistent: bool = if process.bursts.is_empty() {
+                (process.waiting_time - expected).abs() < EPSILON
+            } else {
+                process.waiting_time <= expected - process.io_time() + EPSILON
+            };
+            if !consistent {
+                violations.push(Violation::InconsistentWaitingTime { pid: process.pid, waiting_time: process.waiting_time, expected });
+            }
+        }
+
+        // No overlapping slices on a core.
+        let number_of_cores: usize = event_list.iter().map(|event| event.core + 1).max().unwrap_or(0);
+        let mut busy: Vec<Vec<&Event>> = vec![Vec::new(); number_of_cores];
+        for event in event_list.iter() {
+            busy[event.core].push(event);
+        }
+        for (core, events) in busy.iter_mut().enumerate() {
+            events.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+            for pair in events.windows(2) {
+                if pair[1].start_time < pair[0].finish_time - EPSILON {
+                    violations.push(Violation::Overlap {
+                        core,
+                        first: pair[0].name.clone(),
+                        second: pair[1].name.clone(),
+                        time: pair[1].start_time,
+                    });
+                }
+            }
+        }
+
+        // Work conservation: when a core goes idle, and whenever a process arrives or returns from I/O
+        // while it is idle, no process may be ready.
+        if self.work_conserving {
+            let last_slice = |pid: u32| -> f64 {
+                event_list
+                    .iter()
+                    .filter(|event| event.pid() == Some(pid))
+                    .map(|event| event.finish_time)
+                    .fold(f64::NEG_INFINITY, f64::max)
+            };
+            let running = |pid: u32, time: f64| -> bool {
+                event_list.iter().any(|event| {
+                    event.pid() == Some(pid) && event.start_time <= time + EPSILON && time < event.finish_time - EPSILON
+                })
+            };
+            // A process with I/O is ready if the last state it entered by then is Ready (it may be on a device otherwise).
+            let ready_after_io = |pid: u32, time: f64| -> bool {
+                finished_processes
+                    .iter()
+                    .find(|process| process.pid == pid)
+                    .and_then(|process| process.state_history.iter().rev().find(|transition| transition.time <= time + EPSILON))
+                    .is_some_and(|transition| transition.to == ProcessState::Ready)
+            };
+            let mut probe_times: Vec<f64> = vec![0.0];
+            probe_times.extend(workload.iter().map(|process| process.arrival_time));
+            for process in finished_processes.iter() {
+                probe_times.extend(
+                    process
+                        .state_history
+                        .iter()
+                        .filter(|transition| transition.from == ProcessState::Blocked && transition.to == ProcessState::Ready)
+                        .map(|transition| transition.time),
+                );
+            }
+            for (core, events) in busy.iter().enumerate() {
+                // The core is idle from its first free instant until the next slice.
+                let mut idle_times: Vec<f64> = probe_times.clone();
+                idle_times.extend(events.iter().map(|event| event.finish_time));
+                idle_times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                idle_times.dedup_by(|a, b| (*a - *b).abs() < EPSILON);
+                for time in idle_times {
+                    if events.iter().any(|event| event.start_time <= time + EPSILON && time < event.finish_time - EPSILON) {
+                        continue;
+                    }
+                    let ready: Option<&Process> = workload.iter().find(|process| {
+                        process.threads.is_empty()
+                            && process.can_run_on(core)
+                            && process.arrival_time <= time + EPSILON
+                            && last_slice(process.pid) > time + EPSILON
+                            && !running(process.pid, time)
+                            && (process.bursts.is_empty() || ready_after_io(process.pid, time))
+                    });
+                    if let Some(process) = ready {
+                        violations.push(Violation::IdleWhileReady { core, time, pid: process.pid });
+                    }
+                }
+            }
+        }
+
+        violations
+    }
+
+    /// Panic with the list of violations, if any.
+    pub fn assert_valid(&self, workload: &[Process], event_list: &[Event], finished_processes: &[Process]) {
+        let violations: Vec<Violation> = self.validate(workload, event_list, finished_processes);
+        if !violations.is_empty() {
+            let lines: Vec<String> = violations.iter().map(|violation| format!("  {}", violation)).collect();
+            panic!("Invalid schedule:\n{}", lines.join("\n"));
+        }
+    }
+}
+
+impl Default for Validator {
+    fn default() -> Validator {
+        Validator::new()
+    }
+}
+
+/// Run the algorithm and panic if its schedule breaks one of the invariants of the algorithm.
+pub fn run_and_validate(algorithm: &Algorithm, processes: &[Process], context_switch: ContextSwitch) -> ScheduleResult {
+    let result: ScheduleResult = algorithm.run(processes, context_switch);
+    Validator::for_algorithm(algorithm).assert_valid(&algorithm.workload(processes), &result.event_list, &result.finished_processes);
+    result
+}
+
+// Test the validator on a valid schedule and on a broken one.
+#[test]
+fn test() {
+    let workload: Vec<Process> = vec![
+        Process::new(1, 0.0, 3.0),
+        Process::new(2, 1.0, 2.0),
+    ];
+    let result = Algorithm::Fcfs.run(&workload, crate::cpu_schedulers::ContextSwitch::None);
+    let validator: Validator = Validator::for_algorithm(&Algorithm::Fcfs);
+    assert!(validator.validate(&workload, &result.event_list, &result.finished_processes).is_empty());
+
+    // P2 starts before its arrival, overlaps P1, is split and leaves the CPU idle while P1 is ready.
+    let slice = |name: &str, start_time: f64, finish_time: f64| -> Event {
+        Event { name: name.to_string(), kind: EventKind::Process, core: 0, start_time, finish_time }
+    };
+    let event_list: Vec<Event> = vec![
+        slice("P2", 0.5, 1.5),
+        slice("P1", 1.0, 2.0),
+        slice("P2", 2.0, 3.0),
+        slice("P1", 4.0, 6.0),
+    ];
+    let violations: Vec<Violation> = validator.validate(&workload, &event_list, &[]);
+    assert_eq!(violations, vec![
+        Violation::SplitBurst { pid: 1, cpu_bursts: 1, slices: 2 },
+        Violation::RunsBeforeArrival { pid: 2, arrival_time: 1.0, start_time: 0.5 },
+        Violation::SplitBurst { pid: 2, cpu_bursts: 1, slices: 2 },
+        Violation::Overlap { core: 0, first: "P2".to_string(), second: "P1".to_string(), time: 1.0 },
+        Violation::IdleWhileReady { core: 0, time: 0.0, pid: 1 },
+        Violation::IdleWhileReady { core: 0, time: 3.0, pid: 1 },
+    ]);
+    // The core goes idle at 2, P2 arrives at 3 during the idle gap but only starts at 5.
+    let workload: Vec<Process> = vec![
+        Process::new(1, 0.0, 2.0),
+        Process::new(2, 3.0, 1.0),
+    ];
+    let event_list: Vec<Event> = vec![
+        slice("P1", 0.0, 2.0),
+        slice("P2", 5.0, 6.0),
+    ];
+    assert_eq!(validator.validate(&workload, &event_list, &[]), vec![
+        Violation::IdleWhileReady { core: 0, time: 3.0, pid: 2 },
+    ]);
+}
@@ -0,0 +1,341 @@
+use crate::cpu_schedulers::{Process, Burst};
+
+/// SplitMix64 pseudo random number generator.
+/// Kept in the crate so that a seed gives the same workload on every platform and version.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z: u64 = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in [min, max).
+    pub fn uniform(&mut self, min: f64, max: f64) -> f64 {
+        min + (max - min) * self.next_f64()
+    }
+
+    /// Uniform integer in [min, max].
+    pub fn uniform_integer(&mut self, min: u64, max: u64) -> u64 {
+        min + self.next_u64() % (max - min + 1)
+    }
+
+    /// Exponential with the given mean.
+    pub fn exponential(&mut self, mean: f64) -> f64 {
+        -mean * (1.0 - self.next_f64()).ln()
+    }
+}
+
+/// Round a time to two decimal places, as required by `Process`.
+pub fn round_time(time: f64) -> f64 {
+    (time * 100.0).round() / 100.0
+}
+
+/// Largest Pareto sample: small shapes would otherwise overflow to infinity.
+pub const MAX_PARETO_BURST: f64 = 1e6;
+
+/// Distribution of burst times. Samples are rounded to two decimal places and are at least 0.01.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BurstDistribution {
+    Constant(f64),
+    Exponential { mean: f64 },
+    Uniform { min: f64, max: f64 },
+    /// A mix of short and long (exponential) bursts: a `long_fraction` of the bursts have the long mean.
+    Bimodal { short_mean: f64, long_mean: f64, long_fraction: f64 },
+    /// Heavy tailed: P(X > x) = (scale / x)^shape for x ≥ scale, capped at `MAX_PARETO_BURST`.
+    Pareto { scale: f64, shape: f64 },
+}
+
+impl BurstDistribution {
+    pub fn sample(&self, rng: &mut Rng) -> f64 {
+        let time: f64 = match *self {
+            BurstDistribution::Constant(time) => time,
+            BurstDistribution::Exponential { mean } => rng.exponential(mean),
+            BurstDistribution::Uniform { min, max } => rng.uniform(min, max),
+            BurstDistribution::Bimodal { short_mean, long_mean, long_fraction } => {
+                if rng.next_f64() < long_fraction { rng.exponential(long_mean) } else { rng.exponential(short_mean) }
+            }
+            BurstDistribution::Pareto { scale, shape } => {
+                (scale * (1.0 - rng.next_f64()).powf(-1.0 / shape)).min(MAX_PARETO_BURST)
+            }
+        };
+        round_time(time).max(0.01)
+    }
+
+    /// Mean of the distribution (before rounding and capping); infinite for a Pareto shape ≤ 1.
+    pub fn mean(&self) -> f64 {
+        match *self {
+            BurstDistribution::Constant(time) => time,
+            BurstDistribution::Exponential { mean } => mean,
+            BurstDistribution::Uniform { min, max } => (min + max) / 2.0,
+            BurstDistribution::Bimodal { short_mean, long_mean, long_fraction } => {
+                long_fraction * long_mean + (1.0 - long_fraction) * short_mean
+            }
+            BurstDistribution::Pareto { scale, shape } => if shape > 1.0 { shape * scale / (shape - 1.0) } else { f64::INFINITY },
+        }
+    }
+
+    /// Check that the parameters are finite and describe a distribution of positive times.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let valid: bool = match *self {
+            BurstDistribution::Constant(time) => time.is_finite() && time > 0.0,
+            BurstDistribution::Exponential { mean } => mean.is_finite() && mean > 0.0,
+            BurstDistribution::Uniform { min, max } => min >= 0.0 && max.is_finite() && max > min,
+            BurstDistribution::Bimodal { short_mean, long_mean, long_fraction } => {
+                short_mean.is_finite() && long_mean.is_finite() && short_mean > 0.0 && long_mean > 0.0 && (0.0..=1.0).contains(&long_fraction)
+            }
+            BurstDistribution::Pareto { scale, shape } => shape.is_finite() && scale > 0.0 && scale <= MAX_PARETO_BURST && shape > 0.0,
+        };
+        if valid { Ok(()) } else { Err(format!("Invalid burst distribution: {:?}", self)) }
+    }
+
+    fn check(&self) {
+        if let Err(error) = self.validate() {
+            panic!("{}", error);
+        }
+    }
+}
+
+/// How the processes arrive.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Arrivals {
+    /// Every process arrives at time 0.
+    Simultaneous,
+    /// Poisson process: exponential inter-arrival times with mean 1 / `rate`.
+    Poisson { rate: f64 },
+}
+
+impl Arrivals {
+    /// Check that the arrival rate is a finite positive number.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        match *self {
+            Arrivals::Poisson { rate } if !rate.is_finite() || rate <= 0.0 => Err(String::from("Arrival rate must be a finite positive number.")),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Distribution of priorities (smaller is more important).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PriorityDistribution {
+    Constant(u32),
+    /// Uniform in [min, max].
+    Uniform { min: u32, max: u32 },
+    /// (priority, weight) pairs.
+    Weighted(Vec<(u32, f64)>),
+}
+
+impl PriorityDistribution {
+    pub fn sample(&self, rng: &mut Rng) -> u32 {
+        match self {
+            PriorityDistribution::Constant(priority) => *priority,
+            PriorityDistribution::Uniform { min, max } => rng.uniform_integer(*min as u64, *max as u64) as u32,
+            PriorityDistribution::Weighted(weights) => {
+                let total: f64 = weights.iter().map(|(_, weight)| weight).sum();
+                let mut target: f64 = rng.uniform(0.0, total);
+                for &(priority, weight) in weights.iter() {
+                    if target < weight {
+                        return priority;
+                    }
+                    target -= weight;
+                }
+                weights.last().unwrap().0
+            }
+        }
+    }
+
+    fn check(&self) {
+        let valid: bool = match self {
+            PriorityDistribution::Constant(_) => true,
+            PriorityDistribution::Uniform { min, max } => min <= max,
+            PriorityDistribution::Weighted(weights) => {
+                !weights.is_empty() && weights.iter().all(|(_, weight)| *weight >= 0.0) && weights.iter().any(|(_, weight)| *weight > 0.0)
+            }
+        };
+        if !valid {
+            panic!("Invalid priority distribution: {:?}", self);
+        }
+    }
+}
+
+/// Share of I/O bound processes and the shape of their burst sequences.
+/// CPU bound processes get a single CPU burst from the generator burst distribution.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IoMix {
+    /// Fraction of the processes that are I/O bound.
+    pub io_bound_fraction: f64,
+    /// Number of CPU bursts of an I/O bound process (with one I/O burst between two CPU bursts).
+    pub cpu_bursts: usize,
+    pub cpu_burst: BurstDistribution,
+    pub io_burst: BurstDistribution,
+    /// The I/O bursts use a device picked uniformly among `number_of_devices`.
+    pub number_of_devices: usize,
+}
+
+/// Reproducible generator of `Process` lists.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WorkloadGenerator {
+    pub seed: u64,
+    pub number_of_processes: usize,
+    pub arrivals: Arrivals,
+    pub bursts: BurstDistribution,
+    pub priorities: PriorityDistribution,
+    pub io_mix: Option<IoMix>,
+}
+
+impl WorkloadGenerator {
+    /// Poisson arrivals (one every 2 time units on average), exponential bursts with mean 5, all priorities 0, no I/O.
+    pub fn new(seed: u64, number_of_processes: usize) -> WorkloadGenerator {
+        WorkloadGenerator {
+            seed,
+            number_of_processes,
+            arrivals: Arrivals::Poisson { rate: 0.5 },
+            bursts: BurstDistribution::Exponential { mean: 5.0 },
+            priorities: PriorityDistribution::Constant(0),
+            io_mix: None,
+        }
+    }
+
+    pub fn set_arrivals(&mut self, arrivals: Arrivals) {
+        if let Err(error) = arrivals.validate() {
+            panic!("{}", error);
+        }
+        self.arrivals = arrivals;
+    }
+
+    pub fn set_bursts(&mut self, bursts: BurstDistribution) {
+        bursts.check();
+        self.bursts = bursts;
+    }
+
+    pub fn set_priorities(&mut self, priorities: PriorityDistribution) {
+        priorities.check();
+        self.priorities = priorities;
+    }
+
+    pub fn set_io_mix(&mut self, io_mix: IoMix) {
+        if !(0.0..=1.0).contains(&io_mix.io_bound_fraction) || io_mix.cpu_bursts == 0 || io_mix.number_of_devices == 0 {
+            panic!("Invalid I/O mix: {:?}", io_mix);
+        }
+        io_mix.cpu_burst.check();
+        io_mix.io_burst.check();
+        self.io_mix = Some(io_mix);
+    }
+
+    /// Generate the processes, with pids 1, 2, ... in arrival order.
+    pub fn generate(&self) -> Vec<Process> {
+        self.generate_with_seed(self.seed)
+    }
+
+    /// Same as `generate`, with another seed.
+    pub fn generate_with_seed(&self, seed: u64) -> Vec<Process> {
+        let mut rng: Rng = Rng::new(seed);
+        let mut arrival_time: f64 = 0.0;
+        let mut processes: Vec<Process> = Vec::new();
+        for pid in 1..=self.number_of_processes as u32 {
+            if let Arrivals::Poisson { rate } = self.arrivals {
+                // Keep the exact time between arrivals, round the arrival itself.
+                if pid > 1 {
+                    arrival_time += rng.exponential(1.0 / rate);
+                }
+            }
+            let rounded_arrival_time: f64 = round_time(arrival_time);
+            let priority: u32 = self.priorities.sample(&mut rng);
+
+            let io_bound: Option<&IoMix> = self.io_mix.as_ref().filter(|io_mix| rng.next_f64() < io_mix.io_bound_fraction);
+            let mut process: Process = match io_bound {
+                Some(io_mix) => {
+                    let mut bursts: Vec<Burst> = Vec::new();
+                    for i in 0..io_mix.cpu_bursts {
+                        if i > 0 {
+                            let device: usize = rng.uniform_integer(0, io_mix.number_of_devices as u64 - 1) as usize;
+                            bursts.push(Burst::Io { device, time: io_mix.io_burst.sample(&mut rng) });
+                        }
+                        bursts.push(Burst::Cpu(io_mix.cpu_burst.sample(&mut rng)));
+                    }
+                    Process::new_with_bursts(pid, rounded_arrival_time, bursts)
+                }
+                None => Process::new(pid, rounded_arrival_time, self.bursts.sample(&mut rng)),
+            };
+            process.priority = priority;
+            processes.push(process);
+        }
+        processes
+    }
+}
+
+// Test the reproducibility and the shape of the generated workloads.
+#[test]
+fn test() {
+    let mut generator: WorkloadGenerator = WorkloadGenerator::new(42, 2000);
+    generator.set_priorities(PriorityDistribution::Uniform { min: 1, max: 5 });
+    let processes: Vec<Process> = generator.generate();
+    let again: Vec<Process> = generator.generate();
+    assert_eq!(format!("{:?}", processes), format!("{:?}", again));
+    assert_ne!(format!("{:?}", processes), format!("{:?}", generator.generate_with_seed(43)));
+
+    // Two decimal places, arrival order, and means close to the parameters.
+    for process in processes.iter() {
+        assert_eq!(round_time(process.arrival_time), process.arrival_time);
+        assert_eq!(round_time(process.burst_time), process.burst_time);
+        assert!((1..=5).contains(&process.priority));
+    }
+    assert!(processes.windows(2).all(|pair| pair[0].arrival_time <= pair[1].arrival_time));
+    let mean_burst: f64 = processes.iter().map(|process| process.burst_time).sum::<f64>() / 2000.0;
+    assert!((mean_burst - 5.0).abs() < 0.5);
+    let mean_gap: f64 = processes.last().unwrap().arrival_time / 1999.0;
+    assert!((mean_gap - 2.0).abs() < 0.2);
+
+    // Heavy tail: the largest Pareto burst is far above the mean.
+    generator.set_bursts(BurstDistribution::Pareto { scale: 1.0, shape: 1.5 });
+    let largest: f64 = generator.generate().iter().map(|process| process.burst_time).fold(0.0, f64::max);
+    assert!(largest > 10.0 * generator.bursts.mean());
+
+    // A tiny shape overflows to infinity without the cap.
+    generator.set_bursts(BurstDistribution::Pareto { scale: 1.0, shape: 0.01 });
+    let capped: Vec<Process> = generator.generate();
+    assert!(capped.iter().all(|process| process.burst_time <= MAX_PARETO_BURST));
+    assert!(capped.iter().any(|process| process.burst_time == MAX_PARETO_BURST));
+
+    // I/O mix: about half of the processes get a burst sequence.
+    generator.set_io_mix(IoMix {
+        io_bound_fraction: 0.5,
+        cpu_bursts: 3,
+        cpu_burst: BurstDistribution::Uniform { min: 0.5, max: 1.5 },
+        io_burst: BurstDistribution::Exponential { mean: 4.0 },
+        number_of_devices: 2,
+    });
+    let io_bound: usize = generator.generate().iter().filter(|process| process.burst_sequence().len() == 5).count();
+    assert!((900..1100).contains(&io_bound));
+
+    // Infinite and NaN parameters are rejected.
+    assert!(Arrivals::Poisson { rate: f64::NAN }.validate().is_err());
+    assert!(Arrivals::Poisson { rate: f64::INFINITY }.validate().is_err());
+    assert!(BurstDistribution::Exponential { mean: f64::INFINITY }.validate().is_err());
+    assert!(BurstDistribution::Uniform { min: 1.0, max: f64::INFINITY }.validate().is_err());
+    assert!(BurstDistribution::Pareto { scale: f64::NAN, shape: 1.5 }.validate().is_err());
+    assert!(BurstDistribution::Pareto { scale: 2.0 * MAX_PARETO_BURST, shape: 1.5 }.validate().is_err());
+}